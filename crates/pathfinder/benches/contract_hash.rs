@@ -0,0 +1,35 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use pathfinder_lib::state::{compute_contract_hash, contract_hash_bench};
+
+pub fn criterion_benchmark(c: &mut Criterion) {
+    // The same ~500kB fixture as used in the contract hash test vectors.
+    let contract_definition = zstd::decode_all(
+        &include_bytes!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/fixtures/contract_definition.json.zst"
+        ))[..],
+    )
+    .unwrap();
+
+    let mut group = c.benchmark_group("compute_contract_hash");
+    // Report contracts/s, which is what we care about during sync.
+    group.throughput(Throughput::Elements(1));
+
+    group.bench_function("parse", |b| {
+        b.iter(|| contract_hash_bench::parse(black_box(&contract_definition)).unwrap());
+    });
+    group.bench_function("parse_and_keccak", |b| {
+        b.iter(|| contract_hash_bench::keccak(black_box(&contract_definition)).unwrap());
+    });
+    group.bench_function("parse_and_hash_chains", |b| {
+        b.iter(|| contract_hash_bench::hash_chains(black_box(&contract_definition)).unwrap());
+    });
+    group.bench_function("full", |b| {
+        b.iter(|| compute_contract_hash(black_box(&contract_definition)).unwrap());
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
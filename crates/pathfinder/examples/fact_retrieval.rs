@@ -29,7 +29,7 @@ use pathfinder_lib::{
     core::{EthereumBlockHash, StarknetBlockNumber},
     ethereum::{
         log::{MetaLog, StateUpdateLog},
-        state_update::StateUpdate,
+        state_update::{StateUpdate, DEFAULT_MAX_MEMORY_PAGES_PER_FACT},
     },
 };
 use web3::types::{H256, U256};
@@ -37,7 +37,7 @@ use web3::{transports::Http, types::FilterBuilder, Web3};
 
 #[tokio::main]
 async fn main() {
-    let (transport, block_hash, block_no) = parse_cli_args();
+    let (transport, block_hash, block_no, max_pages) = parse_cli_args();
 
     let chain = pathfinder_lib::ethereum::chain(&transport)
         .await
@@ -57,7 +57,7 @@ async fn main() {
         .find(|log| log.block_number == block_no)
         .expect("state update log not found");
 
-    let state_update = StateUpdate::retrieve(&transport, update_log, chain)
+    let state_update = StateUpdate::retrieve(&transport, update_log, chain, max_pages)
         .await
         .expect("Failed to retrieve the state update");
 
@@ -65,7 +65,7 @@ async fn main() {
 }
 
 /// Creates the CLI and parses the resulting arguments.
-fn parse_cli_args() -> (Web3<Http>, EthereumBlockHash, StarknetBlockNumber) {
+fn parse_cli_args() -> (Web3<Http>, EthereumBlockHash, StarknetBlockNumber, usize) {
     let cli = clap::App::new("fact-retrieval")
         .about("Retrieves and displays a StarkNet state update fact")
         .after_help("You can use Etherscan to identify a fact hash to retrieve. The fact hash for a state update is emitted as a `LogStateTransitionFact` log.")
@@ -95,7 +95,14 @@ fn parse_cli_args() -> (Web3<Http>, EthereumBlockHash, StarknetBlockNumber) {
 
 Examples:
     infura: https://goerli.infura.io/v3/<PROJECT_ID>
-    geth:   https://localhost:8545"#));
+    geth:   https://localhost:8545"#))
+        .arg(
+            Arg::with_name("max-pages")
+                .long("max-memory-pages")
+                .takes_value(true)
+                .value_name("INT")
+                .help("Facts listing more memory pages than this are rejected without fetching them.")
+        );
 
     let args = cli.get_matches();
 
@@ -111,8 +118,12 @@ Examples:
     let block = EthereumBlockHash(block);
     let seq_no = U256::from_dec_str(seq_no).expect("A valid sequence number");
     let seq_no = StarknetBlockNumber(seq_no.as_u64());
+    let max_pages = args
+        .value_of("max-pages")
+        .map(|max| max.parse().expect("A valid memory page count"))
+        .unwrap_or(DEFAULT_MAX_MEMORY_PAGES_PER_FACT);
 
     let client = Web3::new(client);
 
-    (client, block, seq_no)
+    (client, block, seq_no, max_pages)
 }
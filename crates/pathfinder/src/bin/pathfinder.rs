@@ -2,7 +2,9 @@ use anyhow::Context;
 use pathfinder_lib::{
     cairo,
     config::{self, EthereumConfig},
-    ethereum, rpc, sequencer, state,
+    ethereum,
+    retry::RetryBudget,
+    rpc, sequencer, state,
     storage::Storage,
 };
 use std::sync::Arc;
@@ -21,11 +23,20 @@ async fn main() -> anyhow::Result<()> {
         config::Configuration::parse_cmd_line_and_cfg_file().context("Parsing configuration")?;
 
     info!("🏁 Starting node.");
-    let eth_transport = ethereum_transport(config.ethereum)
+    let retry_decoder_errors = config.ethereum.retry_spurious_decoder_errors;
+    let startup_reorg_buffer = config.startup_reorg_buffer;
+    let eth_chain_id = config.ethereum.chain_id;
+    let ca_bundle = config
+        .tls_ca_bundle
+        .as_deref()
+        .map(config::CaBundle::load)
+        .transpose()
+        .context("Loading TLS CA bundle")?;
+    let eth_transport = ethereum_transport(config.ethereum, ca_bundle.as_ref())
         .await
         .context("Creating Ethereum transport")?;
 
-    let network_chain = ethereum::chain(&eth_transport)
+    let network_chain = ethereum::chain_with_override(&eth_transport, eth_chain_id)
         .await
         .context("Determining Ethereum chain")?;
 
@@ -35,15 +46,32 @@ async fn main() -> anyhow::Result<()> {
     };
 
     let storage = Storage::migrate(database_path.into()).unwrap();
-    let sequencer = sequencer::Client::new(network_chain).unwrap();
+    // Bounds the retries of the Ethereum and sequencer clients together.
+    let retry_budget = RetryBudget::default();
+    let sequencer = sequencer::Client::builder(network_chain)
+        .timeout(config.sequencer_timeout)
+        .pool(config.sequencer_pool)
+        .retry_budget(retry_budget.clone());
+    let sequencer = match &ca_bundle {
+        Some(ca_bundle) => sequencer.ca_bundle(ca_bundle),
+        None => sequencer,
+    }
+    .build()
+    .context("Creating sequencer client")?;
     let sync_state = Arc::new(state::SyncState::default());
 
-    let sync_handle = tokio::spawn(state::sync(
+    let (shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+    let mut sync_handle = tokio::spawn(state::sync(
         storage.clone(),
-        eth_transport,
+        eth_transport.clone(),
         network_chain,
         sequencer.clone(),
         sync_state.clone(),
+        retry_decoder_errors,
+        retry_budget,
+        startup_reorg_buffer,
+        config.sync_source,
+        shutdown_rx,
     ));
 
     // TODO: the error could be recovered, but currently it's required for startup. There should
@@ -59,29 +87,52 @@ async fn main() -> anyhow::Result<()> {
     )?;
 
     let api = rpc::api::RpcApi::new(storage, sequencer, network_chain, sync_state)
-        .with_call_handling(call_handle);
+        .with_call_handling(call_handle)
+        .with_eth_transport(eth_transport)
+        .with_number_format(config.rpc_number_format);
 
     let (rpc_handle, local_addr) =
-        rpc::run_server(config.http_rpc_addr, api).context("Starting the RPC server")?;
+        rpc::run_server_with_limits(config.http_rpc_addr, api, config.rpc_limits)
+            .context("Starting the RPC server")?;
     info!("📡 HTTP-RPC server started on: {}", local_addr);
 
     // Monitor our spawned process tasks.
-    tokio::select! {
-        result = sync_handle => {
+    let shutdown = tokio::select! {
+        result = &mut sync_handle => {
             match result {
                 Ok(task_result) => tracing::error!("Sync process ended unexpected with: {:?}", task_result),
                 Err(err) => tracing::error!("Sync process ended unexpected; failed to join task handle: {:?}", err),
             }
+            false
         }
         result = cairo_handle => {
             match result {
                 Ok(task_result) => tracing::error!("Cairo process ended unexpected with: {:?}", task_result),
                 Err(err) => tracing::error!("Cairo process ended unexpected; failed to join task handle: {:?}", err),
             }
+            true
         }
         _result = rpc_handle => {
             // This handle returns () so its not very useful.
             tracing::error!("RPC server process ended unexpected");
+            true
+        }
+        result = tokio::signal::ctrl_c() => {
+            if let Err(err) = result {
+                tracing::error!("Failed to listen for the shutdown signal: {:?}", err);
+            }
+            info!("Shutting down");
+            true
+        }
+    };
+
+    if shutdown {
+        // Stop syncing gracefully, so that no L1 or L2 update is left half-applied.
+        let _ = shutdown_tx.send(true);
+        match sync_handle.await {
+            Ok(Ok(())) => {}
+            Ok(Err(err)) => tracing::error!("Sync process stopped with: {:?}", err),
+            Err(err) => tracing::error!("Failed to join sync process handle: {:?}", err),
         }
     }
 
@@ -94,8 +145,16 @@ async fn main() -> anyhow::Result<()> {
 /// - the [Url](reqwest::Url)
 /// - the user-agent (if provided)
 /// - the password (if provided)
-async fn ethereum_transport(config: EthereumConfig) -> anyhow::Result<Web3<Http>> {
+/// - the trusted root certificates (if provided)
+async fn ethereum_transport(
+    config: EthereumConfig,
+    ca_bundle: Option<&config::CaBundle>,
+) -> anyhow::Result<Web3<Http>> {
     let client = reqwest::Client::builder();
+    let client = match ca_bundle {
+        Some(ca_bundle) => ca_bundle.apply(client),
+        None => client,
+    };
     let client = match config.user {
         Some(user_agent) => client.user_agent(user_agent),
         None => client,
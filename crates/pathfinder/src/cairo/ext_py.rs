@@ -234,10 +234,7 @@ mod tests {
     }
 
     fn fill_example_state(tx: &rusqlite::Transaction) {
-        let contract_definition = zstd::decode_all(std::io::Cursor::new(include_bytes!(
-            "../../fixtures/contract_definition.json.zst"
-        )))
-        .unwrap();
+        let contract_definition = crate::test_utils::contract_definition_fixture();
 
         let address = StarkHash::from_hex_str(
             "057dde83c18c0efe7123c36a52d704cf27d5c38cdf0b1e1edc3b0dae3ee4e374",
@@ -1,36 +1,346 @@
 //! Contains the node configuration parsing code.
 mod builder;
 mod cli;
+mod env;
 mod file;
 
-use std::{fmt::Display, net::SocketAddr, path::PathBuf, str::FromStr};
+use std::{fmt::Display, net::SocketAddr, path::PathBuf, str::FromStr, time::Duration};
 
 use enum_iterator::IntoEnumIterator;
 use reqwest::Url;
 
 const DEFAULT_HTTP_RPC_ADDR: &str = "127.0.0.1:9545";
+const DEFAULT_STARTUP_REORG_BUFFER: u64 = 0;
 
 /// Possible configuration options.
 #[derive(Debug, PartialEq, Clone, Copy, Hash, Eq, IntoEnumIterator)]
 pub enum ConfigOption {
     /// The Ethereum URL.
     EthereumHttpUrl,
-    /// The Ethereum user.
+    /// The User-Agent sent to the Ethereum endpoint.
     EthereumUser,
     /// The Ethereum password.
     EthereumPassword,
+    /// The Ethereum chain ID, overriding the one reported by the Ethereum endpoint.
+    EthereumChainId,
     /// The HTTP-RPC listening socket address.
     HttpRpcAddress,
+    /// The maximum size of a single HTTP-RPC request body.
+    RpcMaxRequestBytes,
+    /// The timeout of a single HTTP-RPC request.
+    RpcRequestTimeout,
+    /// Whether HTTP-RPC replies render numbers as hex or decimal strings.
+    RpcNumberFormat,
+    /// Whether to retry the spurious log decoder errors of some Ethereum endpoints.
+    RetrySpuriousDecoderErrors,
+    /// The timeout of a single sequencer request.
+    SequencerTimeout,
+    /// The maximum number of idle sequencer connections kept open.
+    SequencerPoolMaxIdle,
+    /// How long idle sequencer connections are kept open.
+    SequencerPoolIdleTimeout,
+    /// The [SyncSource] of the node.
+    SyncSource,
+    /// The number of most recent Ethereum blocks whose L1 state updates are re-scanned on startup.
+    StartupReorgBuffer,
+    /// The PEM file of the root certificates trusted for HTTPS endpoints.
+    TlsCaBundle,
 }
 
 impl Display for ConfigOption {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             ConfigOption::EthereumHttpUrl => f.write_str("Ethereum HTTP URL"),
-            ConfigOption::EthereumUser => f.write_str("Ethereum user"),
+            ConfigOption::EthereumUser => f.write_str("Ethereum user-agent"),
             ConfigOption::EthereumPassword => f.write_str("Ethereum password"),
+            ConfigOption::EthereumChainId => f.write_str("Ethereum chain ID"),
             ConfigOption::HttpRpcAddress => f.write_str("HTTP-RPC socket address"),
+            ConfigOption::RpcMaxRequestBytes => f.write_str("HTTP-RPC maximum request size"),
+            ConfigOption::RpcRequestTimeout => f.write_str("HTTP-RPC request timeout"),
+            ConfigOption::RpcNumberFormat => f.write_str("HTTP-RPC number format"),
+            ConfigOption::RetrySpuriousDecoderErrors => {
+                f.write_str("Retry spurious Ethereum decoder errors")
+            }
+            ConfigOption::SequencerTimeout => f.write_str("Sequencer request timeout"),
+            ConfigOption::SequencerPoolMaxIdle => f.write_str("Sequencer maximum idle connections"),
+            ConfigOption::SequencerPoolIdleTimeout => {
+                f.write_str("Sequencer idle connection timeout")
+            }
+            ConfigOption::SyncSource => f.write_str("Sync source"),
+            ConfigOption::StartupReorgBuffer => f.write_str("Startup reorg buffer"),
+            ConfigOption::TlsCaBundle => f.write_str("TLS CA bundle"),
+        }
+    }
+}
+
+/// Describes how a [ConfigOption] is specified by the user.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ConfigOptionInfo {
+    /// The option being described.
+    pub option: ConfigOption,
+    /// The (dotted) key of the option in the configuration file.
+    pub toml_key: &'static str,
+    /// The environment variable name of the option.
+    pub env_var: &'static str,
+    /// The long command-line flag of the option, without the leading `--`.
+    pub cli_flag: &'static str,
+    /// Whether the value is sensitive, and should not be displayed or logged.
+    pub secret: bool,
+    /// Whether the option must be specified for the configuration to be valid.
+    pub required: bool,
+}
+
+const OPTIONS: &[ConfigOptionInfo] = &[
+    ConfigOptionInfo {
+        option: ConfigOption::EthereumHttpUrl,
+        toml_key: "ethereum.url",
+        env_var: "PATHFINDER_ETHEREUM_URL",
+        cli_flag: "ethereum.url",
+        secret: false,
+        required: true,
+    },
+    ConfigOptionInfo {
+        option: ConfigOption::EthereumUser,
+        toml_key: "ethereum.user",
+        env_var: "PATHFINDER_ETHEREUM_USER",
+        cli_flag: "ethereum.user",
+        secret: false,
+        required: false,
+    },
+    ConfigOptionInfo {
+        option: ConfigOption::EthereumPassword,
+        toml_key: "ethereum.password",
+        env_var: "PATHFINDER_ETHEREUM_PASSWORD",
+        cli_flag: "ethereum.password",
+        secret: true,
+        required: false,
+    },
+    ConfigOptionInfo {
+        option: ConfigOption::EthereumChainId,
+        toml_key: "ethereum.chain-id",
+        env_var: "PATHFINDER_ETHEREUM_CHAIN_ID",
+        cli_flag: "ethereum.chain-id",
+        secret: false,
+        required: false,
+    },
+    ConfigOptionInfo {
+        option: ConfigOption::HttpRpcAddress,
+        toml_key: "http-rpc",
+        env_var: "PATHFINDER_HTTP_RPC",
+        cli_flag: "http-rpc",
+        secret: false,
+        required: false,
+    },
+    ConfigOptionInfo {
+        option: ConfigOption::RpcMaxRequestBytes,
+        toml_key: "rpc.max-request-bytes",
+        env_var: "PATHFINDER_RPC_MAX_REQUEST_BYTES",
+        cli_flag: "rpc.max-request-bytes",
+        secret: false,
+        required: false,
+    },
+    ConfigOptionInfo {
+        option: ConfigOption::RpcRequestTimeout,
+        toml_key: "rpc.request-timeout",
+        env_var: "PATHFINDER_RPC_REQUEST_TIMEOUT",
+        cli_flag: "rpc.request-timeout",
+        secret: false,
+        required: false,
+    },
+    ConfigOptionInfo {
+        option: ConfigOption::RpcNumberFormat,
+        toml_key: "rpc.number-format",
+        env_var: "PATHFINDER_RPC_NUMBER_FORMAT",
+        cli_flag: "rpc.number-format",
+        secret: false,
+        required: false,
+    },
+    ConfigOptionInfo {
+        option: ConfigOption::RetrySpuriousDecoderErrors,
+        toml_key: "ethereum.retry-spurious-decoder-errors",
+        env_var: "PATHFINDER_ETHEREUM_RETRY_SPURIOUS_DECODER_ERRORS",
+        cli_flag: "ethereum.retry-spurious-decoder-errors",
+        secret: false,
+        required: false,
+    },
+    ConfigOptionInfo {
+        option: ConfigOption::SequencerTimeout,
+        toml_key: "sequencer.timeout",
+        env_var: "PATHFINDER_SEQUENCER_TIMEOUT",
+        cli_flag: "sequencer.timeout",
+        secret: false,
+        required: false,
+    },
+    ConfigOptionInfo {
+        option: ConfigOption::SequencerPoolMaxIdle,
+        toml_key: "sequencer.pool-max-idle-per-host",
+        env_var: "PATHFINDER_SEQUENCER_POOL_MAX_IDLE_PER_HOST",
+        cli_flag: "sequencer.pool-max-idle-per-host",
+        secret: false,
+        required: false,
+    },
+    ConfigOptionInfo {
+        option: ConfigOption::SequencerPoolIdleTimeout,
+        toml_key: "sequencer.pool-idle-timeout",
+        env_var: "PATHFINDER_SEQUENCER_POOL_IDLE_TIMEOUT",
+        cli_flag: "sequencer.pool-idle-timeout",
+        secret: false,
+        required: false,
+    },
+    ConfigOptionInfo {
+        option: ConfigOption::SyncSource,
+        toml_key: "sync.source",
+        env_var: "PATHFINDER_SYNC_SOURCE",
+        cli_flag: "sync.source",
+        secret: false,
+        required: false,
+    },
+    ConfigOptionInfo {
+        option: ConfigOption::StartupReorgBuffer,
+        toml_key: "sync.startup-reorg-buffer",
+        env_var: "PATHFINDER_SYNC_STARTUP_REORG_BUFFER",
+        cli_flag: "sync.startup-reorg-buffer",
+        secret: false,
+        required: false,
+    },
+    ConfigOptionInfo {
+        option: ConfigOption::TlsCaBundle,
+        toml_key: "tls.ca-bundle",
+        env_var: "PATHFINDER_TLS_CA_BUNDLE",
+        cli_flag: "tls.ca-bundle",
+        secret: false,
+        required: false,
+    },
+];
+
+impl ConfigOption {
+    /// Returns the [ConfigOptionInfo] of every option, in declaration order.
+    pub fn all() -> &'static [ConfigOptionInfo] {
+        OPTIONS
+    }
+
+    /// Returns the [ConfigOptionInfo] of this option.
+    pub fn info(&self) -> &'static ConfigOptionInfo {
+        OPTIONS
+            .iter()
+            .find(|info| info.option == *self)
+            .expect("Every option is described")
+    }
+}
+
+/// Where the node syncs StarkNet state from.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SyncSource {
+    /// Only the state updates logged on L1.
+    L1,
+    /// Only the StarkNet sequencer.
+    Sequencer,
+    /// Both L1 and the sequencer.
+    Both,
+}
+
+impl Default for SyncSource {
+    fn default() -> Self {
+        SyncSource::Both
+    }
+}
+
+impl FromStr for SyncSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "l1" => Ok(SyncSource::L1),
+            "sequencer" => Ok(SyncSource::Sequencer),
+            "both" => Ok(SyncSource::Both),
+            other => Err(format!(
+                "expected one of l1, sequencer or both, got '{}'",
+                other
+            )),
+        }
+    }
+}
+
+impl Display for SyncSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SyncSource::L1 => f.write_str("l1"),
+            SyncSource::Sequencer => f.write_str("sequencer"),
+            SyncSource::Both => f.write_str("both"),
+        }
+    }
+}
+
+/// Root certificates loaded from a PEM bundle, which replace the built-in ones of an HTTP client.
+#[derive(Debug, Clone)]
+pub struct CaBundle(Vec<reqwest::Certificate>);
+
+impl CaBundle {
+    /// Loads the certificates of the PEM file at `path`.
+    ///
+    /// Errors if the file cannot be read or holds no valid certificates.
+    pub fn load(path: &std::path::Path) -> std::io::Result<Self> {
+        let pem = std::fs::read(path).map_err(|err| {
+            std::io::Error::new(
+                err.kind(),
+                format!("Reading CA bundle {}: {}", path.display(), err),
+            )
+        })?;
+        Self::from_pem(&pem).map_err(|err| {
+            std::io::Error::new(
+                err.kind(),
+                format!("Invalid CA bundle {}: {}", path.display(), err),
+            )
+        })
+    }
+
+    /// Parses every `CERTIFICATE` block of `pem`. Errors if there are none, or if any of them is
+    /// malformed.
+    pub fn from_pem(pem: &[u8]) -> std::io::Result<Self> {
+        const BEGIN: &str = "-----BEGIN CERTIFICATE-----";
+        const END: &str = "-----END CERTIFICATE-----";
+
+        let invalid =
+            |message: String| std::io::Error::new(std::io::ErrorKind::InvalidData, message);
+
+        let pem = std::str::from_utf8(pem).map_err(|err| invalid(err.to_string()))?;
+
+        let mut certificates = Vec::new();
+        let mut rest = pem;
+        while let Some(start) = rest.find(BEGIN) {
+            let len = rest[start..].find(END).ok_or_else(|| {
+                invalid(format!(
+                    "certificate {} is not terminated",
+                    certificates.len()
+                ))
+            })? + END.len();
+            let block = &rest[start..start + len];
+
+            let certificate = reqwest::Certificate::from_pem(block.as_bytes())
+                .map_err(|err| invalid(format!("certificate {}: {}", certificates.len(), err)))?;
+            certificates.push(certificate);
+
+            rest = &rest[start + len..];
+        }
+
+        if certificates.is_empty() {
+            return Err(invalid("no certificates found".to_owned()));
         }
+
+        Ok(Self(certificates))
+    }
+
+    /// Configures `builder` to trust only the certificates of this bundle.
+    pub fn apply(&self, builder: reqwest::ClientBuilder) -> reqwest::ClientBuilder {
+        self.0.iter().cloned().fold(
+            builder.tls_built_in_root_certs(false),
+            |builder, certificate| builder.add_root_certificate(certificate),
+        )
+    }
+
+    /// The certificates of the bundle, in file order.
+    pub fn certificates(&self) -> &[reqwest::Certificate] {
+        &self.0
     }
 }
 
@@ -39,10 +349,18 @@ impl Display for ConfigOption {
 pub struct EthereumConfig {
     /// The Ethereum URL.
     pub url: Url,
-    /// The optional Ethereum user.
+    /// The optional User-Agent sent to the Ethereum endpoint.
+    ///
+    /// This is not a credential, the [password](Self::password) is used on its own.
     pub user: Option<String>,
-    /// The optional Ethereum password.
+    /// The optional Ethereum password, set on the [url](Self::url).
     pub password: Option<String>,
+    /// The optional Ethereum chain ID, which takes precedence over the chain ID reported by the
+    /// endpoint.
+    pub chain_id: Option<u64>,
+    /// Whether log queries should be retried on the spurious decoder error which
+    /// some endpoints (Infura) return from time to time. Defaults to `true`.
+    pub retry_spurious_decoder_errors: bool,
 }
 
 /// Node configuration options.
@@ -52,27 +370,44 @@ pub struct Configuration {
     pub ethereum: EthereumConfig,
     /// The HTTP-RPC listening address and port.
     pub http_rpc_addr: SocketAddr,
+    /// The request size and time limits of the HTTP-RPC server.
+    pub rpc_limits: crate::rpc::Limits,
+    /// How the HTTP-RPC server renders StarkHash based values.
+    pub rpc_number_format: crate::rpc::serde::NumberFormat,
+    /// The timeout of a single sequencer request.
+    pub sequencer_timeout: Duration,
+    /// The connection pool settings of the sequencer client.
+    pub sequencer_pool: crate::sequencer::PoolConfig,
+    /// Where to sync StarkNet state from.
+    pub sync_source: SyncSource,
+    /// The number of most recent Ethereum blocks whose L1 state updates are re-scanned on
+    /// startup. Zero, the default, disables the re-scan.
+    pub startup_reorg_buffer: u64,
+    /// The PEM file of the root certificates to trust for HTTPS endpoints, instead of the
+    /// built-in ones. Loaded with [CaBundle::load] on startup.
+    pub tls_ca_bundle: Option<PathBuf>,
 }
 
 impl Configuration {
     /// Creates a [node configuration](Configuration) based on the options specified
-    /// via the command-line and config file.
+    /// via the command-line, environment variables and config file.
     ///
-    /// The config filepath may be specified as a command-line parameter.
+    /// The config filepath may be specified as a command-line parameter, as may
+    /// the profile to select from it.
     ///
-    /// Options from the command-line and config file will be merged, with the
-    /// command-line taking precedence. It is valid for no configuration file to exist,
-    /// so long as all required options are covered by the command-line arguments.
+    /// Options from the command-line, environment and config file will be merged, in
+    /// that order of precedence. It is valid for no configuration file to exist,
+    /// so long as all required options are covered by the other sources.
     ///
-    /// Errors if the configuration file couldn't be parsed, or if any required options
-    /// are not specified.
+    /// Errors if the configuration file couldn't be parsed, if an environment variable
+    /// is not valid unicode, or if any required options are not specified.
     ///
     /// Note: This will terminate the program if invalid command-line arguments are supplied.
     ///       This is intended, as [clap] will show the program usage / help.
     pub fn parse_cmd_line_and_cfg_file() -> std::io::Result<Self> {
         // Parse command-line arguments. This must be first in order to use
         // users config filepath (if supplied).
-        let (cfg_filepath, cli_cfg) = cli::parse_cmd_line();
+        let (cfg_filepath, profile, cli_cfg) = cli::parse_cmd_line();
 
         // Parse configuration file if specified.
         let file_cfg = match cfg_filepath {
@@ -80,16 +415,137 @@ impl Configuration {
                 let filepath = PathBuf::from_str(&filepath).map_err(|err| {
                     std::io::Error::new(std::io::ErrorKind::InvalidInput, err.to_string())
                 })?;
-                Some(file::config_from_filepath(&filepath)?)
+                match profile {
+                    Some(profile) => Some(file::config_from_filepath_with_profile(
+                        &filepath, &profile,
+                    )?),
+                    None => Some(file::config_from_filepath(&filepath)?),
+                }
             }
             None => None,
         };
 
+        // The command-line overrides the environment, which overrides the file.
+        let cfg = cli_cfg.merge(env::config_from_environment()?);
         let cfg = match file_cfg {
-            Some(cfg) => cli_cfg.merge(cfg),
-            None => cli_cfg,
+            Some(file_cfg) => cfg.merge(file_cfg),
+            None => cfg,
         };
 
         cfg.try_build()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn all_options_are_described_once() {
+        let all = ConfigOption::all();
+        assert!(!all.is_empty());
+        assert_eq!(
+            all.iter().map(|info| info.option).collect::<Vec<_>>(),
+            ConfigOption::into_enum_iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn keys_are_unique() {
+        let all = ConfigOption::all();
+        let toml_keys = all.iter().map(|info| info.toml_key).collect::<HashSet<_>>();
+        let env_vars = all.iter().map(|info| info.env_var).collect::<HashSet<_>>();
+        let cli_flags = all.iter().map(|info| info.cli_flag).collect::<HashSet<_>>();
+
+        assert_eq!(toml_keys.len(), all.len());
+        assert_eq!(env_vars.len(), all.len());
+        assert_eq!(cli_flags.len(), all.len());
+    }
+
+    #[test]
+    fn env_vars_follow_cli_flags() {
+        for info in ConfigOption::all() {
+            let expected =
+                format!("PATHFINDER_{}", info.cli_flag.replace(['.', '-'], "_")).to_uppercase();
+            assert_eq!(info.env_var, expected);
+        }
+    }
+
+    #[test]
+    fn info() {
+        for option in ConfigOption::into_enum_iter() {
+            assert_eq!(option.info().option, option);
+        }
+    }
+    mod ca_bundle {
+        use super::*;
+
+        const PEM: &[u8] = include_bytes!("../fixtures/tls/ca.pem");
+
+        #[test]
+        fn valid_pem() {
+            let bundle = CaBundle::from_pem(PEM).unwrap();
+            assert_eq!(bundle.certificates().len(), 1);
+
+            let mut two = PEM.to_vec();
+            two.extend_from_slice(PEM);
+            assert_eq!(CaBundle::from_pem(&two).unwrap().certificates().len(), 2);
+
+            bundle.apply(reqwest::Client::builder()).build().unwrap();
+        }
+
+        #[test]
+        fn malformed_pem() {
+            let malformed = String::from_utf8(PEM.to_vec())
+                .unwrap()
+                .replacen("MII", "!!!", 1);
+            let err = CaBundle::from_pem(malformed.as_bytes()).unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+            let truncated = &PEM[..PEM.len() - 30];
+            CaBundle::from_pem(truncated).unwrap_err();
+        }
+
+        #[test]
+        fn no_certificates() {
+            CaBundle::from_pem(b"").unwrap_err();
+            CaBundle::from_pem(b"not a pem file").unwrap_err();
+        }
+
+        #[test]
+        fn load_missing_file() {
+            let dir = tempfile::tempdir().unwrap();
+            let err = CaBundle::load(&dir.path().join("missing.pem")).unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+        }
+
+        #[test]
+        fn load_file() {
+            let mut file = tempfile::NamedTempFile::new().unwrap();
+            std::io::Write::write_all(&mut file, PEM).unwrap();
+            assert_eq!(CaBundle::load(file.path()).unwrap().certificates().len(), 1);
+        }
+    }
+
+    mod sync_source {
+        use super::*;
+
+        #[test]
+        fn parses_accepted_values() {
+            for source in [SyncSource::L1, SyncSource::Sequencer, SyncSource::Both] {
+                assert_eq!(source.to_string().parse::<SyncSource>(), Ok(source));
+            }
+            assert_eq!("l1".parse::<SyncSource>(), Ok(SyncSource::L1));
+            assert_eq!("sequencer".parse::<SyncSource>(), Ok(SyncSource::Sequencer));
+            assert_eq!("both".parse::<SyncSource>(), Ok(SyncSource::Both));
+        }
+
+        #[test]
+        fn rejects_unknown_value() {
+            assert!("l2".parse::<SyncSource>().is_err());
+            assert!("L1".parse::<SyncSource>().is_err());
+            assert!("".parse::<SyncSource>().is_err());
+        }
+    }
+}
@@ -7,17 +7,14 @@ use crate::config::builder::ConfigBuilder;
 use super::ConfigOption;
 
 const CONFIG_KEY: &str = "config";
-const ETH_URL_KEY: &str = "ethereum.url";
-const ETH_USER_KEY: &str = "ethereum.user";
-const ETH_PASS_KEY: &str = "ethereum.password";
-const HTTP_RPC_ADDR_KEY: &str = "http-rpc";
+const PROFILE_KEY: &str = "profile";
 
-/// Parses the cmd line arguments and returns the optional
-/// configuration file's path and the specified configuration options.
+/// Parses the cmd line arguments and returns the optional configuration
+/// file's path, the optional profile and the specified configuration options.
 ///
 /// Note: This will terminate the program if invalid arguments are supplied.
 ///       This is intended, as [clap] will show the program usage / help.
-pub fn parse_cmd_line() -> (Option<String>, ConfigBuilder) {
+pub fn parse_cmd_line() -> (Option<String>, Option<String>, ConfigBuilder) {
     // A thin wrapper around `parse_args()`. This should be kept thin
     // to enable test coverage without requiring cmd line arg input.
     match parse_args(&mut std::env::args_os()) {
@@ -28,7 +25,7 @@ pub fn parse_cmd_line() -> (Option<String>, ConfigBuilder) {
 
 /// A wrapper around [clap::App]'s `get_matches_from_safe()` which returns
 /// a [ConfigOption].
-fn parse_args<I, T>(args: I) -> clap::Result<(Option<String>, ConfigBuilder)>
+fn parse_args<I, T>(args: I) -> clap::Result<(Option<String>, Option<String>, ConfigBuilder)>
 where
     I: IntoIterator<Item = T>,
     T: Into<OsString> + Clone,
@@ -36,18 +33,17 @@ where
     let args = clap_app().get_matches_from_safe(args)?;
 
     let config_filepath = args.value_of(CONFIG_KEY).map(|s| s.to_owned());
-    let ethereum_url = args.value_of(ETH_URL_KEY).map(|s| s.to_owned());
-    let ethereum_user = args.value_of(ETH_USER_KEY).map(|s| s.to_owned());
-    let ethereum_password = args.value_of(ETH_PASS_KEY).map(|s| s.to_owned());
-    let http_rpc_addr = args.value_of(HTTP_RPC_ADDR_KEY).map(|s| s.to_owned());
-
-    let cfg = ConfigBuilder::default()
-        .with(ConfigOption::EthereumHttpUrl, ethereum_url)
-        .with(ConfigOption::EthereumUser, ethereum_user)
-        .with(ConfigOption::EthereumPassword, ethereum_password)
-        .with(ConfigOption::HttpRpcAddress, http_rpc_addr);
-
-    Ok((config_filepath, cfg))
+    let profile = args.value_of(PROFILE_KEY).map(|s| s.to_owned());
+    let cfg = ConfigOption::all()
+        .iter()
+        .fold(ConfigBuilder::default(), |cfg, info| {
+            cfg.with(
+                info.option,
+                args.value_of(info.cli_flag).map(|s| s.to_owned()),
+            )
+        });
+
+    Ok((config_filepath, profile, cfg))
 }
 
 /// Defines our command-line interface using [clap::App].
@@ -58,6 +54,10 @@ fn clap_app() -> clap::App<'static, 'static> {
     lazy_static::lazy_static! {
         static ref HTTP_RPC_HELP: String =
             format!("HTTP-RPC listening address [default: {}]", DEFAULT_HTTP_RPC_ADDR);
+        static ref SEQUENCER_TIMEOUT_HELP: String = format!(
+            "Sequencer request timeout [default: {}]",
+            humantime::format_duration(crate::sequencer::DEFAULT_TIMEOUT)
+        );
     }
 
     let version = concat!(crate_version!(), "-alpha");
@@ -73,88 +73,292 @@ fn clap_app() -> clap::App<'static, 'static> {
                 .takes_value(true),
         )
         .arg(
-            Arg::with_name(ETH_USER_KEY)
-                .long(ETH_USER_KEY)
-                .help("Ethereum API user")
-                .takes_value(true)
-                .long_help("The optional user to use for the Ethereum API"),
+            Arg::with_name(PROFILE_KEY)
+                .short("p")
+                .long(PROFILE_KEY)
+                .help("Configuration file profile to use.")
+                .long_help("Selects the [profiles.<NAME>] table of the configuration file, whose options take precedence over the top-level ones.")
+                .value_name("NAME")
+                .requires(CONFIG_KEY)
+                .takes_value(true),
         )
         .arg(
-            Arg::with_name(ETH_PASS_KEY)
-                .long(ETH_PASS_KEY)
+            option_arg(ConfigOption::EthereumUser)
+                .help("Ethereum API user-agent")
+                .long_help("The optional User-Agent header sent to the Ethereum API. This is not a credential, the password is used on its own."),
+        )
+        .arg(
+            option_arg(ConfigOption::EthereumPassword)
                 .help("Ethereum API password")
-                .takes_value(true)
                 .long_help("The optional password to use for the Ethereum API"),
         )
         .arg(
-            Arg::with_name(ETH_URL_KEY)
-                .long(ETH_URL_KEY)
+            option_arg(ConfigOption::EthereumChainId)
+                .help("Ethereum chain ID override")
+                .value_name("CHAIN ID")
+                .long_help("The optional chain ID of the Ethereum network, 1 for mainnet or 5 for Goerli. Takes precedence over the chain ID reported by the Ethereum API, a mismatch is only logged as a warning."),
+        )
+        .arg(
+            option_arg(ConfigOption::EthereumHttpUrl)
                 .help("Ethereum API endpoint")
-                .takes_value(true)
                 .value_name("HTTP(s) URL")
                 .long_help(r"This should point to the HTTP RPC endpoint of your Ethereum entry-point, typically a local Ethereum client or a hosted gateway service such as Infura or Cloudflare.
 Examples:
     infura: https://goerli.infura.io/v3/<PROJECT_ID>
     geth:   https://localhost:8545"))
         .arg(
-            Arg::with_name(HTTP_RPC_ADDR_KEY)
-                .long(HTTP_RPC_ADDR_KEY)
+            option_arg(ConfigOption::HttpRpcAddress)
                 .help(&HTTP_RPC_HELP)
-                .takes_value(true)
                 .value_name("IP:PORT")
         )
+        .arg(
+            option_arg(ConfigOption::RpcMaxRequestBytes)
+                .help("Maximum size of a single HTTP-RPC request [default: 10MB]")
+                .value_name("SIZE")
+                .long_help("Requests with a larger body are rejected by the HTTP-RPC server. The size is given in B, KB, MB or GB, for example 512KB or 1MB.")
+        )
+        .arg(
+            option_arg(ConfigOption::RpcRequestTimeout)
+                .help("Timeout of a single HTTP-RPC request [default: 30s]")
+                .value_name("DURATION")
+                .long_help("HTTP-RPC method calls taking longer than this duration are aborted with an error, for example 30s or 2m.")
+        )
+        .arg(
+            option_arg(ConfigOption::RpcNumberFormat)
+                .help("Number format of HTTP-RPC replies [default: hex]")
+                .value_name("FORMAT")
+                .long_help("Hashes, addresses and other field elements in HTTP-RPC replies are \"0x\" prefixed hex strings by default, as required by the specification. Set this to decimal to render them as decimal strings instead. Possible values are hex and decimal.")
+        )
+        .arg(
+            option_arg(ConfigOption::RetrySpuriousDecoderErrors)
+                .help("Retry spurious Ethereum log decoder errors [default: true]")
+                .possible_values(&["true", "false"])
+                .value_name("BOOL")
+                .long_help("Some Ethereum endpoints (notably Infura) occasionally return an empty reply to log queries, which surfaces as a decoder error. By default such queries are simply retried; set this to false to fail immediately instead, which can help when debugging a misbehaving provider.")
+        )
+        .arg(
+            option_arg(ConfigOption::SequencerTimeout)
+                .help(&SEQUENCER_TIMEOUT_HELP)
+                .value_name("DURATION")
+                .long_help("The timeout of a single request to the StarkNet sequencer, for example 120s or 2m. This needs to accommodate the largest replies, which are the full contract definitions.")
+        )
+        .arg(
+            option_arg(ConfigOption::SequencerPoolMaxIdle)
+                .help("Maximum number of idle connections kept open to the sequencer [default: unlimited]")
+                .value_name("COUNT")
+                .long_help("All sequencer requests share a single connection pool. This limits how many idle connections the pool keeps open for reuse.")
+        )
+        .arg(
+            option_arg(ConfigOption::SequencerPoolIdleTimeout)
+                .help("How long idle connections to the sequencer are kept open [default: 90s]")
+                .value_name("DURATION")
+                .long_help("Idle connections in the sequencer connection pool are closed after this duration, for example 90s or 5m.")
+        )
+        .arg(
+            option_arg(ConfigOption::SyncSource)
+                .help("Where to sync StarkNet state from [default: both]")
+                .value_name("l1|sequencer|both")
+                .long_help("Sync StarkNet state from the state updates logged on L1, from the StarkNet sequencer, or from both.")
+        )
+        .arg(
+            option_arg(ConfigOption::StartupReorgBuffer)
+                .help("Number of recent Ethereum blocks to re-scan for L1 state updates on startup [default: 0]")
+                .value_name("BLOCKS")
+                .long_help("On startup, the L1 state updates logged in this many of the most recent Ethereum blocks are discarded and fetched again, in case L1 reorged while the node was down. Zero disables the re-scan.")
+        )
+        .arg(
+            option_arg(ConfigOption::TlsCaBundle)
+                .help("PEM file of the root certificates to trust for HTTPS endpoints")
+                .value_name("FILE")
+                .long_help("Replaces the built-in root certificates of the Ethereum and sequencer HTTPS clients with the certificates of the given PEM file.")
+        )
+}
+
+/// An [Arg] taking a value, named after the `cli_flag` of the option.
+fn option_arg(option: ConfigOption) -> Arg<'static, 'static> {
+    let flag = option.info().cli_flag;
+    Arg::with_name(flag).long(flag).takes_value(true)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn cli_flags_match_option_info() {
+        for info in ConfigOption::all() {
+            let flag = format!("--{}", info.cli_flag);
+            let (_, _, mut cfg) = parse_args(vec!["bin name", &flag, "true"]).unwrap();
+            assert_eq!(
+                cfg.take(info.option),
+                Some("true".to_owned()),
+                "{}",
+                info.cli_flag
+            );
+        }
+    }
+
     #[test]
     fn ethereum_url_long() {
         let value = "value".to_owned();
-        let (_, mut cfg) = parse_args(vec!["bin name", "--ethereum.url", &value]).unwrap();
+        let (_, _, mut cfg) = parse_args(vec!["bin name", "--ethereum.url", &value]).unwrap();
         assert_eq!(cfg.take(ConfigOption::EthereumHttpUrl), Some(value));
     }
 
     #[test]
     fn ethereum_user_long() {
         let value = "value".to_owned();
-        let (_, mut cfg) = parse_args(vec!["bin name", "--ethereum.user", &value]).unwrap();
+        let (_, _, mut cfg) = parse_args(vec!["bin name", "--ethereum.user", &value]).unwrap();
         assert_eq!(cfg.take(ConfigOption::EthereumUser), Some(value));
     }
 
     #[test]
     fn ethereum_password_long() {
         let value = "value".to_owned();
-        let (_, mut cfg) = parse_args(vec!["bin name", "--ethereum.password", &value]).unwrap();
+        let (_, _, mut cfg) = parse_args(vec!["bin name", "--ethereum.password", &value]).unwrap();
         assert_eq!(cfg.take(ConfigOption::EthereumPassword), Some(value));
     }
 
+    #[test]
+    fn ethereum_chain_id_long() {
+        let value = "value".to_owned();
+        let (_, _, mut cfg) = parse_args(vec!["bin name", "--ethereum.chain-id", &value]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::EthereumChainId), Some(value));
+    }
+
     #[test]
     fn config_filepath_short() {
         let value = "value".to_owned();
-        let (filepath, _) = parse_args(vec!["bin name", "-c", &value]).unwrap();
+        let (filepath, _, _) = parse_args(vec!["bin name", "-c", &value]).unwrap();
         assert_eq!(filepath, Some(value));
     }
 
     #[test]
     fn config_filepath_long() {
         let value = "value".to_owned();
-        let (filepath, _) = parse_args(vec!["bin name", "--config", &value]).unwrap();
+        let (filepath, _, _) = parse_args(vec!["bin name", "--config", &value]).unwrap();
         assert_eq!(filepath, Some(value));
     }
 
     #[test]
     fn http_rpc_address_long() {
         let value = "value".to_owned();
-        let (_, mut cfg) = parse_args(vec!["bin name", "--http-rpc", &value]).unwrap();
+        let (_, _, mut cfg) = parse_args(vec!["bin name", "--http-rpc", &value]).unwrap();
         assert_eq!(cfg.take(ConfigOption::HttpRpcAddress), Some(value));
     }
 
+    #[test]
+    fn rpc_max_request_bytes_long() {
+        let value = "value".to_owned();
+        let (_, _, mut cfg) =
+            parse_args(vec!["bin name", "--rpc.max-request-bytes", &value]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::RpcMaxRequestBytes), Some(value));
+    }
+
+    #[test]
+    fn rpc_request_timeout_long() {
+        let value = "value".to_owned();
+        let (_, _, mut cfg) =
+            parse_args(vec!["bin name", "--rpc.request-timeout", &value]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::RpcRequestTimeout), Some(value));
+    }
+
+    #[test]
+    fn rpc_number_format_long() {
+        let value = "decimal".to_owned();
+        let (_, _, mut cfg) = parse_args(vec!["bin name", "--rpc.number-format", &value]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::RpcNumberFormat), Some(value));
+    }
+
+    #[test]
+    fn ethereum_retry_spurious_decoder_errors_long() {
+        let value = "false".to_owned();
+        let (_, _, mut cfg) = parse_args(vec![
+            "bin name",
+            "--ethereum.retry-spurious-decoder-errors",
+            &value,
+        ])
+        .unwrap();
+        assert_eq!(
+            cfg.take(ConfigOption::RetrySpuriousDecoderErrors),
+            Some(value)
+        );
+    }
+
+    #[test]
+    fn startup_reorg_buffer_long() {
+        let value = "value".to_owned();
+        let (_, _, mut cfg) =
+            parse_args(vec!["bin name", "--sync.startup-reorg-buffer", &value]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::StartupReorgBuffer), Some(value));
+    }
+
+    #[test]
+    fn tls_ca_bundle_long() {
+        let value = "value".to_owned();
+        let (_, _, mut cfg) = parse_args(vec!["bin name", "--tls.ca-bundle", &value]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::TlsCaBundle), Some(value));
+    }
+
+    #[test]
+    fn sync_source_long() {
+        let value = "value".to_owned();
+        let (_, _, mut cfg) = parse_args(vec!["bin name", "--sync.source", &value]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::SyncSource), Some(value));
+    }
+
+    #[test]
+    fn sequencer_pool_long() {
+        let (_, _, mut cfg) = parse_args(vec![
+            "bin name",
+            "--sequencer.pool-max-idle-per-host",
+            "4",
+            "--sequencer.pool-idle-timeout",
+            "30s",
+        ])
+        .unwrap();
+        assert_eq!(
+            cfg.take(ConfigOption::SequencerPoolMaxIdle),
+            Some("4".to_owned())
+        );
+        assert_eq!(
+            cfg.take(ConfigOption::SequencerPoolIdleTimeout),
+            Some("30s".to_owned())
+        );
+    }
+
+    #[test]
+    fn sequencer_timeout_long() {
+        let value = "value".to_owned();
+        let (_, _, mut cfg) = parse_args(vec!["bin name", "--sequencer.timeout", &value]).unwrap();
+        assert_eq!(cfg.take(ConfigOption::SequencerTimeout), Some(value));
+    }
+
+    #[test]
+    fn profile_short() {
+        let value = "value".to_owned();
+        let (_, profile, _) = parse_args(vec!["bin name", "-c", "file", "-p", &value]).unwrap();
+        assert_eq!(profile, Some(value));
+    }
+
+    #[test]
+    fn profile_long() {
+        let value = "value".to_owned();
+        let (_, profile, _) =
+            parse_args(vec!["bin name", "--config", "file", "--profile", &value]).unwrap();
+        assert_eq!(profile, Some(value));
+    }
+
+    #[test]
+    fn profile_requires_config() {
+        assert!(parse_args(vec!["bin name", "--profile", "value"]).is_err());
+    }
+
     #[test]
     fn empty_config() {
-        let (filepath, cfg) = parse_args(vec!["bin name"]).unwrap();
+        let (filepath, profile, cfg) = parse_args(vec!["bin name"]).unwrap();
         assert_eq!(filepath, None);
+        assert_eq!(profile, None);
         assert_eq!(cfg, ConfigBuilder::default());
     }
 }
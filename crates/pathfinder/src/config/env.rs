@@ -0,0 +1,85 @@
+//! Environment variable parsing
+use std::ffi::OsString;
+
+use crate::config::builder::ConfigBuilder;
+
+use super::ConfigOption;
+
+/// Reads the configuration options from their `PATHFINDER_*` environment variables.
+///
+/// Variables which are not set are skipped. Errors if a variable is not valid unicode.
+pub fn config_from_environment() -> std::io::Result<ConfigBuilder> {
+    config_from_vars(|name| std::env::var_os(name))
+}
+
+/// Reads the configuration options using `var` to look up each option's
+/// [env_var](super::ConfigOptionInfo::env_var).
+fn config_from_vars<F>(var: F) -> std::io::Result<ConfigBuilder>
+where
+    F: Fn(&str) -> Option<OsString>,
+{
+    ConfigOption::all()
+        .iter()
+        .try_fold(ConfigBuilder::default(), |cfg, info| {
+            let value = var(info.env_var)
+                .map(|value| {
+                    value.into_string().map_err(|_| {
+                        std::io::Error::new(
+                            std::io::ErrorKind::InvalidInput,
+                            format!("{} is not valid unicode", info.env_var),
+                        )
+                    })
+                })
+                .transpose()?;
+            Ok(cfg.with(info.option, value))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn vars(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<OsString> {
+        let vars = vars
+            .iter()
+            .map(|(name, value)| (name.to_string(), OsString::from(value)))
+            .collect::<HashMap<_, _>>();
+        move |name| vars.get(name).cloned()
+    }
+
+    #[test]
+    fn env_vars_match_option_info() {
+        for info in ConfigOption::all() {
+            let mut cfg = config_from_vars(vars(&[(info.env_var, "value")])).unwrap();
+            assert_eq!(
+                cfg.take(info.option),
+                Some("value".to_owned()),
+                "{}",
+                info.env_var
+            );
+        }
+    }
+
+    #[test]
+    fn unset_vars_are_skipped() {
+        let mut cfg = config_from_vars(vars(&[("PATHFINDER_SYNC_SOURCE", "l1")])).unwrap();
+        assert_eq!(cfg.take(ConfigOption::SyncSource), Some("l1".to_owned()));
+        for info in ConfigOption::all() {
+            assert_eq!(cfg.take(info.option), None, "{}", info.env_var);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn non_unicode_is_an_error() {
+        use std::os::unix::ffi::OsStringExt;
+
+        let error = config_from_vars(|name| match name {
+            "PATHFINDER_ETHEREUM_URL" => Some(OsString::from_vec(vec![0xff])),
+            _ => None,
+        })
+        .unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    }
+}
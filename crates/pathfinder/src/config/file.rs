@@ -1,13 +1,106 @@
 //! TOML configuration file parsing
+use std::collections::BTreeMap;
+
 use serde::Deserialize;
 
 use crate::config::builder::ConfigBuilder;
 
-#[derive(Deserialize, Debug, PartialEq)]
+/// The `[ethereum]` table, which may also be given as just the URL (`ethereum = "<url>"`).
+#[derive(Debug, PartialEq)]
 struct EthereumConfig {
     url: Option<String>,
     user: Option<String>,
     password: Option<String>,
+    chain_id: Option<u64>,
+    retry_spurious_decoder_errors: Option<bool>,
+}
+
+impl<'de> Deserialize<'de> for EthereumConfig {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        struct Table {
+            url: Option<String>,
+            user: Option<String>,
+            password: Option<String>,
+            #[serde(rename = "chain-id")]
+            chain_id: Option<u64>,
+            #[serde(rename = "retry-spurious-decoder-errors")]
+            retry_spurious_decoder_errors: Option<bool>,
+        }
+
+        // Not an untagged enum, as that would replace the errors of the table's fields with
+        // "data did not match any variant".
+        struct StringOrTable;
+
+        impl<'de> serde::de::Visitor<'de> for StringOrTable {
+            type Value = EthereumConfig;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                formatter.write_str("a URL string or an [ethereum] table")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, url: &str) -> Result<Self::Value, E> {
+                Ok(EthereumConfig {
+                    url: Some(url.to_owned()),
+                    user: None,
+                    password: None,
+                    chain_id: None,
+                    retry_spurious_decoder_errors: None,
+                })
+            }
+
+            fn visit_map<M>(self, map: M) -> Result<Self::Value, M::Error>
+            where
+                M: serde::de::MapAccess<'de>,
+            {
+                let table = Table::deserialize(serde::de::value::MapAccessDeserializer::new(map))?;
+                Ok(EthereumConfig {
+                    url: table.url,
+                    user: table.user,
+                    password: table.password,
+                    chain_id: table.chain_id,
+                    retry_spurious_decoder_errors: table.retry_spurious_decoder_errors,
+                })
+            }
+        }
+
+        deserializer.deserialize_any(StringOrTable)
+    }
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct RpcConfig {
+    #[serde(rename = "max-request-bytes")]
+    max_request_bytes: Option<String>,
+    #[serde(rename = "request-timeout")]
+    request_timeout: Option<String>,
+    #[serde(rename = "number-format")]
+    number_format: Option<String>,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct SequencerConfig {
+    timeout: Option<String>,
+    #[serde(rename = "pool-max-idle-per-host")]
+    pool_max_idle_per_host: Option<usize>,
+    #[serde(rename = "pool-idle-timeout")]
+    pool_idle_timeout: Option<String>,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct SyncConfig {
+    source: Option<String>,
+    #[serde(rename = "startup-reorg-buffer")]
+    startup_reorg_buffer: Option<u64>,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct TlsConfig {
+    #[serde(rename = "ca-bundle")]
+    ca_bundle: Option<String>,
 }
 
 #[derive(Deserialize, Debug, PartialEq)]
@@ -15,32 +108,281 @@ struct FileConfig {
     ethereum: Option<EthereumConfig>,
     #[serde(rename = "http-rpc")]
     http_rpc: Option<String>,
+    rpc: Option<RpcConfig>,
+    sequencer: Option<SequencerConfig>,
+    sync: Option<SyncConfig>,
+    tls: Option<TlsConfig>,
+    /// Files whose options are used where this file does not set them, relative to this file.
+    /// Later files take precedence over earlier ones.
+    include: Option<Vec<String>>,
+    /// Named `[profiles.<name>]` tables, each mirroring the top-level options.
+    profiles: Option<BTreeMap<String, ProfileConfig>>,
+}
+
+/// A named set of options which can be selected using [config_from_filepath_with_profile].
+#[derive(Deserialize, Debug, PartialEq)]
+struct ProfileConfig {
+    ethereum: Option<EthereumConfig>,
+    #[serde(rename = "http-rpc")]
+    http_rpc: Option<String>,
+    rpc: Option<RpcConfig>,
+    sequencer: Option<SequencerConfig>,
+    sync: Option<SyncConfig>,
+    tls: Option<TlsConfig>,
 }
 
 impl FileConfig {
+    fn into_config_options(self) -> ConfigBuilder {
+        ProfileConfig {
+            ethereum: self.ethereum,
+            http_rpc: self.http_rpc,
+            rpc: self.rpc,
+            sequencer: self.sequencer,
+            sync: self.sync,
+            tls: self.tls,
+        }
+        .into_config_options()
+    }
+
+    /// Selects the profile `name`, whose options take precedence over the top-level ones.
+    fn into_profile_config_options(mut self, name: &str) -> std::io::Result<ConfigBuilder> {
+        let mut profiles = self.profiles.take().unwrap_or_default();
+
+        let profile = profiles.remove(name).ok_or_else(|| {
+            let available = profiles.keys().cloned().collect::<Vec<_>>();
+            let available = match available.is_empty() {
+                true => "none".to_owned(),
+                false => available.join(", "),
+            };
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "Profile '{}' not found, available profiles: {}",
+                    name, available
+                ),
+            )
+        })?;
+
+        Ok(profile
+            .into_config_options()
+            .merge(self.into_config_options()))
+    }
+}
+
+impl ProfileConfig {
     fn into_config_options(self) -> ConfigBuilder {
         use crate::config::ConfigOption;
         let builder = match self.ethereum {
             Some(eth) => ConfigBuilder::default()
                 .with(ConfigOption::EthereumHttpUrl, eth.url)
                 .with(ConfigOption::EthereumUser, eth.user)
-                .with(ConfigOption::EthereumPassword, eth.password),
+                .with(ConfigOption::EthereumPassword, eth.password)
+                .with(
+                    ConfigOption::EthereumChainId,
+                    eth.chain_id.map(|id| id.to_string()),
+                )
+                .with(
+                    ConfigOption::RetrySpuriousDecoderErrors,
+                    eth.retry_spurious_decoder_errors.map(|b| b.to_string()),
+                ),
             None => ConfigBuilder::default(),
         };
+        let builder = match self.sync {
+            Some(sync) => builder.with(ConfigOption::SyncSource, sync.source).with(
+                ConfigOption::StartupReorgBuffer,
+                sync.startup_reorg_buffer.map(|blocks| blocks.to_string()),
+            ),
+            None => builder,
+        };
+        let builder = match self.sequencer {
+            Some(sequencer) => builder
+                .with(ConfigOption::SequencerTimeout, sequencer.timeout)
+                .with(
+                    ConfigOption::SequencerPoolMaxIdle,
+                    sequencer.pool_max_idle_per_host.map(|n| n.to_string()),
+                )
+                .with(
+                    ConfigOption::SequencerPoolIdleTimeout,
+                    sequencer.pool_idle_timeout,
+                ),
+            None => builder,
+        };
+        let builder = match self.rpc {
+            Some(rpc) => builder
+                .with(ConfigOption::RpcMaxRequestBytes, rpc.max_request_bytes)
+                .with(ConfigOption::RpcRequestTimeout, rpc.request_timeout)
+                .with(ConfigOption::RpcNumberFormat, rpc.number_format),
+            None => builder,
+        };
+        let builder = match self.tls {
+            Some(tls) => builder.with(ConfigOption::TlsCaBundle, tls.ca_bundle),
+            None => builder,
+        };
         builder.with(ConfigOption::HttpRpcAddress, self.http_rpc)
     }
 }
 
+/// Errors of loading a configuration file with [config_from_filepath], naming the file which
+/// failed.
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    /// The file could not be read, e.g. because it does not exist or permission was denied.
+    #[error("Failed to read config file {}: {source}", .path.display())]
+    Read {
+        path: std::path::PathBuf,
+        source: std::io::Error,
+    },
+    /// The file was read, but is not a valid configuration file.
+    #[error("Failed to parse config file {}: {source}", .path.display())]
+    Parse {
+        path: std::path::PathBuf,
+        source: std::io::Error,
+    },
+    /// The files are valid on their own, but not together, e.g. cyclic includes or a missing
+    /// profile.
+    #[error(transparent)]
+    Other(#[from] std::io::Error),
+}
+
+impl ConfigError {
+    /// The [kind](std::io::ErrorKind) of the underlying [io::Error](std::io::Error).
+    pub fn kind(&self) -> std::io::ErrorKind {
+        match self {
+            ConfigError::Read { source, .. } | ConfigError::Parse { source, .. } => source.kind(),
+            ConfigError::Other(e) => e.kind(),
+        }
+    }
+}
+
+impl From<ConfigError> for std::io::Error {
+    fn from(e: ConfigError) -> Self {
+        match e {
+            ConfigError::Other(e) => e,
+            e => std::io::Error::new(e.kind(), e),
+        }
+    }
+}
+
 /// Parses a [ConfigBuilder] from a toml format file.
-pub fn config_from_filepath(filepath: &std::path::Path) -> std::io::Result<ConfigBuilder> {
-    let file_contents = std::fs::read_to_string(filepath)?;
-    config_from_str(&file_contents)
+///
+/// The files listed by its top-level `include = ["common.toml", ...]` key are parsed as well,
+/// relative to the file's directory. Their options are used where the including file does not
+/// set them, with later files taking precedence over earlier ones. Only the top-level options of
+/// included files are used, and includes may be nested but not cyclic.
+pub fn config_from_filepath(filepath: &std::path::Path) -> Result<ConfigBuilder, ConfigError> {
+    config_from_file(filepath, None, &mut Vec::new())
+}
+
+/// Parses a [ConfigBuilder] from a toml format file, using the options of the
+/// `[profiles.<profile>]` table. Options not set by the profile fall back to the
+/// top-level ones, and then to the included files as in [config_from_filepath].
+///
+/// Errors if the profile does not exist.
+pub fn config_from_filepath_with_profile(
+    filepath: &std::path::Path,
+    profile: &str,
+) -> Result<ConfigBuilder, ConfigError> {
+    config_from_file(filepath, Some(profile), &mut Vec::new())
+}
+
+/// Parses the file at `filepath` and its includes. `including` holds the files which are
+/// currently being parsed, outermost first, to detect cyclic includes.
+fn config_from_file(
+    filepath: &std::path::Path,
+    profile: Option<&str>,
+    including: &mut Vec<std::path::PathBuf>,
+) -> Result<ConfigBuilder, ConfigError> {
+    let read_error = |source| ConfigError::Read {
+        path: filepath.to_owned(),
+        source,
+    };
+
+    let canonical = filepath.canonicalize().map_err(read_error)?;
+    if including.contains(&canonical) {
+        let cycle = including
+            .iter()
+            .chain(std::iter::once(&canonical))
+            .map(|path| path.display().to_string())
+            .collect::<Vec<_>>()
+            .join(" -> ");
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("Cyclic config include: {}", cycle),
+        )
+        .into());
+    }
+
+    let file_contents = std::fs::read_to_string(&canonical).map_err(read_error)?;
+    let mut cfg = parse_file_config(&file_contents).map_err(|source| ConfigError::Parse {
+        path: filepath.to_owned(),
+        source,
+    })?;
+
+    including.push(canonical);
+
+    let directory = filepath
+        .parent()
+        .unwrap_or_else(|| std::path::Path::new(""));
+    let mut included = ConfigBuilder::default();
+    for include in cfg.include.take().unwrap_or_default() {
+        included = config_from_file(&directory.join(include), None, including)?.merge(included);
+    }
+
+    including.pop();
+
+    let cfg = match profile {
+        Some(profile) => cfg.into_profile_config_options(profile)?,
+        None => cfg.into_config_options(),
+    };
+
+    Ok(cfg.merge(included))
 }
 
+#[cfg(test)]
 fn config_from_str(s: &str) -> std::io::Result<ConfigBuilder> {
+    parse_file_config(s).map(|cfg| cfg.into_config_options())
+}
+
+#[cfg(test)]
+fn config_from_str_with_profile(s: &str, profile: &str) -> std::io::Result<ConfigBuilder> {
+    parse_file_config(s)?.into_profile_config_options(profile)
+}
+
+fn parse_file_config(s: &str) -> std::io::Result<FileConfig> {
     toml::from_str::<FileConfig>(s)
-        .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err.to_string()))
-        .map(|cfg| cfg.into_config_options())
+        .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, describe_error(&err)))
+}
+
+/// Describes a [toml::de::Error] in terms of the offending key, e.g.
+/// "`http-rpc` expected a string, found integer `5` (line 1, column 12)".
+///
+/// The key path is the one reported by [toml], so `[table]` headers, quoting and comments are
+/// accounted for. Falls back to the plain [toml] error message if the error has no key or location.
+fn describe_error(err: &toml::de::Error) -> String {
+    let full_message = err.to_string();
+    let (line, column) = match err.line_col() {
+        Some(line_col) => line_col,
+        None => return full_message,
+    };
+    // The message is followed by " for key `<path>`" and " at line ...", which we replace with
+    // our own description of the key and location.
+    let (message, key) = match full_message
+        .rsplit_once(" for key `")
+        .and_then(|(message, rest)| Some((message, rest.split_once('`')?.0)))
+    {
+        Some(message_and_key) => message_and_key,
+        None => return full_message,
+    };
+
+    let description = match message
+        .strip_prefix("invalid type: ")
+        .and_then(|m| m.split_once(", expected "))
+    {
+        Some((found, expected)) => format!("`{}` expected {}, found {}", key, expected, found),
+        None => format!("`{}`: {}", key, message),
+    };
+
+    format!("{} (line {}, column {})", description, line + 1, column + 1)
 }
 
 #[cfg(test)]
@@ -92,6 +434,200 @@ password = "{}""#,
         assert_eq!(cfg.take(ConfigOption::EthereumPassword), Some(password));
     }
 
+    #[test]
+    fn ethereum_url_shorthand() {
+        let value = "value".to_owned();
+        let toml = format!(r#"ethereum = "{}""#, value);
+        let mut cfg = config_from_str(&toml).unwrap();
+        assert_eq!(cfg.take(ConfigOption::EthereumHttpUrl), Some(value));
+        assert_eq!(cfg.take(ConfigOption::EthereumUser), None);
+        assert_eq!(cfg.take(ConfigOption::EthereumPassword), None);
+    }
+
+    #[test]
+    fn ethereum_invalid_type() {
+        let err = config_from_str("ethereum = 5").unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert!(
+            err.to_string()
+                .contains("expected a URL string or an [ethereum] table"),
+            "Error should describe the expected value: {}",
+            err
+        );
+    }
+
+    #[test]
+    fn ethereum_table_field_invalid_type() {
+        let toml = r#"[ethereum]
+url = "value"
+chain-id = "five""#;
+        let err = config_from_str(toml).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert!(
+            err.to_string().contains("`ethereum.chain-id` expected u64"),
+            "Error should name the field and its type: {}",
+            err
+        );
+    }
+
+    mod invalid_type {
+        use super::*;
+
+        #[test]
+        fn top_level_key() {
+            let err = config_from_str(r#"http-rpc = 5"#).unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+            assert_eq!(
+                err.to_string(),
+                "`http-rpc` expected a string, found integer `5` (line 1, column 12)"
+            );
+        }
+
+        #[test]
+        fn dotted_key() {
+            let err = config_from_str(r#"sequencer.timeout = true"#).unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+            assert!(
+                err.to_string().contains("`sequencer.timeout`"),
+                "Error should name the key: {}",
+                err
+            );
+        }
+
+        #[test]
+        fn key_in_table() {
+            let toml = r#"http-rpc = "value"
+
+[sequencer]
+timeout = 5"#;
+            let err = config_from_str(toml).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "`sequencer.timeout` expected a string, found integer `5` (line 4, column 11)"
+            );
+        }
+
+        #[test]
+        fn commented_and_quoted_table_header() {
+            let toml = r#"["sequencer"] # the StarkNet gateway
+timeout = 5"#;
+            let err = config_from_str(toml).unwrap_err();
+            assert!(
+                err.to_string().contains("`sequencer.timeout`"),
+                "Error should name the key: {}",
+                err
+            );
+        }
+
+        #[test]
+        fn key_in_profile() {
+            let toml = r#"[profiles.dev.sequencer]
+timeout = 5"#;
+            let err = config_from_str(toml).unwrap_err();
+            assert!(
+                err.to_string().contains("`profiles.dev.sequencer.timeout`"),
+                "Error should name the key: {}",
+                err
+            );
+        }
+    }
+
+    #[test]
+    fn ethereum_chain_id() {
+        let toml = r#"ethereum.chain-id = 5"#;
+        let mut cfg = config_from_str(toml).unwrap();
+        assert_eq!(
+            cfg.take(ConfigOption::EthereumChainId),
+            Some("5".to_owned())
+        );
+    }
+
+    #[test]
+    fn ethereum_retry_spurious_decoder_errors() {
+        let toml = r#"ethereum.retry-spurious-decoder-errors = false"#;
+        let mut cfg = config_from_str(toml).unwrap();
+        assert_eq!(
+            cfg.take(ConfigOption::RetrySpuriousDecoderErrors),
+            Some("false".to_owned())
+        );
+    }
+
+    #[test]
+    fn sequencer_pool() {
+        let toml = r#"[sequencer]
+pool-max-idle-per-host = 4
+pool-idle-timeout = "30s""#;
+        let mut cfg = config_from_str(toml).unwrap();
+        assert_eq!(
+            cfg.take(ConfigOption::SequencerPoolMaxIdle),
+            Some("4".to_owned())
+        );
+        assert_eq!(
+            cfg.take(ConfigOption::SequencerPoolIdleTimeout),
+            Some("30s".to_owned())
+        );
+    }
+
+    #[test]
+    fn rpc_limits() {
+        let toml = r#"[rpc]
+max-request-bytes = "512KB"
+request-timeout = "30s""#;
+        let mut cfg = config_from_str(toml).unwrap();
+        assert_eq!(
+            cfg.take(ConfigOption::RpcMaxRequestBytes),
+            Some("512KB".to_owned())
+        );
+        assert_eq!(
+            cfg.take(ConfigOption::RpcRequestTimeout),
+            Some("30s".to_owned())
+        );
+    }
+
+    #[test]
+    fn rpc_number_format() {
+        let value = "decimal".to_owned();
+        let toml = format!(r#"rpc.number-format = "{}""#, value);
+        let mut cfg = config_from_str(&toml).unwrap();
+        assert_eq!(cfg.take(ConfigOption::RpcNumberFormat), Some(value));
+    }
+
+    #[test]
+    fn sequencer_timeout() {
+        let value = "value".to_owned();
+        let toml = format!(r#"sequencer.timeout = "{}""#, value);
+        let mut cfg = config_from_str(&toml).unwrap();
+        assert_eq!(cfg.take(ConfigOption::SequencerTimeout), Some(value));
+    }
+
+    #[test]
+    fn startup_reorg_buffer() {
+        let toml = r#"sync.startup-reorg-buffer = 32"#;
+        let mut cfg = config_from_str(toml).unwrap();
+        assert_eq!(
+            cfg.take(ConfigOption::StartupReorgBuffer),
+            Some("32".to_owned())
+        );
+    }
+
+    #[test]
+    fn tls_ca_bundle() {
+        let toml = r#"tls.ca-bundle = "/etc/ssl/ca.pem""#;
+        let mut cfg = config_from_str(toml).unwrap();
+        assert_eq!(
+            cfg.take(ConfigOption::TlsCaBundle),
+            Some("/etc/ssl/ca.pem".to_owned())
+        );
+    }
+
+    #[test]
+    fn sync_source() {
+        let value = "value".to_owned();
+        let toml = format!(r#"sync.source = "{}""#, value);
+        let mut cfg = config_from_str(&toml).unwrap();
+        assert_eq!(cfg.take(ConfigOption::SyncSource), Some(value));
+    }
+
     #[test]
     fn http_rpc() {
         let value = "value".to_owned();
@@ -105,4 +641,293 @@ password = "{}""#,
         let cfg = config_from_str("").unwrap();
         assert_eq!(cfg, ConfigBuilder::default());
     }
+
+    mod include {
+        use super::*;
+        use std::path::Path;
+
+        fn write(dir: &Path, name: &str, toml: &str) {
+            std::fs::write(dir.join(name), toml).unwrap();
+        }
+
+        #[test]
+        fn two_file_chain() {
+            let dir = tempfile::tempdir().unwrap();
+            write(
+                dir.path(),
+                "main.toml",
+                r#"include = ["common.toml", "secrets.toml"]
+http-rpc = "main rpc""#,
+            );
+            write(
+                dir.path(),
+                "common.toml",
+                r#"include = ["base.toml"]
+http-rpc = "common rpc"
+
+[ethereum]
+url = "common url"
+password = "common password""#,
+            );
+            write(
+                dir.path(),
+                "secrets.toml",
+                r#"ethereum.password = "secret password""#,
+            );
+            write(
+                dir.path(),
+                "base.toml",
+                r#"[ethereum]
+url = "base url"
+user = "base user""#,
+            );
+
+            let mut cfg = config_from_filepath(&dir.path().join("main.toml")).unwrap();
+            // The including file takes precedence over its includes.
+            assert_eq!(
+                cfg.take(ConfigOption::HttpRpcAddress),
+                Some("main rpc".to_owned())
+            );
+            // Later includes take precedence over earlier ones.
+            assert_eq!(
+                cfg.take(ConfigOption::EthereumPassword),
+                Some("secret password".to_owned())
+            );
+            // Nested includes are used where nothing else sets the option.
+            assert_eq!(
+                cfg.take(ConfigOption::EthereumHttpUrl),
+                Some("common url".to_owned())
+            );
+            assert_eq!(
+                cfg.take(ConfigOption::EthereumUser),
+                Some("base user".to_owned())
+            );
+            assert_eq!(cfg, ConfigBuilder::default());
+        }
+
+        #[test]
+        fn with_profile() {
+            let dir = tempfile::tempdir().unwrap();
+            write(
+                dir.path(),
+                "main.toml",
+                r#"include = ["common.toml"]
+
+[profiles.testnet]
+http-rpc = "testnet rpc""#,
+            );
+            write(
+                dir.path(),
+                "common.toml",
+                r#"http-rpc = "common rpc"
+ethereum.url = "common url""#,
+            );
+
+            let mut cfg =
+                config_from_filepath_with_profile(&dir.path().join("main.toml"), "testnet")
+                    .unwrap();
+            assert_eq!(
+                cfg.take(ConfigOption::HttpRpcAddress),
+                Some("testnet rpc".to_owned())
+            );
+            assert_eq!(
+                cfg.take(ConfigOption::EthereumHttpUrl),
+                Some("common url".to_owned())
+            );
+        }
+
+        #[test]
+        fn cycle() {
+            let dir = tempfile::tempdir().unwrap();
+            write(dir.path(), "a.toml", r#"include = ["b.toml"]"#);
+            write(dir.path(), "b.toml", r#"include = ["./a.toml"]"#);
+
+            let err = config_from_filepath(&dir.path().join("a.toml")).unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+            assert!(err.to_string().contains("Cyclic config include"), "{}", err);
+        }
+
+        #[test]
+        fn self_include() {
+            let dir = tempfile::tempdir().unwrap();
+            write(dir.path(), "a.toml", r#"include = ["a.toml"]"#);
+
+            let err = config_from_filepath(&dir.path().join("a.toml")).unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        }
+
+        #[test]
+        fn repeated_include_is_not_a_cycle() {
+            let dir = tempfile::tempdir().unwrap();
+            write(
+                dir.path(),
+                "main.toml",
+                r#"include = ["left.toml", "right.toml"]"#,
+            );
+            write(dir.path(), "left.toml", r#"include = ["base.toml"]"#);
+            write(dir.path(), "right.toml", r#"include = ["base.toml"]"#);
+            write(dir.path(), "base.toml", r#"http-rpc = "base rpc""#);
+
+            let mut cfg = config_from_filepath(&dir.path().join("main.toml")).unwrap();
+            assert_eq!(
+                cfg.take(ConfigOption::HttpRpcAddress),
+                Some("base rpc".to_owned())
+            );
+        }
+
+        #[test]
+        fn missing_include() {
+            let dir = tempfile::tempdir().unwrap();
+            write(dir.path(), "main.toml", r#"include = ["missing.toml"]"#);
+
+            let err = config_from_filepath(&dir.path().join("main.toml")).unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+            assert!(err.to_string().contains("missing.toml"), "{}", err);
+        }
+    }
+
+    mod config_error {
+        use super::*;
+        use assert_matches::assert_matches;
+        use std::error::Error;
+
+        #[test]
+        fn missing_file() {
+            let dir = tempfile::tempdir().unwrap();
+            let path = dir.path().join("missing.toml");
+
+            let err = config_from_filepath(&path).unwrap_err();
+            assert_matches!(&err, ConfigError::Read { path: p, .. } if p == &path);
+            assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+            assert!(err.to_string().contains("Failed to read"), "{}", err);
+            assert!(err.to_string().contains("missing.toml"), "{}", err);
+        }
+
+        #[test]
+        fn unreadable_file() {
+            // A directory exists but cannot be read as a file, regardless of permissions.
+            let dir = tempfile::tempdir().unwrap();
+            let err = config_from_filepath(dir.path()).unwrap_err();
+            assert_matches!(
+                &err,
+                ConfigError::Read { path, .. } if path == dir.path()
+            );
+        }
+
+        #[test]
+        fn permission_denied() {
+            let err = ConfigError::Read {
+                path: "/etc/pathfinder.toml".into(),
+                source: std::io::ErrorKind::PermissionDenied.into(),
+            };
+            assert_eq!(err.kind(), std::io::ErrorKind::PermissionDenied);
+            assert!(err.source().is_some());
+
+            let message = err.to_string();
+            assert!(message.contains("/etc/pathfinder.toml"), "{}", message);
+            assert!(message.contains("permission denied"), "{}", message);
+
+            let err = std::io::Error::from(err);
+            assert_eq!(err.kind(), std::io::ErrorKind::PermissionDenied);
+            assert_eq!(err.to_string(), message);
+        }
+
+        #[test]
+        fn parse_error() {
+            let dir = tempfile::tempdir().unwrap();
+            let path = dir.path().join("invalid.toml");
+            std::fs::write(&path, "http-rpc = 5").unwrap();
+
+            let err = config_from_filepath(&path).unwrap_err();
+            assert_matches!(&err, ConfigError::Parse { path: p, .. } if p == &path);
+            assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+            let message = err.to_string();
+            assert!(message.contains("Failed to parse"), "{}", message);
+            assert!(message.contains("invalid.toml"), "{}", message);
+            assert!(message.contains("`http-rpc`"), "{}", message);
+        }
+    }
+
+    mod profiles {
+        use super::*;
+
+        const TOML: &str = r#"http-rpc = "top-level rpc"
+
+[ethereum]
+url = "top-level url"
+user = "top-level user"
+
+[profiles.mainnet.ethereum]
+url = "mainnet url"
+
+[profiles.testnet]
+http-rpc = "testnet rpc"
+
+[profiles.testnet.ethereum]
+url = "testnet url"
+password = "testnet password""#;
+
+        #[test]
+        fn selected_profile_takes_precedence() {
+            let mut cfg = config_from_str_with_profile(TOML, "testnet").unwrap();
+            assert_eq!(
+                cfg.take(ConfigOption::EthereumHttpUrl),
+                Some("testnet url".to_owned())
+            );
+            assert_eq!(
+                cfg.take(ConfigOption::EthereumPassword),
+                Some("testnet password".to_owned())
+            );
+            assert_eq!(
+                cfg.take(ConfigOption::HttpRpcAddress),
+                Some("testnet rpc".to_owned())
+            );
+            // Not set by the profile, so falls back to the top-level value.
+            assert_eq!(
+                cfg.take(ConfigOption::EthereumUser),
+                Some("top-level user".to_owned())
+            );
+        }
+
+        #[test]
+        fn other_profile() {
+            let mut cfg = config_from_str_with_profile(TOML, "mainnet").unwrap();
+            assert_eq!(
+                cfg.take(ConfigOption::EthereumHttpUrl),
+                Some("mainnet url".to_owned())
+            );
+            assert_eq!(cfg.take(ConfigOption::EthereumPassword), None);
+            assert_eq!(
+                cfg.take(ConfigOption::HttpRpcAddress),
+                Some("top-level rpc".to_owned())
+            );
+        }
+
+        #[test]
+        fn profiles_are_ignored_without_selection() {
+            let mut cfg = config_from_str(TOML).unwrap();
+            assert_eq!(
+                cfg.take(ConfigOption::EthereumHttpUrl),
+                Some("top-level url".to_owned())
+            );
+        }
+
+        #[test]
+        fn missing_profile() {
+            let err = config_from_str_with_profile(TOML, "devnet").unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+            assert!(
+                err.to_string().contains("mainnet, testnet"),
+                "Error should list the available profiles: {}",
+                err
+            );
+        }
+
+        #[test]
+        fn missing_profile_without_profiles() {
+            let err = config_from_str_with_profile("", "devnet").unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        }
+    }
 }
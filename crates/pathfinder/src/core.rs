@@ -11,7 +11,7 @@ use web3::types::{H160, H256};
 pub struct ContractAddress(pub StarkHash);
 
 /// The salt of a StarkNet contract address.
-#[derive(Debug, Copy, Clone, PartialEq, Deserialize, Serialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub struct ContractAddressSalt(pub StarkHash);
 
 /// A StarkNet contract's hash. This is a hash over a contract's
@@ -19,19 +19,27 @@ pub struct ContractAddressSalt(pub StarkHash);
 ///
 /// Not to be confused with [ContractStateHash].
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "borsh-encoding",
+    derive(borsh::BorshSerialize, borsh::BorshDeserialize)
+)]
 pub struct ContractHash(pub StarkHash);
 
 /// A StarkNet contract's state hash. This is the value stored
 /// in the global state tree.
 ///
 /// Not to be confused with [ContractHash].
-#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct ContractStateHash(pub StarkHash);
 
+/// The nonce of a StarkNet contract, which is incremented by every transaction it sends.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ContractNonce(pub StarkHash);
+
 /// A commitment root of a StarkNet contract. This is the entry-point
 /// for a contract's state at a specific point in time via the contract
 /// state tree.
-#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct ContractRoot(pub StarkHash);
 
 /// A Starknet contract's bytecode and ABI.
@@ -42,7 +50,7 @@ pub struct ContractCode {
 }
 
 /// Entry point of a StarkNet `call`.
-#[derive(Debug, Copy, Clone, PartialEq, Deserialize, Serialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub struct EntryPoint(pub StarkHash);
 
 impl EntryPoint {
@@ -58,88 +66,92 @@ impl EntryPoint {
 }
 
 /// A single parameter passed to a StarkNet `call`.
-#[derive(Debug, Copy, Clone, PartialEq, Deserialize, Serialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub struct CallParam(pub StarkHash);
 
 /// A single parameter passed to a StarkNet contract constructor.
-#[derive(Debug, Copy, Clone, PartialEq, Deserialize, Serialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub struct ConstructorParam(pub StarkHash);
 
 /// A single result value of a StarkNet `call`.
-#[derive(Debug, Copy, Clone, PartialEq, Deserialize, Serialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub struct CallResultValue(pub StarkHash);
 
 /// A single element of a signature used to secure a StarkNet `call`.
-#[derive(Debug, Copy, Clone, PartialEq, Deserialize, Serialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub struct CallSignatureElem(pub StarkHash);
 
 /// A word from a StarkNet contract bytecode.
-#[derive(Debug, Copy, Clone, PartialEq, Deserialize, Serialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub struct ByteCodeWord(pub StarkHash);
 
 /// The address of a storage element for a StarkNet contract.
-#[derive(Debug, Copy, Clone, PartialEq, Deserialize, Serialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub struct StorageAddress(pub StarkHash);
 
 /// The value of a storage element for a StarkNet contract.
-#[derive(Debug, Copy, Clone, PartialEq, Deserialize, Serialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub struct StorageValue(pub StarkHash);
 
 /// A commitment root of the global StarkNet state. This is the entry-point
 /// for the global state at a specific point in time via the global state tree.
-#[derive(Debug, Copy, Clone, PartialEq, Deserialize, Serialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[cfg_attr(
+    feature = "borsh-encoding",
+    derive(borsh::BorshSerialize, borsh::BorshDeserialize)
+)]
 pub struct GlobalRoot(pub StarkHash);
 
 /// A StarkNet block hash.
-#[derive(Debug, Copy, Clone, PartialEq, Deserialize, Serialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub struct StarknetBlockHash(pub StarkHash);
 
 /// A StarkNet block number.
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub struct StarknetBlockNumber(pub u64);
 
 /// The timestamp of a Starknet block.
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct StarknetBlockTimestamp(pub u64);
 
 /// A StarkNet transaction hash.
-#[derive(Debug, Copy, Clone, PartialEq, Deserialize, Serialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub struct StarknetTransactionHash(pub StarkHash);
 
 /// A StarkNet transaction hash.
-#[derive(Debug, Copy, Clone, PartialEq, Deserialize, Serialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub struct StarknetTransactionIndex(pub u64);
 
 /// A single element of a signature used to secure a StarkNet transaction.
-#[derive(Debug, Copy, Clone, PartialEq, Deserialize, Serialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub struct TransactionSignatureElem(pub StarkHash);
 
 /// A nonce that is added to an L1 to L2 message in a StarkNet transaction.
-#[derive(Debug, Copy, Clone, PartialEq, Deserialize, Serialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub struct L1ToL2MessageNonce(pub StarkHash);
 
 /// A single element of the payload of an L1 to L2 message in a StarkNet transaction.
-#[derive(Debug, Copy, Clone, PartialEq, Deserialize, Serialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub struct L1ToL2MessagePayloadElem(pub StarkHash);
 
 /// A single element of the payload of an L2 to L1 message in a StarkNet transaction.
-#[derive(Debug, Copy, Clone, PartialEq, Deserialize, Serialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub struct L2ToL1MessagePayloadElem(pub StarkHash);
 
 /// StarkNet transaction event data.
-#[derive(Debug, Copy, Clone, PartialEq, Deserialize, Serialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub struct EventData(pub StarkHash);
 
 /// StarkNet transaction event key.
-#[derive(Debug, Copy, Clone, PartialEq, Deserialize, Serialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub struct EventKey(pub StarkHash);
 
 /// StarkNet protocol version.
-#[derive(Debug, Copy, Clone, PartialEq, Deserialize, Serialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub struct StarknetProtocolVersion(pub H256);
 
 /// An Ethereum address.
-#[derive(Debug, Copy, Clone, PartialEq, Deserialize, Serialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub struct EthereumAddress(pub H160);
 
 /// An Ethereum block hash.
@@ -162,6 +174,102 @@ pub struct EthereumTransactionIndex(pub u64);
 #[derive(Debug, Copy, Clone, PartialEq, Hash, Eq)]
 pub struct EthereumLogIndex(pub u64);
 
+/// Implements the fixed size big-endian byte conversions of [StarkHash] for its newtypes.
+macro_rules! impl_be_bytes {
+    ($($type:ty),*) => {
+        $(
+            impl $type {
+                /// Big-endian representation of the inner [StarkHash].
+                pub fn to_be_bytes(self) -> [u8; 32] {
+                    self.0.to_be_bytes()
+                }
+
+                /// See [StarkHash::from_be_bytes].
+                pub fn from_be_bytes(bytes: [u8; 32]) -> Result<Self, pedersen::OverflowError> {
+                    StarkHash::from_be_bytes(bytes).map(Self)
+                }
+            }
+        )*
+    };
+}
+
+impl_be_bytes!(ContractHash, GlobalRoot);
+
+/// Implements the conversions to and from the inner [StarkHash] of its newtypes.
+///
+/// There are deliberately no conversions between the newtypes themselves, converting for example
+/// a [ContractHash] into a [GlobalRoot] has to go through [StarkHash] explicitly.
+macro_rules! impl_stark_hash_newtype {
+    ($($type:ty),*) => {
+        $(
+            impl $type {
+                /// A reference to the inner [StarkHash].
+                pub fn as_inner(&self) -> &StarkHash {
+                    &self.0
+                }
+
+                /// Unwraps the inner [StarkHash].
+                pub fn into_inner(self) -> StarkHash {
+                    self.0
+                }
+            }
+
+            impl From<StarkHash> for $type {
+                fn from(hash: StarkHash) -> Self {
+                    Self(hash)
+                }
+            }
+
+            impl From<$type> for StarkHash {
+                fn from(value: $type) -> Self {
+                    value.0
+                }
+            }
+        )*
+    };
+}
+
+impl_stark_hash_newtype!(
+    ContractAddress,
+    ContractAddressSalt,
+    ContractHash,
+    ContractStateHash,
+    ContractNonce,
+    ContractRoot,
+    EntryPoint,
+    CallParam,
+    ConstructorParam,
+    CallResultValue,
+    CallSignatureElem,
+    ByteCodeWord,
+    StorageAddress,
+    StorageValue,
+    GlobalRoot,
+    StarknetBlockHash,
+    StarknetTransactionHash,
+    TransactionSignatureElem,
+    L1ToL2MessageNonce,
+    L1ToL2MessagePayloadElem,
+    L2ToL1MessagePayloadElem,
+    EventData,
+    EventKey
+);
+
+impl ContractHash {
+    /// The `0x` prefixed lowercase hex form with leading zeros trimmed, as echoed by the
+    /// feeder gateway, e.g. `0x0` for [StarkHash::ZERO].
+    pub fn to_gateway_string(&self) -> String {
+        self.0.to_hex_str()
+    }
+}
+
+/// Formats as `0x` prefixed, zero-padded 64 character lowercase hex.
+impl std::fmt::Display for ContractHash {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "0x{:x}", self.0)
+    }
+}
+
 impl StarknetBlockNumber {
     pub const GENESIS: StarknetBlockNumber = StarknetBlockNumber(0);
 }
@@ -217,3 +325,197 @@ impl From<StarknetBlockHash> for crate::rpc::types::BlockHashOrTag {
         crate::rpc::types::BlockHashOrTag::Hash(hash)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BYTES: [u8; 32] = [
+        0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B, 0x0C, 0x0D, 0x0E,
+        0x0F, 0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1A, 0x1B, 0x1C, 0x1D,
+        0x1E, 0x1F,
+    ];
+
+    #[test]
+    fn contract_hash_be_bytes_round_trip() {
+        let hash = ContractHash::from_be_bytes(BYTES).unwrap();
+        assert_eq!(hash.to_be_bytes(), BYTES);
+        assert_eq!(hash, ContractHash(StarkHash::from_be_bytes(BYTES).unwrap()));
+    }
+
+    #[test]
+    fn global_root_be_bytes_round_trip() {
+        let root = GlobalRoot::from_be_bytes(BYTES).unwrap();
+        assert_eq!(root.to_be_bytes(), BYTES);
+        assert_eq!(root, GlobalRoot(StarkHash::from_be_bytes(BYTES).unwrap()));
+    }
+
+    #[test]
+    fn be_bytes_overflow() {
+        assert!(ContractHash::from_be_bytes([0xff; 32]).is_err());
+        assert!(GlobalRoot::from_be_bytes([0xff; 32]).is_err());
+    }
+
+    #[test]
+    fn contract_hash_map_key() {
+        use std::collections::{HashMap, HashSet};
+
+        let hash = ContractHash::from_be_bytes(BYTES).unwrap();
+        let other = ContractHash(StarkHash::ZERO);
+
+        let mut map = HashMap::new();
+        map.insert(hash, 1);
+        map.insert(other, 2);
+        assert_eq!(
+            map.get(&ContractHash::from_be_bytes(BYTES).unwrap()),
+            Some(&1)
+        );
+        assert_eq!(map.get(&other), Some(&2));
+
+        let set = [hash, other, hash].into_iter().collect::<HashSet<_>>();
+        assert_eq!(set.len(), 2);
+    }
+
+    mod stark_hash_conversions {
+        use super::*;
+
+        /// Fails to compile if `$target` implements `From<$source>`.
+        ///
+        /// If the impl exists, both blanket impls of `AmbiguousIfFrom` apply to `$target` and the
+        /// `_` parameter cannot be inferred.
+        macro_rules! assert_not_from {
+            ($target:ty, $source:ty) => {{
+                trait AmbiguousIfFrom<A> {
+                    fn some_item() {}
+                }
+                impl<T: ?Sized> AmbiguousIfFrom<()> for T {}
+                #[allow(dead_code)]
+                struct Invalid;
+                impl<T: ?Sized + From<$source>> AmbiguousIfFrom<Invalid> for T {}
+
+                <$target as AmbiguousIfFrom<_>>::some_item()
+            }};
+        }
+
+        #[test]
+        fn round_trip() {
+            let hash = StarkHash::from_be_bytes(BYTES).unwrap();
+
+            let contract_hash = ContractHash::from(hash);
+            assert_eq!(contract_hash, ContractHash(hash));
+            assert_eq!(contract_hash.as_inner(), &hash);
+            assert_eq!(contract_hash.into_inner(), hash);
+            assert_eq!(StarkHash::from(contract_hash), hash);
+
+            let root: GlobalRoot = hash.into();
+            assert_eq!(root, GlobalRoot(hash));
+            let inner: StarkHash = root.into();
+            assert_eq!(inner, hash);
+
+            let address: ContractAddress = hash.into();
+            assert_eq!(address.into_inner(), hash);
+        }
+
+        #[test]
+        fn cross_conversion_is_explicit() {
+            let contract_hash = ContractHash::from_be_bytes(BYTES).unwrap();
+            let root = GlobalRoot::from(contract_hash.into_inner());
+            assert_eq!(root.as_inner(), contract_hash.as_inner());
+        }
+
+        #[test]
+        fn no_cross_conversions() {
+            assert_not_from!(GlobalRoot, ContractHash);
+            assert_not_from!(ContractHash, GlobalRoot);
+            assert_not_from!(ContractHash, ContractStateHash);
+            assert_not_from!(ContractStateHash, ContractHash);
+            assert_not_from!(ContractAddress, ContractHash);
+            assert_not_from!(StorageAddress, StorageValue);
+            assert_not_from!(StarknetBlockHash, StarknetTransactionHash);
+        }
+    }
+
+    mod hex_case {
+        use super::*;
+
+        #[test]
+        fn any_case_is_accepted() {
+            let upper = serde_json::from_str::<GlobalRoot>(r#""0xABCDEF""#).unwrap();
+            let lower = serde_json::from_str::<GlobalRoot>(r#""0xabcdef""#).unwrap();
+            let mixed = serde_json::from_str::<GlobalRoot>(r#""0XaBcDeF""#).unwrap();
+            assert_eq!(upper, lower);
+            assert_eq!(upper, mixed);
+
+            let upper = serde_json::from_str::<ContractAddress>(r#""0xABCDEF""#).unwrap();
+            let lower = serde_json::from_str::<ContractAddress>(r#""0xabcdef""#).unwrap();
+            assert_eq!(upper, lower);
+        }
+
+        #[test]
+        fn output_is_lowercase() {
+            let hash = serde_json::from_str::<ContractHash>(r#""0xABCDEF""#).unwrap();
+            assert_eq!(serde_json::to_string(&hash).unwrap(), r#""0xabcdef""#);
+            assert_eq!(hash.to_gateway_string(), "0xabcdef");
+            assert_eq!(
+                hash.to_string(),
+                "0x0000000000000000000000000000000000000000000000000000000000abcdef"
+            );
+
+            let root = GlobalRoot(hash.0);
+            assert_eq!(serde_json::to_string(&root).unwrap(), r#""0xabcdef""#);
+            assert_eq!(
+                root.0.to_string(),
+                "0x0000000000000000000000000000000000000000000000000000000000abcdef"
+            );
+        }
+    }
+
+    mod contract_hash_fmt {
+        use super::*;
+
+        #[test]
+        fn zero() {
+            let hash = ContractHash(StarkHash::ZERO);
+            assert_eq!(
+                hash.to_string(),
+                "0x0000000000000000000000000000000000000000000000000000000000000000"
+            );
+            assert_eq!(hash.to_gateway_string(), "0x0");
+        }
+
+        #[test]
+        fn full_value() {
+            let hash = ContractHash::from_be_bytes(BYTES).unwrap();
+            assert_eq!(
+                hash.to_string(),
+                "0x000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f"
+            );
+            assert_eq!(
+                hash.to_gateway_string(),
+                "0x102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f"
+            );
+        }
+    }
+
+    #[cfg(feature = "borsh-encoding")]
+    mod borsh_encoding {
+        use super::*;
+        use borsh::{BorshDeserialize, BorshSerialize};
+
+        #[test]
+        fn contract_hash_round_trip() {
+            let hash = ContractHash::from_be_bytes(BYTES).unwrap();
+            let encoded = hash.try_to_vec().unwrap();
+            assert_eq!(encoded, BYTES);
+            assert_eq!(ContractHash::try_from_slice(&encoded).unwrap(), hash);
+        }
+
+        #[test]
+        fn global_root_round_trip() {
+            let root = GlobalRoot::from_be_bytes(BYTES).unwrap();
+            let encoded = root.try_to_vec().unwrap();
+            assert_eq!(encoded, BYTES);
+            assert_eq!(GlobalRoot::try_from_slice(&encoded).unwrap(), root);
+        }
+    }
+}
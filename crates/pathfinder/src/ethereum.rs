@@ -20,6 +20,27 @@ pub enum Chain {
     Goerli,
 }
 
+impl Chain {
+    /// The Ethereum chain ID of the chain.
+    pub fn chain_id(&self) -> u64 {
+        match self {
+            Chain::Mainnet => 1,
+            Chain::Goerli => 5,
+        }
+    }
+
+    /// The [Chain] of the given Ethereum chain ID.
+    ///
+    /// Will error if it's not one of the valid Starknet [Chain] variants.
+    pub fn from_chain_id(id: u64) -> anyhow::Result<Chain> {
+        match id {
+            1 => Ok(Chain::Mainnet),
+            5 => Ok(Chain::Goerli),
+            other => anyhow::bail!("Unsupported chain ID: {}", other),
+        }
+    }
+}
+
 /// List of semi-official Ethereum RPC errors taken from [EIP-1474] (which is stagnant).
 ///
 /// The issue of standardizing the Ethereum RPC seems to now be taking
@@ -145,11 +166,44 @@ impl TryFrom<&web3::types::Log> for EthOrigin {
 ///
 /// Will error if it's not one of the valid Starknet [Chain] variants.
 pub async fn chain<T: Transport>(transport: &Web3<T>) -> anyhow::Result<Chain> {
-    match transport.eth().chain_id().await? {
-        id if id == U256::from(1u32) => Ok(Chain::Mainnet),
-        id if id == U256::from(5u32) => Ok(Chain::Goerli),
-        other => anyhow::bail!("Unsupported chain ID: {}", other),
+    let id = transport.eth().chain_id().await?;
+    anyhow::ensure!(id <= U256::from(u64::MAX), "Unsupported chain ID: {}", id);
+
+    Chain::from_chain_id(id.as_u64())
+}
+
+/// Identifies the Ethereum [Chain] from the configured `chain_id` override, or from the transport
+/// using [chain] if there is no override.
+///
+/// The override is still checked against the `eth_chainId` of the transport, but a mismatch or a
+/// failing query only logs a warning. This keeps the override usable behind proxies which
+/// misreport or don't support `eth_chainId`.
+pub async fn chain_with_override<T: Transport>(
+    transport: &Web3<T>,
+    chain_id: Option<u64>,
+) -> anyhow::Result<Chain> {
+    let chain_id = match chain_id {
+        Some(chain_id) => chain_id,
+        None => return chain(transport).await,
+    };
+
+    let configured = Chain::from_chain_id(chain_id).context("Ethereum chain ID override")?;
+
+    match transport.eth().chain_id().await {
+        Ok(actual) if actual == U256::from(chain_id) => {}
+        Ok(actual) => tracing::warn!(
+            configured=%chain_id,
+            %actual,
+            "Configured Ethereum chain ID does not match the chain ID of the Ethereum endpoint"
+        ),
+        Err(err) => tracing::warn!(
+            configured=%chain_id,
+            error=%err,
+            "Failed to check the configured Ethereum chain ID against the Ethereum endpoint"
+        ),
     }
+
+    Ok(configured)
 }
 
 #[cfg(test)]
@@ -212,4 +266,84 @@ pub mod test {
             assert_eq!(chain, expected_chain);
         }
     }
+
+    mod chain_with_override {
+        use super::*;
+        use tracing_test::traced_test;
+
+        /// A local JSON-RPC server which answers `eth_chainId` with `result`, or with an error if
+        /// `result` is [None].
+        fn transport(result: Option<&'static str>) -> Web3<web3::transports::Http> {
+            crate::test_utils::mock_json_rpc(move |_| match result {
+                Some(result) => Ok(serde_json::json!(result)),
+                None => Err(serde_json::json!({"code": -32601, "message": "Method not found"})),
+            })
+        }
+
+        #[tokio::test]
+        async fn without_override_detects_chain() {
+            let chain = chain_with_override(&transport(Some("0x5")), None)
+                .await
+                .unwrap();
+            assert_eq!(chain, Chain::Goerli);
+        }
+
+        #[tokio::test]
+        #[traced_test]
+        async fn matching_override() {
+            let chain = chain_with_override(&transport(Some("0x1")), Some(1))
+                .await
+                .unwrap();
+            assert_eq!(chain, Chain::Mainnet);
+            assert!(!logs_contain("chain ID"));
+        }
+
+        #[tokio::test]
+        #[traced_test]
+        async fn mismatch_is_a_warning() {
+            let chain = chain_with_override(&transport(Some("0x1")), Some(5))
+                .await
+                .unwrap();
+            assert_eq!(chain, Chain::Goerli);
+            assert!(logs_contain("does not match"));
+        }
+
+        #[tokio::test]
+        #[traced_test]
+        async fn failed_check_is_a_warning() {
+            let chain = chain_with_override(&transport(None), Some(5))
+                .await
+                .unwrap();
+            assert_eq!(chain, Chain::Goerli);
+            assert!(logs_contain("Failed to check"));
+        }
+
+        #[tokio::test]
+        async fn without_override_rejects_unsupported_chain() {
+            for id in ["0xa", "0x10000000000000001"] {
+                let error = chain_with_override(&transport(Some(id)), None)
+                    .await
+                    .unwrap_err();
+                assert!(
+                    error.to_string().starts_with("Unsupported chain ID"),
+                    "{}",
+                    error
+                );
+            }
+        }
+
+        #[tokio::test]
+        async fn unsupported_override_is_an_error() {
+            chain_with_override(&transport(Some("0xa")), Some(10))
+                .await
+                .unwrap_err();
+        }
+
+        #[test]
+        fn chain_id_round_trip() {
+            for chain in [Chain::Mainnet, Chain::Goerli] {
+                assert_eq!(Chain::from_chain_id(chain.chain_id()).unwrap(), chain);
+            }
+        }
+    }
 }
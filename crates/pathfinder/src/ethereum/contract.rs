@@ -61,6 +61,16 @@ const MEMPAGE_ABI: &[u8] = include_bytes!(concat!(
     "/resources/contracts/memory_page_fact_registry.json"
 ));
 
+/// Canonical signature of [STATE_UPDATE_EVENT]. Its Keccak256 hash is the `topic0` of the logs.
+pub const STATE_UPDATE_EVENT_SIGNATURE: &str = "LogStateUpdate(uint256,int256)";
+/// Canonical signature of [STATE_TRANSITION_FACT_EVENT].
+pub const STATE_TRANSITION_FACT_EVENT_SIGNATURE: &str = "LogStateTransitionFact(bytes32)";
+/// Canonical signature of [MEMORY_PAGE_HASHES_EVENT].
+pub const MEMORY_PAGE_HASHES_EVENT_SIGNATURE: &str = "LogMemoryPagesHashes(bytes32,bytes32[])";
+/// Canonical signature of [MEMORY_PAGE_FACT_CONTINUOUS_EVENT].
+pub const MEMORY_PAGE_FACT_CONTINUOUS_EVENT_SIGNATURE: &str =
+    "LogMemoryPageFactContinuous(bytes32,uint256,uint256)";
+
 lazy_static::lazy_static!(
     pub static ref STATE_UPDATE_EVENT: Event = core_contract().event("LogStateUpdate")
             .expect("LogStateUpdate event not found in core contract ABI").to_owned();
@@ -259,6 +269,30 @@ mod tests {
             let _event = STATE_UPDATE_EVENT.clone();
         }
 
+        #[test]
+        fn signatures_match_abi() {
+            use sha3::{Digest, Keccak256};
+
+            for (event, signature) in [
+                (&*STATE_UPDATE_EVENT, STATE_UPDATE_EVENT_SIGNATURE),
+                (
+                    &*STATE_TRANSITION_FACT_EVENT,
+                    STATE_TRANSITION_FACT_EVENT_SIGNATURE,
+                ),
+                (
+                    &*MEMORY_PAGE_HASHES_EVENT,
+                    MEMORY_PAGE_HASHES_EVENT_SIGNATURE,
+                ),
+                (
+                    &*MEMORY_PAGE_FACT_CONTINUOUS_EVENT,
+                    MEMORY_PAGE_FACT_CONTINUOUS_EVENT_SIGNATURE,
+                ),
+            ] {
+                let hash = web3::types::H256::from_slice(&Keccak256::digest(signature.as_bytes()));
+                assert_eq!(event.signature(), hash, "{}", signature);
+            }
+        }
+
         #[test]
         fn state_transition_fact() {
             let _event = STATE_TRANSITION_FACT_EVENT.clone();
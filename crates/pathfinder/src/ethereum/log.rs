@@ -3,14 +3,16 @@ mod parse;
 
 pub use fetch::*;
 
+use anyhow::Context;
 use web3::{
-    types::{Filter, H256},
+    types::{BlockNumber, Filter, FilterBuilder, H256},
     Transport, Web3,
 };
 
 use crate::{
-    core::{GlobalRoot, StarknetBlockNumber},
-    ethereum::{EthOrigin, RpcErrorCode},
+    core::{EthereumBlockNumber, GlobalRoot, StarknetBlockNumber},
+    ethereum::{Chain, EthOrigin, RpcErrorCode},
+    retry::RetryBudget,
 };
 
 /// Describes a state update log event. Is always emitted
@@ -24,6 +26,26 @@ pub struct StateUpdateLog {
     pub block_number: StarknetBlockNumber,
 }
 
+/// A compact, serializable summary of a [StateUpdateLog], from which L1 sync can be resumed
+/// after a restart.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct SyncCheckpoint {
+    pub starknet_block: StarknetBlockNumber,
+    pub global_root: GlobalRoot,
+    /// The L1 block which contains the [StateUpdateLog].
+    pub eth_block: u64,
+}
+
+impl From<&StateUpdateLog> for SyncCheckpoint {
+    fn from(log: &StateUpdateLog) -> Self {
+        Self {
+            starknet_block: log.block_number,
+            global_root: log.global_root,
+            eth_block: log.origin.block.number.0,
+        }
+    }
+}
+
 /// Links a [StateUpdateLog] event to its data -- which is contained
 /// by a [MemoryPagesHashesLog] fact log.
 ///
@@ -57,6 +79,77 @@ pub struct MemoryPageFactContinuousLog {
     pub hash: H256,
 }
 
+/// A parsed log, or the removal of a previously emitted log.
+///
+/// Logs are removed when the L1 block containing them is reorged away. Consumers which have
+/// already processed the log at [EthOrigin] need to roll it back.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LogEvent<T> {
+    Added(T),
+    Removed(EthOrigin),
+}
+
+/// An inclusive range of L1 blocks to query logs from.
+///
+/// The bounds are [EthereumBlockNumbers](EthereumBlockNumber), which keeps them apart from the
+/// [StarknetBlockNumbers](StarknetBlockNumber) carried by the logs themselves.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct EthBlockRange {
+    pub from: EthereumBlockNumber,
+    pub to: EthereumBlockNumber,
+}
+
+impl EthBlockRange {
+    pub fn new(from: EthereumBlockNumber, to: EthereumBlockNumber) -> Self {
+        Self { from, to }
+    }
+
+    /// Sets the `fromBlock` and `toBlock` of `filter` to this range, replacing any existing
+    /// bounds.
+    pub fn apply(&self, filter: FilterBuilder) -> FilterBuilder {
+        filter
+            .from_block(BlockNumber::Number(self.from.0.into()))
+            .to_block(BlockNumber::Number(self.to.0.into()))
+    }
+
+    /// Builds `filter` limited to this range.
+    pub fn filter(&self, filter: FilterBuilder) -> Filter {
+        self.apply(filter).build()
+    }
+}
+
+/// The [GlobalRoot] of the empty StarkNet state, which is what the genesis
+/// [StateUpdateLog] is applied to.
+///
+/// This is the root of an empty global state tree, which is zero.
+pub fn genesis_global_root() -> GlobalRoot {
+    GlobalRoot(pedersen::StarkHash::ZERO)
+}
+
+/// Returns true if the [StateUpdateLog] is for the genesis StarkNet block.
+pub fn is_genesis(log: &StateUpdateLog) -> bool {
+    log.block_number == StarknetBlockNumber::GENESIS
+}
+
+/// Verifies that the [StateUpdateLogs](StateUpdateLog) are for consecutive StarkNet blocks, each
+/// incrementing the block number of the previous log by one.
+///
+/// A gap or a repeated block number means that logs are missing or were fetched out of order.
+/// The global roots can't be verified here, as each log only contains the root after its update.
+pub fn verify_state_update_sequence(logs: &[StateUpdateLog]) -> anyhow::Result<()> {
+    for pair in logs.windows(2) {
+        let (previous, next) = (&pair[0], &pair[1]);
+        anyhow::ensure!(
+            next.block_number.0 == previous.block_number.0 + 1,
+            "State update for block {} follows block {}",
+            next.block_number.0,
+            previous.block_number.0
+        );
+    }
+
+    Ok(())
+}
+
 /// Error return by [get_logs].
 #[derive(Debug)]
 enum GetLogsError {
@@ -68,11 +161,22 @@ enum GetLogsError {
     Other(anyhow::Error),
 }
 
+/// The number of times [get_logs] retries the spurious decoder error of some endpoints.
+const DECODER_ERROR_RETRIES: usize = 10;
+
 /// Wraps the Ethereum get_logs call to handle [GetLogsError::QueryLimit] situations.
+///
+/// `retry_decoder_errors` controls whether the spurious decoder error of some endpoints
+/// is retried (up to [DECODER_ERROR_RETRIES] times, one second apart), or returned as
+/// [GetLogsError::Other]. Retries are also drawn from `budget`, if there is one.
 async fn get_logs<T: Transport>(
     transport: &Web3<T>,
     filter: Filter,
+    retry_decoder_errors: bool,
+    budget: Option<&RetryBudget>,
 ) -> Result<Vec<web3::types::Log>, GetLogsError> {
+    use crate::retry::{retry, Retriable, RetryPolicy};
+    use std::num::{NonZeroU64, NonZeroUsize};
     use web3::Error::*;
     use RpcErrorCode::*;
     /// Error message generated by spurious decoder error which occurs on Infura endpoints from
@@ -83,54 +187,613 @@ async fn get_logs<T: Transport>(
         "One of the blocks specified in filter (fromBlock, toBlock or blockHash) cannot be found.";
     const ALCHEMY_QUERY_TIMEOUT_ERR: &str =
         "Query timeout exceeded. Consider reducing your block range.";
-    loop {
-        match transport.eth().logs(filter.clone()).await {
-            Ok(logs) => return Ok(logs),
-            Err(Rpc(err)) if err.code.code() == LimitExceeded.code() => {
-                return Err(GetLogsError::QueryLimit);
-            }
-            Err(Rpc(err))
-                if err.code.code() == InvalidParams.code()
-                    && err.message.starts_with("Log response size exceeded") =>
-            {
-                // Handle Alchemy query limit error response. Uses InvalidParams which is unusual.
-                return Err(GetLogsError::QueryLimit);
-            }
-            Err(Rpc(err))
-                if err.code.code() == InvalidInput.code()
-                    && err.message == ALCHEMY_UNKNOWN_BLOCK_ERR =>
-            {
-                return Err(GetLogsError::UnknownBlock);
-            }
-            Err(Rpc(err))
-                if err.code.code() == InvalidInput.code()
-                    && err.message == ALCHEMY_QUERY_TIMEOUT_ERR =>
-            {
-                return Err(GetLogsError::QueryLimit);
-            }
-            Err(Decoder(err)) if err == DECODER_ERR => {
-                // This is a fix for a spurious decoder error which seems to occur when using
-                // an Infura endpoint. We simply retry the call again.
-                tracing::trace!("Spurious log decoder error occurred, retrying");
+
+    // Constant backoff of 1 second.
+    let policy = RetryPolicy::exponential(NonZeroU64::new(1).unwrap())
+        .max_num_retries(NonZeroUsize::new(DECODER_ERROR_RETRIES).unwrap());
+    let policy = match budget {
+        Some(budget) => policy.budget(budget.clone()),
+        None => policy,
+    };
+    let classify = |err: &web3::Error| match err {
+        Decoder(err) if retry_decoder_errors && err == DECODER_ERR => {
+            // This is a fix for a spurious decoder error which seems to occur when using
+            // an Infura endpoint. We simply retry the call again.
+            tracing::trace!("Spurious log decoder error occurred, retrying");
+            Retriable::Transient
+        }
+        _ => Retriable::Permanent,
+    };
+
+    match retry(&policy, classify, || transport.eth().logs(filter.clone())).await {
+        Ok(logs) => Ok(logs),
+        Err(Rpc(err)) if err.code.code() == LimitExceeded.code() => Err(GetLogsError::QueryLimit),
+        Err(Rpc(err))
+            if err.code.code() == InvalidParams.code()
+                && err.message.starts_with("Log response size exceeded") =>
+        {
+            // Handle Alchemy query limit error response. Uses InvalidParams which is unusual.
+            Err(GetLogsError::QueryLimit)
+        }
+        Err(Rpc(err))
+            if err.code.code() == InvalidInput.code()
+                && err.message == ALCHEMY_UNKNOWN_BLOCK_ERR =>
+        {
+            Err(GetLogsError::UnknownBlock)
+        }
+        Err(Rpc(err))
+            if err.code.code() == InvalidInput.code()
+                && err.message == ALCHEMY_QUERY_TIMEOUT_ERR =>
+        {
+            Err(GetLogsError::QueryLimit)
+        }
+        Err(other) => Err(GetLogsError::Other(anyhow::anyhow!(
+            "Error getting logs: {:?}",
+            other
+        ))),
+    }
+}
+
+/// The number of times [get_logs_retrying_unknown_block] retries a [GetLogsError::UnknownBlock].
+const UNKNOWN_BLOCK_RETRIES: usize = 3;
+
+/// [get_logs], but [GetLogsError::UnknownBlock] is retried up to [UNKNOWN_BLOCK_RETRIES] times,
+/// one second apart, before it is returned.
+///
+/// Near the chain tip, a block we learned about from one node of the provider can still be
+/// unknown to the node answering the log query. Such blocks usually become known shortly after.
+///
+/// Only use this for ranges which are known to exist, the forward log fetcher for example queries
+/// past the chain tip on purpose and handles [GetLogsError::UnknownBlock] itself.
+async fn get_logs_retrying_unknown_block<T: Transport>(
+    transport: &Web3<T>,
+    filter: Filter,
+    retry_decoder_errors: bool,
+    budget: Option<&RetryBudget>,
+) -> Result<Vec<web3::types::Log>, GetLogsError> {
+    use crate::retry::{retry, Retriable, RetryPolicy};
+    use std::num::{NonZeroU64, NonZeroUsize};
+
+    // Constant backoff of 1 second.
+    let policy = RetryPolicy::exponential(NonZeroU64::new(1).unwrap())
+        .max_num_retries(NonZeroUsize::new(UNKNOWN_BLOCK_RETRIES).unwrap());
+    let policy = match budget {
+        Some(budget) => policy.budget(budget.clone()),
+        None => policy,
+    };
+    let classify = |err: &GetLogsError| match err {
+        GetLogsError::UnknownBlock => {
+            tracing::trace!("Unknown block in log query, retrying");
+            Retriable::Transient
+        }
+        _ => Retriable::Permanent,
+    };
+
+    retry(&policy, classify, || {
+        get_logs(transport, filter.clone(), retry_decoder_errors, budget)
+    })
+    .await
+}
+
+/// Returns the most recent [StateUpdateLog] emitted on L1.
+///
+/// Searches backwards from the current L1 head, but gives up after 100 000 blocks
+/// (roughly two weeks) as state updates are expected every few hours.
+pub async fn latest_state_update<T: Transport>(
+    transport: &Web3<T>,
+    chain: Chain,
+) -> anyhow::Result<StateUpdateLog> {
+    const MAX_LOOKBACK: u64 = 100_000;
+
+    let filter = FilterBuilder::default()
+        .address(vec![StateUpdateLog::contract_address(chain)])
+        .topics(Some(vec![StateUpdateLog::signature()]), None, None, None);
+
+    match recent_logs(transport, filter, 1, MAX_LOOKBACK)
+        .await?
+        .into_iter()
+        .next()
+    {
+        Some(log) => StateUpdateLog::try_from(log).context("Parsing state update log"),
+        None => anyhow::bail!(
+            "No state update found in the latest {} L1 blocks",
+            MAX_LOOKBACK
+        ),
+    }
+}
+
+/// Returns up to `limit` of the most recent logs matching `filter`, newest first. Logs which
+/// were removed by a reorg are skipped.
+///
+/// Searches backwards from the current L1 head in small block ranges, so that finding the newest
+/// logs does not scan the whole chain, and gives up after `max_lookback` blocks. The block range of
+/// `filter` is replaced.
+pub async fn recent_logs<T: Transport>(
+    transport: &Web3<T>,
+    filter: FilterBuilder,
+    limit: usize,
+    max_lookback: u64,
+) -> anyhow::Result<Vec<web3::types::Log>> {
+    let latest = transport
+        .eth()
+        .block_number()
+        .await
+        .context("Get latest block number from L1")?
+        .as_u64();
+
+    let lower_bound = latest.saturating_sub(max_lookback);
+    let mut to_block = latest;
+    let mut stride = 1_000;
+    let mut recent = Vec::new();
+
+    while recent.len() < limit {
+        let from_block = to_block.saturating_sub(stride).max(lower_bound);
+        let range = EthBlockRange::new(
+            EthereumBlockNumber(from_block),
+            EthereumBlockNumber(to_block),
+        )
+        .filter(filter.clone());
+
+        let logs = match get_logs_retrying_unknown_block(transport, range, true, None).await {
+            Ok(logs) => logs,
+            Err(GetLogsError::QueryLimit) if stride > 1 => {
+                stride /= 2;
                 continue;
             }
-            Err(other) => {
-                return Err(GetLogsError::Other(anyhow::anyhow!(
-                    "Error getting logs: {:?}",
-                    other
-                )));
+            Err(GetLogsError::QueryLimit) => {
+                anyhow::bail!(
+                    "Query limit exceeded for L1 blocks {}-{}",
+                    from_block,
+                    to_block
+                )
+            }
+            Err(GetLogsError::UnknownBlock) => {
+                anyhow::bail!("L1 blocks {}-{} are unknown", from_block, to_block)
             }
+            Err(GetLogsError::Other(other)) => return Err(other),
         };
+
+        // Logs are returned in ascending order.
+        recent.extend(
+            logs.into_iter()
+                .rev()
+                .filter(|log| log.removed != Some(true))
+                .take(limit - recent.len()),
+        );
+
+        if from_block == lower_bound {
+            break;
+        }
+
+        to_block = from_block - 1;
     }
+
+    Ok(recent)
 }
 
 #[cfg(test)]
 mod tests {
+    mod genesis {
+        use pedersen::StarkHash;
+        use web3::types::H256;
+
+        use crate::{
+            core::{
+                EthereumBlockHash, EthereumBlockNumber, EthereumLogIndex, EthereumTransactionHash,
+                EthereumTransactionIndex, GlobalRoot, StarknetBlockNumber,
+            },
+            ethereum::{
+                log::{genesis_global_root, is_genesis, StateUpdateLog},
+                BlockOrigin, EthOrigin, TransactionOrigin,
+            },
+            state::state_tree::GlobalStateTree,
+            storage::Storage,
+        };
+
+        fn update_log(block_number: u64) -> StateUpdateLog {
+            StateUpdateLog {
+                origin: EthOrigin {
+                    block: BlockOrigin {
+                        hash: EthereumBlockHash(H256::from_low_u64_le(10)),
+                        number: EthereumBlockNumber(10),
+                    },
+                    transaction: TransactionOrigin {
+                        hash: EthereumTransactionHash(H256::from_low_u64_le(11)),
+                        index: EthereumTransactionIndex(12),
+                    },
+                    log_index: EthereumLogIndex(11),
+                },
+                global_root: GlobalRoot(StarkHash::from_hex_str("12354").unwrap()),
+                block_number: StarknetBlockNumber(block_number),
+            }
+        }
+
+        #[test]
+        fn global_root_is_empty_tree_root() {
+            assert_eq!(genesis_global_root(), GlobalRoot(StarkHash::ZERO));
+
+            let storage = Storage::in_memory().unwrap();
+            let mut conn = storage.connection().unwrap();
+            let transaction = conn.transaction().unwrap();
+
+            let tree = GlobalStateTree::load(&transaction, genesis_global_root()).unwrap();
+            assert_eq!(tree.apply().unwrap(), genesis_global_root());
+        }
+
+        #[test]
+        fn block_zero_is_genesis() {
+            assert!(is_genesis(&update_log(0)));
+        }
+
+        #[test]
+        fn other_blocks_are_not_genesis() {
+            assert!(!is_genesis(&update_log(1)));
+            assert!(!is_genesis(&update_log(1000)));
+        }
+    }
+
+    mod verify_state_update_sequence {
+        use super::super::verify_state_update_sequence;
+        use crate::{
+            core::{
+                EthereumBlockHash, EthereumBlockNumber, EthereumLogIndex, EthereumTransactionHash,
+                EthereumTransactionIndex, GlobalRoot, StarknetBlockNumber,
+            },
+            ethereum::{log::StateUpdateLog, BlockOrigin, EthOrigin, TransactionOrigin},
+        };
+        use pedersen::StarkHash;
+        use web3::types::H256;
+
+        fn logs(block_numbers: &[u64]) -> Vec<StateUpdateLog> {
+            block_numbers
+                .iter()
+                .map(|&number| StateUpdateLog {
+                    origin: EthOrigin {
+                        block: BlockOrigin {
+                            hash: EthereumBlockHash(H256::from_low_u64_le(number)),
+                            number: EthereumBlockNumber(number),
+                        },
+                        transaction: TransactionOrigin {
+                            hash: EthereumTransactionHash(H256::from_low_u64_le(number)),
+                            index: EthereumTransactionIndex(0),
+                        },
+                        log_index: EthereumLogIndex(0),
+                    },
+                    global_root: GlobalRoot(StarkHash::from_be_slice(&[number as u8]).unwrap()),
+                    block_number: StarknetBlockNumber(number),
+                })
+                .collect()
+        }
+
+        #[test]
+        fn contiguous() {
+            verify_state_update_sequence(&logs(&[10, 11, 12, 13])).unwrap();
+        }
+
+        #[test]
+        fn empty_and_single() {
+            verify_state_update_sequence(&[]).unwrap();
+            verify_state_update_sequence(&logs(&[5])).unwrap();
+        }
+
+        #[test]
+        fn gap() {
+            verify_state_update_sequence(&logs(&[10, 11, 13])).unwrap_err();
+        }
+
+        #[test]
+        fn repeated() {
+            verify_state_update_sequence(&logs(&[10, 11, 11])).unwrap_err();
+        }
+
+        #[test]
+        fn descending() {
+            verify_state_update_sequence(&logs(&[12, 11])).unwrap_err();
+        }
+    }
+
+    mod sync_checkpoint {
+        use super::super::{StateUpdateLog, SyncCheckpoint};
+        use crate::{
+            core::{
+                EthereumBlockHash, EthereumBlockNumber, EthereumLogIndex, EthereumTransactionHash,
+                EthereumTransactionIndex, GlobalRoot, StarknetBlockNumber,
+            },
+            ethereum::{BlockOrigin, EthOrigin, TransactionOrigin},
+        };
+        use pedersen::StarkHash;
+        use web3::types::H256;
+
+        fn log() -> StateUpdateLog {
+            StateUpdateLog {
+                origin: EthOrigin {
+                    block: BlockOrigin {
+                        hash: EthereumBlockHash(H256::from_low_u64_le(1000)),
+                        number: EthereumBlockNumber(1000),
+                    },
+                    transaction: TransactionOrigin {
+                        hash: EthereumTransactionHash(H256::from_low_u64_le(2000)),
+                        index: EthereumTransactionIndex(3),
+                    },
+                    log_index: EthereumLogIndex(4),
+                },
+                global_root: GlobalRoot(StarkHash::from_hex_str("0x1234abcd").unwrap()),
+                block_number: StarknetBlockNumber(42),
+            }
+        }
+
+        #[test]
+        fn from_log() {
+            let checkpoint = SyncCheckpoint::from(&log());
+            assert_eq!(
+                checkpoint,
+                SyncCheckpoint {
+                    starknet_block: StarknetBlockNumber(42),
+                    global_root: GlobalRoot(StarkHash::from_hex_str("0x1234abcd").unwrap()),
+                    eth_block: 1000,
+                }
+            );
+        }
+
+        #[test]
+        fn json_round_trip() {
+            let checkpoint = SyncCheckpoint::from(&log());
+            let json = serde_json::to_string(&checkpoint).unwrap();
+            let restored = serde_json::from_str::<SyncCheckpoint>(&json).unwrap();
+            assert_eq!(restored, checkpoint);
+        }
+    }
+
+    mod unknown_block_retry {
+        use super::super::{get_logs_retrying_unknown_block, GetLogsError, UNKNOWN_BLOCK_RETRIES};
+        use assert_matches::assert_matches;
+        use std::{
+            collections::VecDeque,
+            sync::{
+                atomic::{AtomicUsize, Ordering},
+                Arc, Mutex,
+            },
+        };
+        use web3::types::FilterBuilder;
+
+        type Reply = Result<serde_json::Value, serde_json::Value>;
+
+        /// The reply of Alchemy endpoints for blocks they don't know about.
+        fn unknown_block() -> Reply {
+            Err(serde_json::json!({
+                "code": -32000,
+                "message": "One of the blocks specified in filter (fromBlock, toBlock or blockHash) cannot be found."
+            }))
+        }
+
+        fn empty_logs() -> Reply {
+            Ok(serde_json::json!([]))
+        }
+
+        /// Runs [get_logs_retrying_unknown_block] against a local JSON-RPC server which answers
+        /// each request with the next of `replies`, and returns the result along with the number
+        /// of requests made.
+        async fn run(
+            replies: VecDeque<Reply>,
+        ) -> (Result<Vec<web3::types::Log>, GetLogsError>, usize) {
+            let replies = Arc::new(Mutex::new(replies));
+            let requests = Arc::new(AtomicUsize::new(0));
+            let transport = {
+                let requests = requests.clone();
+                crate::test_utils::mock_json_rpc(move |_| {
+                    requests.fetch_add(1, Ordering::Relaxed);
+                    replies.lock().unwrap().pop_front().unwrap()
+                })
+            };
+
+            let budget = crate::retry::RetryBudget::default();
+            let result = get_logs_retrying_unknown_block(
+                &transport,
+                FilterBuilder::default().build(),
+                true,
+                Some(&budget),
+            )
+            .await;
+            (result, requests.load(Ordering::Relaxed))
+        }
+
+        #[tokio::test]
+        async fn is_retried() {
+            let (result, requests) = run(VecDeque::from([unknown_block(), empty_logs()])).await;
+            assert_matches!(result, Ok(logs) if logs.is_empty());
+            assert_eq!(requests, 2);
+        }
+
+        #[tokio::test]
+        async fn is_returned_once_retries_are_exhausted() {
+            let replies = std::iter::repeat_with(unknown_block)
+                .take(UNKNOWN_BLOCK_RETRIES + 1)
+                .collect();
+
+            let (result, requests) = run(replies).await;
+            assert_matches!(result, Err(GetLogsError::UnknownBlock));
+            assert_eq!(requests, UNKNOWN_BLOCK_RETRIES + 1);
+        }
+
+        #[tokio::test]
+        async fn other_errors_are_not_retried() {
+            let limit = Err(serde_json::json!({
+                "code": -32005,
+                "message": "query returned more than 10000 results"
+            }));
+
+            let (result, requests) = run(VecDeque::from([limit, empty_logs()])).await;
+            assert_matches!(result, Err(GetLogsError::QueryLimit));
+            assert_eq!(requests, 1);
+        }
+    }
+
+    mod recent_logs {
+        use super::super::recent_logs;
+        use pretty_assertions::assert_eq;
+        use web3::types::{FilterBuilder, Log};
+
+        /// Serves `eth_blockNumber` with `head` and `eth_getLogs` with the logs of `blocks` in the
+        /// requested range, one log per block in ascending order.
+        async fn serve(head: u64, blocks: Vec<u64>) -> web3::Web3<web3::transports::Http> {
+            serve_limited(head, blocks, usize::MAX).await
+        }
+
+        /// Like [serve], but `eth_getLogs` fails with a query limit error if the requested range
+        /// holds more than `max_logs` logs.
+        async fn serve_limited(
+            head: u64,
+            blocks: Vec<u64>,
+            max_logs: usize,
+        ) -> web3::Web3<web3::transports::Http> {
+            crate::test_utils::mock_json_rpc(move |request| {
+                match request["method"].as_str().unwrap() {
+                    "eth_blockNumber" => Ok(serde_json::json!(format!("{:#x}", head))),
+                    "eth_getLogs" => {
+                        let filter = &request["params"][0];
+                        let bound = |key: &str| {
+                            let hex = filter[key].as_str().unwrap().trim_start_matches("0x");
+                            u64::from_str_radix(hex, 16).unwrap()
+                        };
+                        let (from, to) = (bound("fromBlock"), bound("toBlock"));
+                        let logs = blocks
+                            .iter()
+                            .filter(|&&block| from <= block && block <= to)
+                            .map(|&block| log(block))
+                            .collect::<Vec<_>>();
+
+                        if logs.len() > max_logs {
+                            return Err(serde_json::json!({
+                                "code": -32005,
+                                "message": "query returned more than 10000 results"
+                            }));
+                        }
+                        Ok(serde_json::json!(logs))
+                    }
+                    other => panic!("Unexpected method {}", other),
+                }
+            })
+        }
+
+        fn log(block: u64) -> serde_json::Value {
+            serde_json::json!({
+                "address": "0x0000000000000000000000000000000000000000",
+                "topics": [],
+                "data": "0x",
+                "blockNumber": format!("{:#x}", block),
+            })
+        }
+
+        fn blocks(logs: Vec<Log>) -> Vec<u64> {
+            logs.into_iter()
+                .map(|log| log.block_number.unwrap().as_u64())
+                .collect()
+        }
+
+        #[tokio::test]
+        async fn descending_and_limited() {
+            let transport = serve(5_000, vec![10, 1_200, 3_500, 3_600, 4_990]).await;
+
+            let logs = recent_logs(&transport, FilterBuilder::default(), 3, 10_000)
+                .await
+                .unwrap();
+            assert_eq!(blocks(logs), vec![4_990, 3_600, 3_500]);
+        }
+
+        #[tokio::test]
+        async fn fewer_than_limit() {
+            let transport = serve(5_000, vec![10, 4_990]).await;
+
+            let logs = recent_logs(&transport, FilterBuilder::default(), 5, 10_000)
+                .await
+                .unwrap();
+            assert_eq!(blocks(logs), vec![4_990, 10]);
+        }
+
+        #[tokio::test]
+        async fn stops_at_max_lookback() {
+            let transport = serve(5_000, vec![10, 1_200, 4_990]).await;
+
+            let logs = recent_logs(&transport, FilterBuilder::default(), 5, 2_000)
+                .await
+                .unwrap();
+            assert_eq!(blocks(logs), vec![4_990]);
+        }
+
+        #[tokio::test]
+        async fn stride_shrinks_on_query_limit() {
+            let transport = serve_limited(5_000, (4_900..4_920).collect(), 4).await;
+
+            let logs = recent_logs(&transport, FilterBuilder::default(), 3, 10_000)
+                .await
+                .unwrap();
+            assert_eq!(blocks(logs), vec![4_919, 4_918, 4_917]);
+        }
+
+        #[tokio::test]
+        async fn query_limit_at_minimum_stride_is_an_error() {
+            let transport = serve_limited(5_000, vec![4_990], 0).await;
+
+            recent_logs(&transport, FilterBuilder::default(), 1, 10_000)
+                .await
+                .unwrap_err();
+        }
+    }
+
+    mod eth_block_range {
+        use super::super::EthBlockRange;
+        use crate::core::EthereumBlockNumber;
+        use pretty_assertions::assert_eq;
+        use web3::types::{BlockNumber, FilterBuilder, H160, H256};
+
+        #[test]
+        fn sets_bounds() {
+            let range = EthBlockRange::new(EthereumBlockNumber(100), EthereumBlockNumber(200));
+            let filter = range.filter(FilterBuilder::default());
+
+            let expected = FilterBuilder::default()
+                .from_block(BlockNumber::Number(100.into()))
+                .to_block(BlockNumber::Number(200.into()))
+                .build();
+            assert_eq!(filter, expected);
+
+            let json = serde_json::to_value(&filter).unwrap();
+            assert_eq!(json["fromBlock"], "0x64");
+            assert_eq!(json["toBlock"], "0xc8");
+        }
+
+        #[test]
+        fn keeps_address_and_topics() {
+            let base = FilterBuilder::default()
+                .address(vec![H160::from_low_u64_be(1)])
+                .topics(Some(vec![H256::from_low_u64_be(2)]), None, None, None);
+
+            let range = EthBlockRange::new(EthereumBlockNumber(5), EthereumBlockNumber(5));
+            let filter = range.filter(base.clone());
+
+            let expected = base
+                .from_block(BlockNumber::Number(5.into()))
+                .to_block(BlockNumber::Number(5.into()))
+                .build();
+            assert_eq!(filter, expected);
+        }
+
+        #[test]
+        fn replaces_existing_bounds() {
+            let base = FilterBuilder::default()
+                .from_block(BlockNumber::Earliest)
+                .to_block(BlockNumber::Latest);
+
+            let range = EthBlockRange::new(EthereumBlockNumber(1), EthereumBlockNumber(2));
+
+            assert_eq!(range.filter(base), range.filter(FilterBuilder::default()));
+        }
+    }
 
     mod get_logs {
         use crate::ethereum::{log::GetLogsError, test_transport};
 
-        use super::super::get_logs;
+        use super::super::{get_logs, DECODER_ERROR_RETRIES};
         use assert_matches::assert_matches;
         use web3::types::{BlockNumber, FilterBuilder, H256};
 
@@ -149,7 +812,7 @@ mod tests {
 
             let transport = test_transport(crate::ethereum::Chain::Goerli);
 
-            let result = get_logs(&transport, filter).await;
+            let result = get_logs(&transport, filter, true, None).await;
             assert_matches!(result, Ok(logs) if logs.len() == 85);
         }
 
@@ -164,7 +827,7 @@ mod tests {
 
             let transport = test_transport(crate::ethereum::Chain::Goerli);
 
-            let result = get_logs(&transport, filter).await;
+            let result = get_logs(&transport, filter, true, None).await;
             assert_matches!(result, Err(GetLogsError::QueryLimit));
         }
 
@@ -184,7 +847,7 @@ mod tests {
                 .to_block(BlockNumber::Number((latest + 20).into()))
                 .build();
 
-            let result = get_logs(&transport, filter).await;
+            let result = get_logs(&transport, filter, true, None).await;
             match result {
                 // This occurs for an Infura endpoint
                 Ok(logs) => assert!(logs.is_empty()),
@@ -192,5 +855,75 @@ mod tests {
                 Err(e) => assert_matches!(e, GetLogsError::UnknownBlock),
             }
         }
+
+        mod spurious_decoder_error {
+            use super::*;
+            use std::collections::VecDeque;
+
+            /// Runs [get_logs] against a local JSON-RPC server which answers each request with
+            /// the next of `results`. A `null` result is what causes the spurious decoder error.
+            async fn run_get_logs(
+                results: VecDeque<serde_json::Value>,
+                retry_decoder_errors: bool,
+            ) -> Result<Vec<web3::types::Log>, GetLogsError> {
+                use std::sync::{Arc, Mutex};
+
+                let results = Arc::new(Mutex::new(results));
+                let transport = crate::test_utils::mock_json_rpc(move |_| {
+                    Ok(results.lock().unwrap().pop_front().unwrap())
+                });
+
+                get_logs(
+                    &transport,
+                    FilterBuilder::default().build(),
+                    retry_decoder_errors,
+                    Some(&crate::retry::RetryBudget::default()),
+                )
+                .await
+            }
+
+            #[tokio::test]
+            async fn is_retried() {
+                let results = VecDeque::from([serde_json::Value::Null, serde_json::json!([])]);
+
+                let result = run_get_logs(results, true).await;
+                assert_matches!(result, Ok(logs) if logs.is_empty());
+            }
+
+            #[tokio::test]
+            async fn is_error_when_disabled() {
+                let results = VecDeque::from([serde_json::Value::Null, serde_json::json!([])]);
+
+                let result = run_get_logs(results, false).await;
+                assert_matches!(result, Err(GetLogsError::Other(_)));
+            }
+
+            #[tokio::test]
+            async fn is_returned_once_retries_are_exhausted() {
+                // The reply after the last retry would succeed, if it were ever requested.
+                let mut results = std::iter::repeat(serde_json::Value::Null)
+                    .take(DECODER_ERROR_RETRIES + 1)
+                    .collect::<VecDeque<_>>();
+                results.push_back(serde_json::json!([]));
+
+                let result = run_get_logs(results, true).await;
+                assert_matches!(result, Err(GetLogsError::Other(_)));
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn latest_state_update() {
+        use crate::ethereum::{test_transport, Chain};
+
+        let transport = test_transport(Chain::Goerli);
+        let latest_block = transport.eth().block_number().await.unwrap().as_u64();
+
+        let update = super::latest_state_update(&transport, Chain::Goerli)
+            .await
+            .unwrap();
+
+        assert!(update.block_number.0 > 0);
+        assert!(update.origin.block.number.0 <= latest_block);
     }
 }
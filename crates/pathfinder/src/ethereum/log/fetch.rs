@@ -1,4 +1,4 @@
-use web3::types::{H160, H256};
+use web3::types::{FilterBuilder, H160, H256};
 
 use crate::ethereum::{
     contract::{
@@ -12,11 +12,31 @@ use crate::ethereum::{
 };
 
 mod backward;
+mod cursor;
 mod forward;
 
 pub use backward::*;
+pub use cursor::*;
 pub use forward::*;
 
+/// Creates a [FilterBuilder] which only matches the state update logs of the StarkNet core
+/// contract, [StateUpdateLog] and [StateTransitionFactLog], by their `topic0` event signatures.
+///
+/// The block range is left to the caller.
+pub fn state_update_filter(chain: Chain) -> FilterBuilder {
+    FilterBuilder::default()
+        .address(vec![StateUpdateLog::contract_address(chain)])
+        .topics(
+            Some(vec![
+                StateUpdateLog::signature(),
+                StateTransitionFactLog::signature(),
+            ]),
+            None,
+            None,
+            None,
+        )
+}
+
 /// May contain one of two types of [MetaLog].
 ///
 /// Used by [BackwardLogFetcher].
@@ -133,3 +153,52 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod state_update_filter {
+        use super::*;
+        use crate::ethereum::contract::{
+            STATE_TRANSITION_FACT_EVENT_SIGNATURE, STATE_UPDATE_EVENT_SIGNATURE,
+        };
+        use sha3::{Digest, Keccak256};
+
+        fn keccak(signature: &str) -> H256 {
+            H256::from_slice(&Keccak256::digest(signature.as_bytes()))
+        }
+
+        #[test]
+        fn topics_are_event_signatures() {
+            let filter = state_update_filter(Chain::Goerli).build();
+            let filter = serde_json::to_value(&filter).unwrap();
+
+            // Only topic0 is constrained.
+            assert_eq!(
+                filter["topics"][0],
+                serde_json::json!([
+                    keccak(STATE_UPDATE_EVENT_SIGNATURE),
+                    keccak(STATE_TRANSITION_FACT_EVENT_SIGNATURE)
+                ])
+            );
+            assert!(filter["topics"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .skip(1)
+                .all(serde_json::Value::is_null));
+        }
+
+        #[test]
+        fn address_is_core_contract() {
+            let filter = state_update_filter(Chain::Goerli).build();
+            let filter = serde_json::to_value(&filter).unwrap();
+
+            assert_eq!(
+                filter["address"],
+                serde_json::json!(crate::ethereum::contract::addresses(Chain::Goerli).core)
+            );
+        }
+    }
+}
@@ -3,12 +3,15 @@ use web3::{
     Transport, Web3,
 };
 
-use crate::ethereum::{
-    log::{
-        fetch::{EitherMetaLog, MetaLog},
-        get_logs, GetLogsError,
+use crate::{
+    ethereum::{
+        log::{
+            fetch::{EitherMetaLog, MetaLog},
+            get_logs, GetLogsError,
+        },
+        Chain,
     },
-    Chain,
+    retry::RetryBudget,
 };
 
 #[derive(Debug)]
@@ -49,6 +52,8 @@ where
     tail: EitherMetaLog<L, R>,
     stride: u64,
     base_filter: FilterBuilder,
+    retry_decoder_errors: bool,
+    retry_budget: Option<RetryBudget>,
 }
 
 impl<L, R> BackwardLogFetcher<L, R>
@@ -68,9 +73,23 @@ where
             tail,
             stride: 10_000,
             base_filter,
+            retry_decoder_errors: true,
+            retry_budget: None,
         }
     }
 
+    /// Sets whether the spurious log decoder error of some Ethereum endpoints
+    /// should be retried. This is enabled by default.
+    pub fn set_retry_spurious_decoder_errors(&mut self, retry: bool) {
+        self.retry_decoder_errors = retry;
+    }
+
+    /// Draws the retries of the log queries from `budget`. Retries are only limited per query
+    /// by default.
+    pub fn set_retry_budget(&mut self, budget: RetryBudget) {
+        self.retry_budget = Some(budget);
+    }
+
     /// Fetches the next set of logs from L1.
     ///
     /// ## Important: logs are returned in reverse chronological order.
@@ -104,7 +123,14 @@ where
                 .from_block(BlockNumber::Number(from_block.into()))
                 .build();
 
-            let logs = match get_logs(transport, filter).await {
+            let logs = match get_logs(
+                transport,
+                filter,
+                self.retry_decoder_errors,
+                self.retry_budget.as_ref(),
+            )
+            .await
+            {
                 Ok(logs) => logs,
                 Err(GetLogsError::QueryLimit) => {
                     stride_cap = Some(self.stride);
@@ -117,7 +143,11 @@ where
             };
 
             // We need to iterate in reverse since that is the direction we are searching in.
-            let mut logs = logs.into_iter().rev();
+            // Logs removed by a reorg are skipped, see the forward fetcher.
+            let mut logs = logs
+                .into_iter()
+                .filter(|log| log.removed != Some(true))
+                .rev();
 
             // Check for reorgs.
             //
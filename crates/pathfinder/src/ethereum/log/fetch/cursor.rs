@@ -0,0 +1,156 @@
+use serde::{Deserialize, Serialize};
+use web3::{Transport, Web3};
+
+use crate::{
+    core::{EthereumBlockNumber, StarknetBlockNumber},
+    ethereum::{
+        log::{
+            fetch::{FetchError, LogFetcher},
+            StateUpdateLog,
+        },
+        Chain,
+    },
+};
+
+/// Identifies the last [StateUpdateLog] which was fully processed.
+///
+/// Persisting this allows log fetching to resume after it on restart, instead of
+/// reprocessing logs from genesis.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogFetchCursor {
+    /// The StarkNet block of the last processed state update.
+    pub last_block: StarknetBlockNumber,
+    /// The L1 block containing the last processed state update.
+    pub last_eth_block: u64,
+}
+
+impl LogFetchCursor {
+    /// Returns a cursor positioned at `update`.
+    pub fn at(update: &StateUpdateLog) -> Self {
+        Self {
+            last_block: update.block_number,
+            last_eth_block: update.origin.block.number.0,
+        }
+    }
+
+    /// Returns `true` if `update` has not been processed according to this cursor.
+    pub fn is_before(&self, update: &StateUpdateLog) -> bool {
+        update.block_number > self.last_block
+    }
+
+    /// Removes the `logs` which were already processed according to this cursor.
+    pub fn retain_after(&self, mut logs: Vec<StateUpdateLog>) -> Vec<StateUpdateLog> {
+        logs.retain(|log| self.is_before(log));
+        logs
+    }
+}
+
+impl LogFetcher<StateUpdateLog> {
+    /// Creates a [LogFetcher] which resumes from `cursor`. Use
+    /// [fetch_after_cursor](LogFetcher::fetch_after_cursor) to exclude the logs up to and
+    /// including the cursor, which share its L1 block.
+    pub fn from_cursor(cursor: &LogFetchCursor, chain: Chain) -> Self {
+        Self::from_block(EthereumBlockNumber(cursor.last_eth_block), chain)
+    }
+
+    /// Fetches the next set of logs from L1 like [fetch](LogFetcher::fetch), but only returns
+    /// the logs after `cursor`.
+    ///
+    /// Note that this may return an empty set even if the end of L1 has not been reached,
+    /// if all fetched logs were already processed.
+    pub async fn fetch_after_cursor<Tr: Transport>(
+        &mut self,
+        transport: &Web3<Tr>,
+        cursor: &LogFetchCursor,
+    ) -> Result<Vec<StateUpdateLog>, FetchError> {
+        let logs = self.fetch(transport).await?;
+        Ok(cursor.retain_after(logs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        core::{
+            EthereumBlockHash, EthereumLogIndex, EthereumTransactionHash, EthereumTransactionIndex,
+            GlobalRoot,
+        },
+        ethereum::{BlockOrigin, EthOrigin, TransactionOrigin},
+    };
+    use pedersen::StarkHash;
+    use web3::types::H256;
+
+    /// A synthetic stream of state updates, with two updates per L1 block.
+    fn stream() -> Vec<StateUpdateLog> {
+        (0..10u64)
+            .map(|i| StateUpdateLog {
+                origin: EthOrigin {
+                    block: BlockOrigin {
+                        hash: EthereumBlockHash(H256::from_low_u64_be(i / 2)),
+                        number: EthereumBlockNumber(1000 + i / 2),
+                    },
+                    transaction: TransactionOrigin {
+                        hash: EthereumTransactionHash(H256::from_low_u64_be(i)),
+                        index: EthereumTransactionIndex(i % 2),
+                    },
+                    log_index: EthereumLogIndex(i % 2),
+                },
+                global_root: GlobalRoot(StarkHash::from_be_slice(&i.to_be_bytes()).unwrap()),
+                block_number: StarknetBlockNumber(i),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn excludes_processed_logs() {
+        let stream = stream();
+        let cursor = LogFetchCursor::at(&stream[4]);
+
+        let remaining = cursor.retain_after(stream.clone());
+        assert_eq!(remaining, stream[5..]);
+    }
+
+    #[test]
+    fn excludes_earlier_logs_in_same_l1_block() {
+        let stream = stream();
+        // The first update of L1 block 1002, followed by another update in the same block.
+        let cursor = LogFetchCursor::at(&stream[4]);
+        assert_eq!(cursor.last_eth_block, stream[5].origin.block.number.0);
+
+        // Resuming from the cursor's L1 block also returns the earlier logs of that block.
+        let resumed = stream
+            .iter()
+            .filter(|log| log.origin.block.number.0 >= cursor.last_eth_block)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        assert_eq!(cursor.retain_after(resumed), stream[5..]);
+    }
+
+    #[test]
+    fn at_end_of_stream() {
+        let stream = stream();
+        let cursor = LogFetchCursor::at(stream.last().unwrap());
+
+        assert!(cursor.retain_after(stream).is_empty());
+    }
+
+    #[test]
+    fn serde_round_trip() {
+        let cursor = LogFetchCursor {
+            last_block: StarknetBlockNumber(13699),
+            last_eth_block: 6_000_000,
+        };
+
+        let json = serde_json::to_value(&cursor).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"last_block": 13699, "last_eth_block": 6_000_000})
+        );
+        assert_eq!(
+            serde_json::from_value::<LogFetchCursor>(json).unwrap(),
+            cursor
+        );
+    }
+}
@@ -4,9 +4,13 @@ use web3::{
     Transport, Web3,
 };
 
-use crate::ethereum::{
-    log::{fetch::MetaLog, get_logs, GetLogsError},
-    Chain,
+use crate::{
+    core::EthereumBlockNumber,
+    ethereum::{
+        log::{fetch::MetaLog, get_logs, GetLogsError},
+        Chain,
+    },
+    retry::RetryBudget,
 };
 
 /// Fetches consecutive logs of type T from L1, accounting for chain
@@ -16,8 +20,12 @@ where
     T: MetaLog + PartialEq + std::fmt::Debug + Clone,
 {
     head: Option<T>,
+    /// The L1 block to start from if there is no `head`.
+    from_block: u64,
     stride: u64,
     base_filter: FilterBuilder,
+    retry_decoder_errors: bool,
+    retry_budget: Option<RetryBudget>,
 }
 
 #[derive(Debug)]
@@ -49,11 +57,35 @@ where
 
         Self {
             head,
+            from_block: 0,
             stride: 10_000,
             base_filter,
+            retry_decoder_errors: true,
+            retry_budget: None,
         }
     }
 
+    /// Creates a [LogFetcher] without a head, which fetches logs starting from (and including)
+    /// the L1 block `from_block`.
+    pub fn from_block(from_block: EthereumBlockNumber, chain: Chain) -> Self {
+        Self {
+            from_block: from_block.0,
+            ..Self::new(None, chain)
+        }
+    }
+
+    /// Sets whether the spurious log decoder error of some Ethereum endpoints
+    /// should be retried. This is enabled by default.
+    pub fn set_retry_spurious_decoder_errors(&mut self, retry: bool) {
+        self.retry_decoder_errors = retry;
+    }
+
+    /// Draws the retries of the log queries from `budget`. Retries are only limited per query
+    /// by default.
+    pub fn set_retry_budget(&mut self, budget: RetryBudget) {
+        self.retry_budget = Some(budget);
+    }
+
     pub fn set_head(&mut self, head: Option<T>) {
         self.head = head;
     }
@@ -86,7 +118,7 @@ where
             .head
             .as_ref()
             .map(|update| update.origin().block.number.0)
-            .unwrap_or_default();
+            .unwrap_or(self.from_block);
         let base_filter = self
             .base_filter
             .clone()
@@ -109,7 +141,14 @@ where
                 .to_block(BlockNumber::Number(to_block.into()))
                 .build();
 
-            let logs = match get_logs(transport, filter).await {
+            let logs = match get_logs(
+                transport,
+                filter,
+                self.retry_decoder_errors,
+                self.retry_budget.as_ref(),
+            )
+            .await
+            {
                 Ok(logs) => logs,
                 Err(GetLogsError::QueryLimit) => {
                     stride_cap = Some(self.stride);
@@ -139,7 +178,9 @@ where
                 Err(GetLogsError::Other(other)) => return Err(FetchError::Other(other)),
             };
 
-            let mut logs = logs.into_iter();
+            // Logs removed by a reorg are skipped. If the head was one of them, it won't be
+            // found below and the reorg is reported.
+            let mut logs = logs.into_iter().filter(|log| log.removed != Some(true));
 
             // Check for reorgs. Only required if there was a head to validate.
             //
@@ -263,4 +304,28 @@ mod tests {
             block_number += 1;
         }
     }
+
+    #[tokio::test]
+    async fn removed_logs_are_skipped() {
+        let added: serde_json::Value =
+            serde_json::from_str(include_str!("../../../../fixtures/logs/state_update.json"))
+                .unwrap();
+        let mut removed = added.clone();
+        removed["logIndex"] = serde_json::json!("0x3");
+        removed["removed"] = serde_json::json!(true);
+
+        let transport = crate::test_utils::mock_json_rpc(move |request| {
+            assert_eq!(request["method"], "eth_getLogs");
+            Ok(serde_json::json!([removed, added]))
+        });
+
+        let mut fetcher = LogFetcher::<StateUpdateLog>::from_block(
+            EthereumBlockNumber(5_000_000),
+            crate::ethereum::Chain::Goerli,
+        );
+        let logs = fetcher.fetch(&transport).await.unwrap();
+
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].origin.log_index, EthereumLogIndex(4));
+    }
 }
@@ -14,8 +14,8 @@ use crate::{
             STATE_TRANSITION_FACT_EVENT, STATE_UPDATE_EVENT,
         },
         log::{
-            MemoryPageFactContinuousLog, MemoryPagesHashesLog, StateTransitionFactLog,
-            StateUpdateLog,
+            LogEvent, MemoryPageFactContinuousLog, MemoryPagesHashesLog, MetaLog,
+            StateTransitionFactLog, StateUpdateLog,
         },
         EthOrigin,
     },
@@ -91,6 +91,21 @@ impl TryFrom<web3::types::Log> for MemoryPagesHashesLog {
             .collect::<Result<Vec<_>, _>>()
             .context("page hash could not be parsed")?;
 
+        // Both would corrupt the assembly of the state update from the memory pages.
+        anyhow::ensure!(
+            !mempage_hashes.is_empty(),
+            "Memory page hashes of fact {:?} are empty",
+            hash
+        );
+        let mut unique = std::collections::HashSet::with_capacity(mempage_hashes.len());
+        if let Some(duplicate) = mempage_hashes.iter().find(|page| !unique.insert(*page)) {
+            anyhow::bail!(
+                "Memory page hash {:?} is listed more than once in fact {:?}",
+                duplicate,
+                hash
+            );
+        }
+
         Ok(Self {
             origin,
             hash,
@@ -117,6 +132,23 @@ impl TryFrom<web3::types::Log> for MemoryPageFactContinuousLog {
     }
 }
 
+impl<T> TryFrom<web3::types::Log> for LogEvent<T>
+where
+    T: MetaLog,
+{
+    type Error = anyhow::Error;
+
+    fn try_from(value: web3::types::Log) -> Result<Self, Self::Error> {
+        // Only the origin of a removed log is of interest, its data is not parsed.
+        if value.removed == Some(true) {
+            let origin = EthOrigin::try_from(&value)?;
+            return Ok(Self::Removed(origin));
+        }
+
+        Ok(Self::Added(T::try_from(value)?))
+    }
+}
+
 /// Utility which extracts the [EthOrigin] and log index, and then converts to a [RawLog].
 fn parse_web3_log(log: web3::types::Log) -> anyhow::Result<(EthOrigin, RawLog)> {
     let origin = EthOrigin::try_from(&log)?;
@@ -245,6 +277,35 @@ mod tests {
             log.log_index = None;
             StateUpdateLog::try_from(log).unwrap_err();
         }
+
+        #[test]
+        fn removed() {
+            let (mut log, _, _) = test_data();
+            log.removed = Some(true);
+            let origin = EthOrigin::try_from(&log).unwrap();
+
+            let result = LogEvent::<StateUpdateLog>::try_from(log).unwrap();
+            assert_eq!(result, LogEvent::Removed(origin));
+        }
+
+        #[test]
+        fn not_removed() {
+            let (log, root, sequence) = test_data();
+
+            for removed in [None, Some(false)] {
+                let mut log = log.clone();
+                log.removed = removed;
+
+                let result = LogEvent::<StateUpdateLog>::try_from(log).unwrap();
+                match result {
+                    LogEvent::Added(log) => {
+                        assert_eq!(log.global_root, root);
+                        assert_eq!(log.block_number, sequence);
+                    }
+                    LogEvent::Removed(_) => panic!("log was not removed"),
+                }
+            }
+        }
     }
 
     mod state_transition_fact {
@@ -410,6 +471,49 @@ mod tests {
             log.log_index = None;
             MemoryPagesHashesLog::try_from(log).unwrap_err();
         }
+
+        /// Replaces the `pagesHashes` array of the [test_data] log with `pages_hashes`.
+        fn with_pages_hashes(pages_hashes: &[H256]) -> web3::types::Log {
+            let (mut log, _, _) = test_data();
+            // factHash, the offset of the array and its length precede the elements
+            log.data.0.truncate(64);
+            log.data
+                .0
+                .extend_from_slice(&H256::from_low_u64_be(pages_hashes.len() as u64).0);
+            for page_hash in pages_hashes {
+                log.data.0.extend_from_slice(page_hash.as_bytes());
+            }
+            log
+        }
+
+        #[test]
+        fn empty_pages_hashes() {
+            let error = MemoryPagesHashesLog::try_from(with_pages_hashes(&[])).unwrap_err();
+            assert!(error.to_string().contains("are empty"), "{}", error);
+        }
+
+        #[test]
+        fn duplicate_pages_hashes() {
+            let (_, _, pages_hashes) = test_data();
+            let duplicated = [pages_hashes[0], pages_hashes[1], pages_hashes[0]];
+
+            let error = MemoryPagesHashesLog::try_from(with_pages_hashes(&duplicated)).unwrap_err();
+            assert!(
+                error
+                    .to_string()
+                    .contains(&format!("{:?} is listed more than once", pages_hashes[0])),
+                "{}",
+                error
+            );
+        }
+
+        #[test]
+        fn rebuilt_data_is_valid() {
+            let (_, _, pages_hashes) = test_data();
+
+            let result = MemoryPagesHashesLog::try_from(with_pages_hashes(&pages_hashes)).unwrap();
+            assert_eq!(result.mempage_hashes, pages_hashes);
+        }
     }
 
     mod memory_page_fact_continuous {
@@ -494,4 +598,156 @@ mod tests {
             MemoryPageFactContinuousLog::try_from(log).unwrap_err();
         }
     }
+
+    /// Parses the `eth_getLogs` style JSON fixtures in `fixtures/logs`.
+    ///
+    /// The address, topics and data of each fixture are as emitted on Goerli by the transaction
+    /// referenced in the corresponding module above. The block hash, block number and indices are
+    /// stand-ins.
+    mod fixtures {
+        use std::str::FromStr;
+
+        use super::*;
+        use crate::core::{
+            EthereumBlockHash, EthereumBlockNumber, EthereumLogIndex, EthereumTransactionHash,
+            EthereumTransactionIndex,
+        };
+        use crate::ethereum::{BlockOrigin, TransactionOrigin};
+        use pretty_assertions::assert_eq;
+
+        fn origin(
+            block_hash: &str,
+            block_number: u64,
+            tx_hash: &str,
+            tx_index: u64,
+            log_index: u64,
+        ) -> EthOrigin {
+            EthOrigin {
+                block: BlockOrigin {
+                    hash: EthereumBlockHash(H256::from_str(block_hash).unwrap()),
+                    number: EthereumBlockNumber(block_number),
+                },
+                transaction: TransactionOrigin {
+                    hash: EthereumTransactionHash(H256::from_str(tx_hash).unwrap()),
+                    index: EthereumTransactionIndex(tx_index),
+                },
+                log_index: EthereumLogIndex(log_index),
+            }
+        }
+
+        fn load(fixture: &str) -> web3::types::Log {
+            serde_json::from_str(fixture).unwrap()
+        }
+
+        #[test]
+        fn state_update() {
+            let log = load(include_str!("../../../fixtures/logs/state_update.json"));
+
+            let result = StateUpdateLog::try_from(log).unwrap();
+            assert_eq!(
+                result,
+                StateUpdateLog {
+                    origin: origin(
+                        "0x1111111111111111111111111111111111111111111111111111111111111111",
+                        5_000_000,
+                        "0xb6ba98e34c60bb39785df907de3c41c0a9c95302e50f213606772817514714ce",
+                        7,
+                        4
+                    ),
+                    global_root: GlobalRoot(
+                        StarkHash::from_hex_str(
+                            "06bd197ccc199cc3be696635a482ff818a1f166ef91c5fd844aacafb15a12bcd"
+                        )
+                        .unwrap()
+                    ),
+                    block_number: StarknetBlockNumber(13699),
+                }
+            );
+        }
+
+        #[test]
+        fn state_transition_fact() {
+            let log = load(include_str!(
+                "../../../fixtures/logs/state_transition_fact.json"
+            ));
+
+            let result = StateTransitionFactLog::try_from(log).unwrap();
+            assert_eq!(
+                result,
+                StateTransitionFactLog {
+                    origin: origin(
+                        "0x1111111111111111111111111111111111111111111111111111111111111111",
+                        5_000_000,
+                        "0xb6ba98e34c60bb39785df907de3c41c0a9c95302e50f213606772817514714ce",
+                        7,
+                        3
+                    ),
+                    fact_hash: H256::from_str(
+                        "0xdc6194a6f096f55bea405c62196520299060ee13e405b1cdd442a20ab97fd226"
+                    )
+                    .unwrap(),
+                }
+            );
+        }
+
+        #[test]
+        fn memory_pages_hashes() {
+            let log = load(include_str!(
+                "../../../fixtures/logs/memory_pages_hashes.json"
+            ));
+
+            let result = MemoryPagesHashesLog::try_from(log).unwrap();
+            assert_eq!(
+                result,
+                MemoryPagesHashesLog {
+                    origin: origin(
+                        "0x2222222222222222222222222222222222222222222222222222222222222222",
+                        4_999_990,
+                        "0x45852ddb65f209137a6966bc9efa7484e58a351619787dfa20e7fa8cc996d118",
+                        12,
+                        30
+                    ),
+                    hash: H256::from_str(
+                        "0xc4a36c5055545c20f1c4bcd0c0fb0f281d14f045b5d1e347810e5a0c1006c431"
+                    )
+                    .unwrap(),
+                    mempage_hashes: vec![
+                        H256::from_str(
+                            "0x98814cac4375766d0dbc4e571de92fc8e0ee9287e282b0d3130478b053f6398c"
+                        )
+                        .unwrap(),
+                        H256::from_str(
+                            "0xf8f4bdc57b81e3eb36a0587454a2b391358970664428b92cbf0bd14ca408bbdf"
+                        )
+                        .unwrap(),
+                    ],
+                }
+            );
+        }
+
+        #[test]
+        fn memory_page_fact_continuous() {
+            let log = load(include_str!(
+                "../../../fixtures/logs/memory_page_fact_continuous.json"
+            ));
+
+            let result = MemoryPageFactContinuousLog::try_from(log).unwrap();
+            assert_eq!(
+                result,
+                MemoryPageFactContinuousLog {
+                    origin: origin(
+                        "0x3333333333333333333333333333333333333333333333333333333333333333",
+                        4_999_980,
+                        "0x6690a78c3284b1c825925021211a3ffa8c31b92bc0e00c57e0e1306d6425fc36",
+                        3,
+                        1
+                    ),
+                    hash: H256::from_str(
+                        "0x422c4adc2ee6fb0f296713ec48463605627b5a8088293f8a2fa3140ac21ef916"
+                    )
+                    .unwrap(),
+                }
+            );
+        }
+    }
 }
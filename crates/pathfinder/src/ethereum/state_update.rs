@@ -2,6 +2,9 @@ mod parse;
 mod retrieve;
 pub mod state_root;
 
+pub use parse::{stream_state_diff, MemoryPageMetadata, StateDiffItem, StateDiffStream};
+
+use anyhow::Context;
 use pedersen::StarkHash;
 use retrieve::*;
 
@@ -11,11 +14,18 @@ use crate::{
     core::{ContractAddress, ContractHash, StorageAddress, StorageValue},
     ethereum::{
         log::StateUpdateLog,
-        state_update::{parse::StateUpdateParser, retrieve::retrieve_transition_fact},
+        state_update::{
+            parse::{data_pages, StateUpdateParser},
+            retrieve::retrieve_transition_fact,
+        },
         Chain,
     },
 };
 
+/// The default maximum number of memory pages a single fact may list, see
+/// [StateUpdate::retrieve].
+pub const DEFAULT_MAX_MEMORY_PAGES_PER_FACT: usize = 1000;
+
 /// Describes the deployment of a new StarkNet contract.
 #[derive(Debug, Clone, PartialEq)]
 pub struct DeployedContract {
@@ -53,6 +63,11 @@ pub enum RetrieveStateUpdateError {
     MemoryPageHashesNotFound,
     MemoryPageLogNotFound,
     MemoryPageTransactionNotFound,
+    /// The fact lists more memory pages than we are willing to fetch.
+    TooManyMemoryPages {
+        pages: usize,
+        max: usize,
+    },
     Reorg,
     Other(anyhow::Error),
 }
@@ -65,6 +80,11 @@ impl std::fmt::Display for RetrieveStateUpdateError {
             MemoryPageHashesNotFound => write!(f, "Not found: Memory page hashes"),
             MemoryPageLogNotFound => write!(f, "Not found: Memory page log"),
             MemoryPageTransactionNotFound => write!(f, "Not found: Memory page transaction"),
+            TooManyMemoryPages { pages, max } => write!(
+                f,
+                "Suspicious fact: {} memory pages exceed the maximum of {}",
+                pages, max
+            ),
             Reorg => write!(f, "Reorg event detected"),
             Other(e) => e.fmt(f),
         }
@@ -83,27 +103,41 @@ impl From<anyhow::Error> for RetrieveStateUpdateError {
 
 impl StateUpdate {
     /// Retrieves the [StateUpdate] associated with the given [StateUpdateLog] from L1.
+    ///
+    /// Facts listing more than `max_pages_per_fact` memory pages are rejected as suspicious
+    /// before any of the pages are fetched.
     pub async fn retrieve<T: Transport>(
         transport: &Web3<T>,
         state_update: StateUpdateLog,
         chain: Chain,
+        max_pages_per_fact: usize,
     ) -> Result<Self, RetrieveStateUpdateError> {
+        let block_number = state_update.block_number;
+        let global_root = state_update.global_root;
         let transition_fact = retrieve_transition_fact(transport, state_update, chain).await?;
 
         let mempage_hashes = retrieve_mempage_hashes(transport, transition_fact, chain).await?;
+        let expected_page_hashes = mempage_hashes.mempage_hashes.clone();
+
+        verify_mempage_count(&expected_page_hashes, max_pages_per_fact)?;
 
         let mempage_logs = retrieve_memory_page_logs(transport, mempage_hashes, chain).await?;
 
         let mempage_data = retrieve_mempage_transaction_data(transport, mempage_logs).await?;
 
-        // flatten memory page data (skip first page)
-        let mempage_data = mempage_data
-            .into_iter()
-            .skip(1)
-            .flatten()
-            .collect::<Vec<_>>();
+        // don't trust the transaction data unless it matches the hashes of the fact
+        verify_mempage_data(&mempage_data, &expected_page_hashes)?;
+
+        // the metadata page must describe the state update we set out to retrieve
+        let metadata = mempage_data
+            .first()
+            .context("Memory page metadata missing")?;
+        let metadata =
+            MemoryPageMetadata::parse(metadata).context("Parsing memory page metadata")?;
+        verify_metadata(&metadata, block_number, global_root)?;
 
-        // parse memory page data
+        // parse the memory page data in order, skipping the metadata page
+        let mempage_data = data_pages(&mempage_data)?.iter().flatten().copied();
         let update = StateUpdateParser::parse(mempage_data)?;
         Ok(update)
     }
@@ -125,6 +159,92 @@ mod tests {
 
     use super::*;
 
+    #[tokio::test]
+    async fn too_many_memory_pages_are_rejected_before_fetching() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+        use web3::ethabi::{encode, Token};
+
+        use crate::ethereum::contract::{MEMORY_PAGE_HASHES_EVENT, STATE_TRANSITION_FACT_EVENT};
+
+        let chain = crate::ethereum::Chain::Goerli;
+        let addresses = crate::ethereum::contract::addresses(chain);
+        let fact_hash = H256::from_low_u64_be(0xfac7);
+        let block_hash = H256::from_low_u64_be(0xb10c);
+        let tx_hash = H256::from_low_u64_be(0x7e);
+
+        let update_log = StateUpdateLog {
+            origin: EthOrigin {
+                block: BlockOrigin {
+                    hash: EthereumBlockHash(block_hash),
+                    number: EthereumBlockNumber(100),
+                },
+                transaction: TransactionOrigin {
+                    hash: EthereumTransactionHash(tx_hash),
+                    index: EthereumTransactionIndex(0),
+                },
+                log_index: EthereumLogIndex(1),
+            },
+            global_root: GlobalRoot(StarkHash::from_hex_str("0x1234").unwrap()),
+            block_number: StarknetBlockNumber(10),
+        };
+
+        // The fact is emitted right before the state update, in the same transaction.
+        let fact_log = serde_json::json!({
+            "address": addresses.core,
+            "topics": [STATE_TRANSITION_FACT_EVENT.signature()],
+            "data": format!(
+                "0x{}",
+                hex::encode(encode(&[Token::FixedBytes(fact_hash.0.to_vec())]))
+            ),
+            "blockHash": block_hash,
+            "blockNumber": "0x64",
+            "transactionHash": tx_hash,
+            "transactionIndex": "0x0",
+            "logIndex": "0x0",
+        });
+        // Lists three distinct memory pages, one more than we allow.
+        let pages = (1..=3)
+            .map(|i| Token::FixedBytes(H256::from_low_u64_be(i).0.to_vec()))
+            .collect();
+        let mempage_hashes_log = serde_json::json!({
+            "address": addresses.gps,
+            "topics": [MEMORY_PAGE_HASHES_EVENT.signature()],
+            "data": format!(
+                "0x{}",
+                hex::encode(encode(&[Token::FixedBytes(fact_hash.0.to_vec()), Token::Array(pages)]))
+            ),
+            "blockHash": H256::from_low_u64_be(0x5a),
+            "blockNumber": "0x5a",
+            "transactionHash": H256::from_low_u64_be(0x5b),
+            "transactionIndex": "0x0",
+            "logIndex": "0x0",
+        });
+
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = requests.clone();
+        let transport = crate::test_utils::mock_json_rpc(move |request| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            assert_eq!(request["method"], "eth_getLogs");
+            // The fact is looked up by block hash, the memory page hashes by block range.
+            match request["params"][0].get("blockHash") {
+                Some(_) => Ok(serde_json::json!([fact_log])),
+                None => Ok(serde_json::json!([mempage_hashes_log, fact_log])),
+            }
+        });
+
+        let error = StateUpdate::retrieve(&transport, update_log, chain, 2)
+            .await
+            .unwrap_err();
+
+        assert_matches::assert_matches!(
+            error,
+            RetrieveStateUpdateError::TooManyMemoryPages { pages: 3, max: 2 }
+        );
+        // Only the fact and its memory page hashes were queried, none of the pages themselves.
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+    }
+
     #[tokio::test]
     async fn reality_check() {
         let update_log = StateUpdateLog {
@@ -160,9 +280,14 @@ mod tests {
 
         let chain = crate::ethereum::Chain::Goerli;
         let transport = test_transport(chain);
-        let update = StateUpdate::retrieve(&transport, update_log, chain)
-            .await
-            .unwrap();
+        let update = StateUpdate::retrieve(
+            &transport,
+            update_log,
+            chain,
+            DEFAULT_MAX_MEMORY_PAGES_PER_FACT,
+        )
+        .await
+        .unwrap();
 
         let expected = StateUpdate {
             deployed_contracts: vec![DeployedContract {
@@ -1,11 +1,12 @@
-use std::vec::IntoIter;
-
 use anyhow::{Context, Result};
 use pedersen::StarkHash;
 use web3::types::U256;
 
 use crate::{
-    core::{ContractAddress, ContractHash, StorageAddress, StorageValue},
+    core::{
+        ContractAddress, ContractHash, GlobalRoot, StarknetBlockNumber, StorageAddress,
+        StorageValue,
+    },
     ethereum::state_update::{ContractUpdate, DeployedContract, StateUpdate, StorageUpdate},
 };
 
@@ -31,122 +32,320 @@ use crate::{
 ///         2. For each variable update j:
 ///             a. Variable's address
 ///             b. Variable's new value
-pub struct StateUpdateParser(pub IntoIter<U256>);
+///
+/// The parsing itself is done by [StateDiffStream], this only assembles its items.
+pub struct StateUpdateParser;
 
 impl StateUpdateParser {
-    pub fn parse(mempage_data: Vec<U256>) -> Result<StateUpdate> {
-        let mut parser = Self(mempage_data.into_iter());
-
-        let deployed_contracts = parser.parse_contract_deployments()?;
-        let contract_updates = parser.parse_contract_updates()?;
+    pub fn parse(mempage_data: impl IntoIterator<Item = U256>) -> Result<StateUpdate> {
+        let mut update = StateUpdate {
+            deployed_contracts: Vec::new(),
+            contract_updates: Vec::new(),
+        };
+
+        for item in StateDiffStream::new(mempage_data) {
+            match item? {
+                StateDiffItem::Deployment(deployment) => update.deployed_contracts.push(deployment),
+                StateDiffItem::Contract(address) => update.contract_updates.push(ContractUpdate {
+                    address,
+                    storage_updates: Vec::new(),
+                }),
+                StateDiffItem::Storage(_, storage_update) => update
+                    .contract_updates
+                    .last_mut()
+                    // The stream always yields the contract before its storage updates.
+                    .context("Storage update without a contract")?
+                    .storage_updates
+                    .push(storage_update),
+            }
+        }
 
-        Ok(StateUpdate {
-            deployed_contracts,
-            contract_updates,
-        })
+        Ok(update)
     }
+}
 
-    fn parse_contract_deployments(&mut self) -> Result<Vec<DeployedContract>> {
-        let deployment_data_len = self
-            .0
-            .next()
-            .context("Contract deployment length missing")?;
-        let deployment_data_len =
-            parse_usize(deployment_data_len).context("Parsing contract deployment length")?;
+/// A single item of a [StateUpdate], as yielded by [StateDiffStream].
+#[derive(Debug, Clone, PartialEq)]
+pub enum StateDiffItem {
+    Deployment(DeployedContract),
+    /// Precedes the storage updates of the contract, also if it has none.
+    Contract(ContractAddress),
+    Storage(ContractAddress, StorageUpdate),
+}
 
-        let mut deployment_data = self.0.by_ref().take(deployment_data_len);
-        let mut data_consumed = 0;
+/// Parses [StateUpdate] data into individual [StateDiffItem]s as it is consumed, instead of
+/// assembling the whole [StateUpdate] first. This lets a consumer apply the diff incrementally
+/// while the memory pages are still being decoded.
+///
+/// The stream ends after the first error.
+pub struct StateDiffStream<I> {
+    data: I,
+    state: StreamState,
+}
 
-        let mut deployed_contracts = Vec::new();
-        while let Some(address) = deployment_data.next() {
-            let address = parse_starkhash(address).context("Parsing contract address")?;
-            let address = ContractAddress(address);
+enum StreamState {
+    Start,
+    Deployments {
+        remaining: usize,
+    },
+    ContractCount,
+    Contracts {
+        index: usize,
+        count: usize,
+    },
+    Storage {
+        contract: ContractAddress,
+        update: usize,
+        updates: usize,
+        index: usize,
+        count: usize,
+    },
+    Done,
+}
 
-            let hash = deployment_data
-                .next()
-                .context("Deployed contract hash missing")?;
-            let hash = parse_starkhash(hash).context("Parsing contract hash")?;
-            let hash = ContractHash(hash);
+impl<I: Iterator<Item = U256>> StateDiffStream<I> {
+    pub fn new(mempage_data: impl IntoIterator<IntoIter = I>) -> Self {
+        Self {
+            data: mempage_data.into_iter(),
+            state: StreamState::Start,
+        }
+    }
 
-            let num_constructor_args = deployment_data
-                .next()
-                .context("Constructor arg count missing")?;
-            let num_constructor_args =
-                parse_usize(num_constructor_args).context("Parsing constructor arg count")?;
-
-            let constructor_args = deployment_data
-                .by_ref()
-                .take(num_constructor_args)
-                .map(|arg| parse_starkhash(arg).context("Parsing constructor arg"))
-                .collect::<Result<Vec<_>>>()?;
-            anyhow::ensure!(
-                constructor_args.len() == num_constructor_args,
-                "Missing constructor args"
-            );
+    fn next_item(&mut self) -> Result<Option<StateDiffItem>> {
+        loop {
+            self.state = match self.state {
+                StreamState::Start => {
+                    let len = self
+                        .data
+                        .next()
+                        .context("Contract deployment length missing")?;
+                    let len = parse_usize(len).context("Parsing contract deployment length")?;
+                    StreamState::Deployments { remaining: len }
+                }
+                StreamState::Deployments { remaining: 0 } => StreamState::ContractCount,
+                StreamState::Deployments { remaining } => {
+                    let (deployment, consumed) = self.next_deployment()?;
+                    anyhow::ensure!(
+                        consumed <= remaining,
+                        "contract deployment data length mismatch"
+                    );
+                    self.state = StreamState::Deployments {
+                        remaining: remaining - consumed,
+                    };
+                    return Ok(Some(StateDiffItem::Deployment(deployment)));
+                }
+                StreamState::ContractCount => {
+                    let count = self
+                        .data
+                        .next()
+                        .context("Missing number of contract updates")?;
+                    let count = parse_usize(count).context("Parsing number of contract updates")?;
+                    StreamState::Contracts { index: 0, count }
+                }
+                StreamState::Contracts { index, count } if index == count => StreamState::Done,
+                StreamState::Contracts { index, count } => {
+                    let (contract, updates) = self
+                        .next_contract()
+                        .with_context(|| format!("contract {} of {}", index, count))?;
+
+                    self.state = StreamState::Storage {
+                        contract,
+                        update: 0,
+                        updates,
+                        index,
+                        count,
+                    };
+                    return Ok(Some(StateDiffItem::Contract(contract)));
+                }
+                StreamState::Storage {
+                    update,
+                    updates,
+                    index,
+                    count,
+                    ..
+                } if update == updates => StreamState::Contracts {
+                    index: index + 1,
+                    count,
+                },
+                StreamState::Storage {
+                    contract,
+                    update,
+                    updates,
+                    index,
+                    count,
+                } => {
+                    let storage_update = self
+                        .next_storage_update()
+                        .with_context(|| format!("storage update {} of {}", update, updates))
+                        .with_context(|| format!("contract {} of {}", index, count))?;
+
+                    self.state = StreamState::Storage {
+                        contract,
+                        update: update + 1,
+                        updates,
+                        index,
+                        count,
+                    };
+                    return Ok(Some(StateDiffItem::Storage(contract, storage_update)));
+                }
+                StreamState::Done => return Ok(None),
+            }
+        }
+    }
 
-            deployed_contracts.push(DeployedContract {
-                address,
-                hash,
-                call_data: constructor_args,
-            });
+    /// Parses the next deployed contract, returning it along with the number of elements consumed.
+    fn next_deployment(&mut self) -> Result<(DeployedContract, usize)> {
+        let address = self
+            .data
+            .next()
+            .context("Deployed contract address missing")?;
+        let address = parse_starkhash(address).context("Parsing contract address")?;
 
-            data_consumed += 3 + num_constructor_args;
-        }
+        let hash = self.data.next().context("Deployed contract hash missing")?;
+        let hash = parse_starkhash(hash).context("Parsing contract hash")?;
+
+        let num_constructor_args = self.data.next().context("Constructor arg count missing")?;
+        let num_constructor_args =
+            parse_usize(num_constructor_args).context("Parsing constructor arg count")?;
 
+        let call_data = self
+            .data
+            .by_ref()
+            .take(num_constructor_args)
+            .map(|arg| parse_starkhash(arg).context("Parsing constructor arg"))
+            .collect::<Result<Vec<_>>>()?;
         anyhow::ensure!(
-            data_consumed == deployment_data_len,
-            "contract deployment data length mismatch"
+            call_data.len() == num_constructor_args,
+            "Missing constructor args"
         );
 
-        Ok(deployed_contracts)
-    }
+        let deployment = DeployedContract {
+            address: ContractAddress(address),
+            hash: ContractHash(hash),
+            call_data,
+        };
 
-    fn parse_contract_updates(&mut self) -> Result<Vec<ContractUpdate>> {
-        let num_contracts = self
-            .0
-            .next()
-            .context("Missing number of contract updates")?;
-        let num_contracts =
-            parse_usize(num_contracts).context("Parsing number of contract updates")?;
-
-        (0..num_contracts)
-            .map(|i| {
-                self.parse_contract_update()
-                    .with_context(|| format!("contract {} of {}", i, num_contracts))
-            })
-            .collect()
+        Ok((deployment, 3 + num_constructor_args))
     }
 
-    fn parse_contract_update(&mut self) -> Result<ContractUpdate> {
-        let address = self.0.next().context("Missing contract address")?;
+    /// Parses the address and number of storage updates of the next updated contract.
+    fn next_contract(&mut self) -> Result<(ContractAddress, usize)> {
+        let address = self.data.next().context("Missing contract address")?;
         let address = parse_starkhash(address).context("Parsing contract address")?;
-        let address = ContractAddress(address);
 
-        let num_updates = self.0.next().context("Missing number of storage updates")?;
+        let num_updates = self
+            .data
+            .next()
+            .context("Missing number of storage updates")?;
         let num_updates = parse_usize(num_updates).context("Parsing Number of storage updates")?;
 
-        let storage_updates = (0..num_updates)
-            .map(|i| {
-                self.parse_storage_update()
-                    .with_context(|| format!("storage update {} of {}", i, num_updates))
-            })
-            .collect::<Result<Vec<_>>>()?;
-
-        Ok(ContractUpdate {
-            address,
-            storage_updates,
-        })
+        Ok((ContractAddress(address), num_updates))
     }
 
-    fn parse_storage_update(&mut self) -> Result<StorageUpdate> {
-        let address = self.0.next().context("Missing storage address")?;
+    fn next_storage_update(&mut self) -> Result<StorageUpdate> {
+        let address = self.data.next().context("Missing storage address")?;
         let address = parse_starkhash(address).context("Parsing storage address")?;
-        let address = StorageAddress(address);
-        let value = self.0.next().context("Missing storage value")?;
+        let value = self.data.next().context("Missing storage value")?;
         let value = parse_starkhash(value).context("Parsing storage value")?;
-        let value = StorageValue(value);
 
-        Ok(StorageUpdate { address, value })
+        Ok(StorageUpdate {
+            address: StorageAddress(address),
+            value: StorageValue(value),
+        })
+    }
+}
+
+impl<I: Iterator<Item = U256>> Iterator for StateDiffStream<I> {
+    type Item = Result<StateDiffItem>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_item() {
+            Ok(item) => item.map(Ok),
+            Err(e) => {
+                self.state = StreamState::Done;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// Streams the [StateDiffItem]s of a state update's memory pages, consuming the data pages
+/// lazily in order. See [data_pages].
+pub fn stream_state_diff(
+    mempages: &[Vec<U256>],
+) -> Result<StateDiffStream<impl Iterator<Item = U256> + '_>> {
+    let data = data_pages(mempages)?;
+    Ok(StateDiffStream::new(data.iter().flatten().copied()))
+}
+
+/// The first memory page of a state update. It holds the StarkNet OS program output, which
+/// describes the state transition, instead of [StateUpdate] data.
+///
+/// Its structure is as follows:
+///     1. The previous global root
+///     2. The new global root
+///     3. The StarkNet block number
+///     4. Length of the L2 to L1 messages (in elements), followed by the messages
+///     5. Length of the L1 to L2 messages (in elements), followed by the messages
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemoryPageMetadata {
+    pub previous_root: GlobalRoot,
+    pub new_root: GlobalRoot,
+    pub block_number: StarknetBlockNumber,
+    pub l2_to_l1_messages_len: usize,
+    pub l1_to_l2_messages_len: usize,
+}
+
+impl MemoryPageMetadata {
+    pub fn parse(page: &[U256]) -> Result<Self> {
+        let mut page = page.iter().copied();
+
+        let previous_root = page.next().context("Previous global root missing")?;
+        let previous_root =
+            GlobalRoot(parse_starkhash(previous_root).context("Parsing previous global root")?);
+
+        let new_root = page.next().context("New global root missing")?;
+        let new_root = GlobalRoot(parse_starkhash(new_root).context("Parsing new global root")?);
+
+        let block_number = page.next().context("Block number missing")?;
+        anyhow::ensure!(
+            block_number <= U256::from(u64::MAX),
+            "Block number exceeds u64::MAX"
+        );
+        let block_number = StarknetBlockNumber(block_number.as_u64());
+
+        let mut segment_len = |name: &str| -> Result<usize> {
+            let len = page
+                .next()
+                .with_context(|| format!("{} length missing", name))?;
+            let len = parse_usize(len).with_context(|| format!("Parsing {} length", name))?;
+            anyhow::ensure!(
+                page.by_ref().take(len).count() == len,
+                "{} are incomplete",
+                name
+            );
+            Ok(len)
+        };
+
+        let l2_to_l1_messages_len = segment_len("L2 to L1 messages")?;
+        let l1_to_l2_messages_len = segment_len("L1 to L2 messages")?;
+
+        Ok(Self {
+            previous_root,
+            new_root,
+            block_number,
+            l2_to_l1_messages_len,
+            l1_to_l2_messages_len,
+        })
+    }
+}
+
+/// Returns the memory pages which contain the [StateUpdate] data, in the order in which they
+/// are to be concatenated. This is every page after the [MemoryPageMetadata] page.
+pub fn data_pages(mempages: &[Vec<U256>]) -> Result<&[Vec<U256>]> {
+    match mempages {
+        [_metadata, data @ ..] => Ok(data),
+        [] => anyhow::bail!("Memory page metadata missing"),
     }
 }
 
@@ -279,6 +478,97 @@ mod tests {
         }
     }
 
+    mod memory_page_metadata {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        /// A synthetic metadata page with one message in each direction. The new root and
+        /// block number are those of the `fixtures/logs/state_update.json` log, while the
+        /// previous root and the messages are made up.
+        fn page() -> (Vec<U256>, MemoryPageMetadata) {
+            let new_root = StarkHash::from_hex_str(
+                "06bd197ccc199cc3be696635a482ff818a1f166ef91c5fd844aacafb15a12bcd",
+            )
+            .unwrap();
+            let previous_root = StarkHash::from_hex_str("0x1234").unwrap();
+
+            let page = vec![
+                u256_from_starkhash(previous_root),
+                u256_from_starkhash(new_root),
+                U256::from(13699),
+                // from, to, payload length and payload
+                U256::from(4),
+                U256::from(1),
+                U256::from(2),
+                U256::from(1),
+                U256::from(3),
+                // from, to, nonce, selector, payload length and payload
+                U256::from(7),
+                U256::from(4),
+                U256::from(5),
+                U256::from(0),
+                U256::from(6),
+                U256::from(2),
+                U256::from(7),
+                U256::from(8),
+            ];
+
+            let metadata = MemoryPageMetadata {
+                previous_root: GlobalRoot(previous_root),
+                new_root: GlobalRoot(new_root),
+                block_number: StarknetBlockNumber(13699),
+                l2_to_l1_messages_len: 4,
+                l1_to_l2_messages_len: 7,
+            };
+
+            (page, metadata)
+        }
+
+        #[test]
+        fn ok() {
+            let (page, expected) = page();
+            let metadata = MemoryPageMetadata::parse(&page).unwrap();
+            assert_eq!(metadata, expected);
+        }
+
+        #[test]
+        fn no_messages() {
+            let (mut page, mut expected) = page();
+            page.truncate(3);
+            page.extend([U256::zero(), U256::zero()]);
+            expected.l2_to_l1_messages_len = 0;
+            expected.l1_to_l2_messages_len = 0;
+
+            let metadata = MemoryPageMetadata::parse(&page).unwrap();
+            assert_eq!(metadata, expected);
+        }
+
+        #[test]
+        fn incomplete_messages() {
+            let (mut page, _) = page();
+            page.pop();
+            MemoryPageMetadata::parse(&page).unwrap_err();
+        }
+
+        #[test]
+        fn missing_data() {
+            let (page, _) = page();
+            MemoryPageMetadata::parse(&page[..2]).unwrap_err();
+        }
+
+        #[test]
+        fn data_pages_skip_metadata() {
+            let pages = vec![
+                vec![U256::from(1)],
+                vec![U256::from(2)],
+                vec![U256::from(3)],
+            ];
+            assert_eq!(data_pages(&pages).unwrap(), &pages[1..]);
+            assert_eq!(data_pages(&pages[..1]).unwrap(), &[] as &[Vec<U256>]);
+            data_pages(&[]).unwrap_err();
+        }
+    }
+
     mod parse_usize {
         use super::*;
         use pretty_assertions::assert_eq;
@@ -309,7 +599,7 @@ mod tests {
         }
     }
 
-    mod parse_storage_update {
+    mod next_storage_update {
         use super::*;
         use pretty_assertions::assert_eq;
 
@@ -321,8 +611,8 @@ mod tests {
             };
             let data: Vec<U256> = update.clone().into();
 
-            let mut parser = StateUpdateParser(data.into_iter());
-            let result = parser.parse_storage_update().unwrap();
+            let mut stream = StateDiffStream::new(data);
+            let result = stream.next_storage_update().unwrap();
             assert_eq!(result, update);
         }
 
@@ -335,120 +625,193 @@ mod tests {
             let mut data: Vec<U256> = update.into();
             data.pop();
 
-            let mut parser = StateUpdateParser(data.into_iter());
-            parser.parse_storage_update().unwrap_err();
+            let mut stream = StateDiffStream::new(data);
+            stream.next_storage_update().unwrap_err();
         }
     }
 
-    mod parse_contract_update {
+    mod contract_updates {
         use super::*;
         use pretty_assertions::assert_eq;
 
+        fn fact(contract_updates: Vec<ContractUpdate>) -> StateUpdate {
+            StateUpdate {
+                deployed_contracts: Vec::new(),
+                contract_updates,
+            }
+        }
+
         #[test]
         fn ok() {
-            let update = contract_update();
-            let data: Vec<U256> = update.clone().into();
+            let fact = fact(vec![contract_update(), contract_update()]);
+            let data: Vec<U256> = fact.clone().into();
 
-            let mut parser = StateUpdateParser(data.into_iter());
-            let result = parser.parse_contract_update().unwrap();
-            assert_eq!(result, update);
+            let result = StateUpdateParser::parse(data).unwrap();
+            assert_eq!(result, fact);
         }
 
         #[test]
         fn no_storage_updates() {
-            let update = ContractUpdate {
-                address: ContractAddress(StarkHash::from_hex_str("123456").unwrap()),
-                storage_updates: Vec::new(),
-            };
-
-            let data: Vec<U256> = update.clone().into();
+            let fact = fact(vec![
+                ContractUpdate {
+                    address: ContractAddress(StarkHash::from_hex_str("123456").unwrap()),
+                    storage_updates: Vec::new(),
+                },
+                contract_update(),
+            ]);
+            let data: Vec<U256> = fact.clone().into();
 
-            let mut parser = StateUpdateParser(data.into_iter());
-            let result = parser.parse_contract_update().unwrap();
-            assert_eq!(result, update);
+            let result = StateUpdateParser::parse(data).unwrap();
+            assert_eq!(result, fact);
         }
 
         #[test]
         fn missing_storage_update() {
-            // Corrupt the update length field, increasing it by 1.
-            let update = contract_update();
-            let mut data: Vec<U256> = update.into();
+            let mut data: Vec<U256> = fact(vec![contract_update()]).into();
+            // Corrupt the update length field of the contract, increasing it by 1.
+            data[3] += U256::from(1);
+
+            let error = StateUpdateParser::parse(data).unwrap_err();
+            let error = format!("{:#}", error);
+            assert!(error.starts_with("contract 0 of 1: storage update 2 of 3: "));
+        }
+
+        #[test]
+        fn missing_contract_update() {
+            let mut data: Vec<U256> = fact(vec![contract_update(), contract_update()]).into();
+            // Corrupt the contract count field, increasing it by 1.
             data[1] += U256::from(1);
 
-            let mut parser = StateUpdateParser(data.into_iter());
-            parser.parse_contract_update().unwrap_err();
+            let error = StateUpdateParser::parse(data).unwrap_err();
+            let error = format!("{:#}", error);
+            assert!(error.starts_with("contract 2 of 3: "));
         }
     }
 
-    mod parse_contract_updates {
+    mod contract_deployments {
         use super::*;
         use pretty_assertions::assert_eq;
 
         #[test]
         fn ok() {
-            let updates = ContractUpdates(vec![contract_update(), contract_update()]);
-            let data: Vec<U256> = updates.clone().into();
+            let fact = StateUpdate {
+                deployed_contracts: vec![deployed_contract(), deployed_contract()],
+                contract_updates: Vec::new(),
+            };
+            let data: Vec<U256> = fact.clone().into();
 
-            let mut parser = StateUpdateParser(data.into_iter());
-            let result = parser.parse_contract_updates().unwrap();
-            assert_eq!(result, updates.0);
+            let result = StateUpdateParser::parse(data).unwrap();
+            assert_eq!(result, fact);
         }
 
         #[test]
-        fn no_contract_updates() {
-            let updates = ContractUpdates(Vec::new());
-            let data: Vec<U256> = updates.clone().into();
+        fn missing_data() {
+            let deployment = DeploymentUpdates(vec![deployed_contract(), deployed_contract()]);
+            let mut data: Vec<U256> = deployment.into();
+            // Corrupt the length field, increasing it by 1.
+            data[0] += U256::from(1);
 
-            let mut parser = StateUpdateParser(data.into_iter());
-            let result = parser.parse_contract_updates().unwrap();
-            assert_eq!(result, updates.0);
+            StateUpdateParser::parse(data).unwrap_err();
         }
 
         #[test]
-        fn missing_contract_update() {
-            let updates = ContractUpdates(vec![contract_update(), contract_update()]);
-            // Corrupt the update length field, increasing it by 1.
-            let mut data: Vec<U256> = updates.into();
-            data[0] += U256::from(1);
+        fn length_mismatch() {
+            let fact = StateUpdate {
+                deployed_contracts: vec![deployed_contract()],
+                contract_updates: Vec::new(),
+            };
+            let mut data: Vec<U256> = fact.into();
+            // Corrupt the length field, decreasing it by 1.
+            data[0] -= U256::from(1);
 
-            let mut parser = StateUpdateParser(data.into_iter());
-            parser.parse_contract_updates().unwrap_err();
+            let items = StateDiffStream::new(data).collect::<Vec<_>>();
+            assert_eq!(items.len(), 1);
+            items[0].as_ref().unwrap_err();
         }
     }
 
-    mod parse_contract_deployments {
+    mod state_diff_stream {
         use super::*;
         use pretty_assertions::assert_eq;
 
+        /// Splits the data of `fact` over several data pages, after a metadata page.
+        fn multi_page_fact(fact: StateUpdate) -> Vec<Vec<U256>> {
+            let data: Vec<U256> = fact.into();
+            std::iter::once(vec![U256::zero()])
+                .chain(data.chunks(5).map(|chunk| chunk.to_vec()))
+                .collect()
+        }
+
         #[test]
-        fn ok() {
-            let deployment = DeploymentUpdates(vec![deployed_contract(), deployed_contract()]);
-            let data: Vec<U256> = deployment.clone().into();
+        fn items_in_order() {
+            let empty = ContractAddress(StarkHash::from_hex_str("789").unwrap());
+            let fact = StateUpdate {
+                deployed_contracts: vec![deployed_contract()],
+                contract_updates: vec![
+                    ContractUpdate {
+                        address: empty,
+                        storage_updates: Vec::new(),
+                    },
+                    contract_update(),
+                ],
+            };
+            let pages = multi_page_fact(fact);
+            assert!(pages.len() > 3);
+
+            let update = contract_update();
+            let expected = vec![
+                StateDiffItem::Deployment(deployed_contract()),
+                StateDiffItem::Contract(empty),
+                StateDiffItem::Contract(update.address),
+                StateDiffItem::Storage(update.address, update.storage_updates[0].clone()),
+                StateDiffItem::Storage(update.address, update.storage_updates[1].clone()),
+            ];
 
-            let mut parser = StateUpdateParser(data.into_iter());
-            let result = parser.parse_contract_deployments().unwrap();
-            assert_eq!(result, deployment.0);
+            let streamed = stream_state_diff(&pages)
+                .unwrap()
+                .collect::<Result<Vec<_>>>()
+                .unwrap();
+            assert_eq!(streamed, expected);
         }
 
         #[test]
-        fn no_updates() {
-            let deployment = DeploymentUpdates(Vec::new());
-            let data: Vec<U256> = deployment.clone().into();
+        fn parses_across_pages() {
+            let fact = StateUpdate {
+                deployed_contracts: vec![deployed_contract(), deployed_contract()],
+                contract_updates: vec![contract_update(), contract_update()],
+            };
+            let pages = multi_page_fact(fact.clone());
 
-            let mut parser = StateUpdateParser(data.into_iter());
-            let result = parser.parse_contract_deployments().unwrap();
-            assert_eq!(result, deployment.0);
+            let data = data_pages(&pages).unwrap().iter().flatten().copied();
+            assert_eq!(StateUpdateParser::parse(data).unwrap(), fact);
         }
 
         #[test]
-        fn missing_data() {
-            let deployment = DeploymentUpdates(vec![deployed_contract(), deployed_contract()]);
-            let mut data: Vec<U256> = deployment.into();
-            // Corrupt the length field, increasing it by 1.
-            data[0] += U256::from(1);
+        fn no_updates() {
+            let pages = multi_page_fact(StateUpdate {
+                deployed_contracts: Vec::new(),
+                contract_updates: Vec::new(),
+            });
 
-            let mut parser = StateUpdateParser(data.into_iter());
-            parser.parse_contract_deployments().unwrap_err();
+            assert_eq!(stream_state_diff(&pages).unwrap().count(), 0);
+        }
+
+        #[test]
+        fn truncated_data() {
+            let fact = StateUpdate {
+                deployed_contracts: vec![deployed_contract()],
+                contract_updates: vec![contract_update()],
+            };
+            let mut pages = multi_page_fact(fact);
+            pages.last_mut().unwrap().pop();
+
+            let mut stream = stream_state_diff(&pages).unwrap();
+            let items = stream.by_ref().collect::<Vec<_>>();
+            // The deployment, contract and first storage update are yielded before the error.
+            assert_eq!(items.len(), 4);
+            assert!(items[..3].iter().all(|item| item.is_ok()));
+            items[3].as_ref().unwrap_err();
+            assert!(stream.next().is_none());
         }
     }
 
@@ -3,17 +3,18 @@ use std::collections::{HashMap, HashSet};
 use anyhow::Context;
 use web3::{
     futures::future::try_join_all,
-    types::{FilterBuilder, Transaction, TransactionId, U256},
+    types::{FilterBuilder, Transaction, TransactionId, H256, U256},
     Transport, Web3,
 };
 
+use crate::core::{GlobalRoot, StarknetBlockNumber};
 use crate::ethereum::{
     contract::{REGISTER_MEMORY_PAGE_FUNCTION, STATE_TRANSITION_FACT_EVENT},
     log::{
         BackwardFetchError, BackwardLogFetcher, EitherMetaLog, MemoryPageFactContinuousLog,
         MemoryPagesHashesLog, StateTransitionFactLog, StateUpdateLog,
     },
-    state_update::RetrieveStateUpdateError,
+    state_update::{MemoryPageMetadata, RetrieveStateUpdateError},
     Chain,
 };
 
@@ -42,7 +43,7 @@ pub async fn retrieve_transition_fact<T: Transport>(
         .logs(filter)
         .await
         .context("Error retrieving StateTransitionFactLog")?;
-    for log in logs {
+    for log in logs.into_iter().filter(|log| log.removed != Some(true)) {
         let log = StateTransitionFactLog::try_from(log)?;
 
         if log.origin.block == state_update.origin.block
@@ -136,6 +137,22 @@ pub async fn retrieve_memory_page_logs<T: Transport>(
     Ok(mempages)
 }
 
+/// Rejects facts which list more than `max_pages` memory pages, as fetching each page costs
+/// several L1 queries.
+pub fn verify_mempage_count(
+    page_hashes: &[H256],
+    max_pages: usize,
+) -> Result<(), RetrieveStateUpdateError> {
+    if page_hashes.len() > max_pages {
+        return Err(RetrieveStateUpdateError::TooManyMemoryPages {
+            pages: page_hashes.len(),
+            max: max_pages,
+        });
+    }
+
+    Ok(())
+}
+
 /// Retrieves and parses the transaction data of the given [MemoryPageFactContinuousLog]'s.
 ///
 /// These can be parsed into a [StateUpdate](crate::ethereum::state_update::StateUpdate).
@@ -166,6 +183,43 @@ pub async fn retrieve_mempage_transaction_data<T: Transport>(
     Ok(data)
 }
 
+/// Verifies that the retrieved memory page data hashes to the page hashes of the
+/// [MemoryPagesHashesLog], in the same order.
+///
+/// The memory page registry on L1 identifies a continuous page by the Keccak256 hash of its values,
+/// each encoded as a 32 byte big-endian word. This is the only link between the page data and the
+/// state transition fact, so any mismatch means the data cannot be trusted.
+pub fn verify_mempage_data(pages: &[Vec<U256>], expected: &[H256]) -> anyhow::Result<()> {
+    use sha3::{Digest, Keccak256};
+
+    anyhow::ensure!(
+        pages.len() == expected.len(),
+        "expected {} memory pages but got {}",
+        expected.len(),
+        pages.len()
+    );
+
+    for (i, (page, expected)) in pages.iter().zip(expected).enumerate() {
+        let mut hasher = Keccak256::new();
+        let mut word = [0u8; 32];
+        for value in page {
+            value.to_big_endian(&mut word);
+            hasher.update(word);
+        }
+        let hash = H256::from_slice(&hasher.finalize());
+
+        anyhow::ensure!(
+            &hash == expected,
+            "memory page {} hash mismatch: expected {:?} but data hashes to {:?}",
+            i,
+            expected,
+            hash
+        );
+    }
+
+    Ok(())
+}
+
 fn decode_mempage_transaction(transaction: Transaction) -> anyhow::Result<Vec<U256>> {
     // The first 4 bytes of data represent the short-signature of the function.
     // These must exist in order to be valid. We should compare the signature as
@@ -196,3 +250,135 @@ fn decode_mempage_transaction(transaction: Transaction) -> anyhow::Result<Vec<U2
         })
         .collect()
 }
+
+/// Checks that the [MemoryPageMetadata] describes the state update of the [StateUpdateLog]
+/// with the given `block_number` and `global_root`.
+pub fn verify_metadata(
+    metadata: &MemoryPageMetadata,
+    block_number: StarknetBlockNumber,
+    global_root: GlobalRoot,
+) -> anyhow::Result<()> {
+    anyhow::ensure!(
+        metadata.block_number == block_number,
+        "Memory page metadata is for block {} but the state update log is for block {}",
+        metadata.block_number.0,
+        block_number.0
+    );
+    anyhow::ensure!(
+        metadata.new_root == global_root,
+        "Memory page metadata has new global root {} but the state update log has {}",
+        metadata.new_root.0,
+        global_root.0
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    mod verify_metadata {
+        use super::super::verify_metadata;
+        use crate::core::{GlobalRoot, StarknetBlockNumber};
+        use crate::ethereum::state_update::MemoryPageMetadata;
+        use pedersen::StarkHash;
+
+        fn metadata() -> MemoryPageMetadata {
+            MemoryPageMetadata {
+                previous_root: GlobalRoot(StarkHash::from_hex_str("0x1234").unwrap()),
+                new_root: GlobalRoot(StarkHash::from_hex_str("0x5678").unwrap()),
+                block_number: StarknetBlockNumber(13699),
+                l2_to_l1_messages_len: 0,
+                l1_to_l2_messages_len: 0,
+            }
+        }
+
+        #[test]
+        fn matching() {
+            let metadata = metadata();
+            verify_metadata(&metadata, metadata.block_number, metadata.new_root).unwrap();
+        }
+
+        #[test]
+        fn block_number_mismatch() {
+            let metadata = metadata();
+            verify_metadata(&metadata, StarknetBlockNumber(13700), metadata.new_root).unwrap_err();
+        }
+
+        #[test]
+        fn global_root_mismatch() {
+            let metadata = metadata();
+            // The previous root must not be mistaken for the new one.
+            verify_metadata(&metadata, metadata.block_number, metadata.previous_root).unwrap_err();
+        }
+    }
+
+    mod verify_mempage_count {
+        use super::super::verify_mempage_count;
+        use crate::ethereum::state_update::RetrieveStateUpdateError;
+        use assert_matches::assert_matches;
+        use web3::types::H256;
+
+        #[test]
+        fn within_cap() {
+            verify_mempage_count(&[H256::zero(); 3], 3).unwrap();
+            verify_mempage_count(&[], 3).unwrap();
+        }
+
+        #[test]
+        fn exceeding_cap_is_rejected() {
+            let error = verify_mempage_count(&[H256::zero(); 4], 3).unwrap_err();
+            assert_matches!(
+                error,
+                RetrieveStateUpdateError::TooManyMemoryPages { pages: 4, max: 3 }
+            );
+        }
+    }
+
+    mod verify_mempage_data {
+        use super::super::verify_mempage_data;
+        use std::str::FromStr;
+        use web3::types::{H256, U256};
+
+        /// keccak256(abi.encodePacked(uint256(0)))
+        const ZERO_WORD: &str = "290decd9548b62a8d60345a988386fc84ba6bc95484008f6362f93160ef3e563";
+        /// keccak256(abi.encodePacked(uint256(1)))
+        const ONE_WORD: &str = "b10e2d527612073b26eecdfd717e6a320cf44b4afac2b0732d9fcbe2b7fa0cf6";
+        /// keccak256 of no data
+        const EMPTY: &str = "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470";
+
+        fn pages() -> Vec<Vec<U256>> {
+            vec![vec![U256::zero()], vec![U256::one()], vec![]]
+        }
+
+        fn hashes() -> Vec<H256> {
+            [ZERO_WORD, ONE_WORD, EMPTY]
+                .iter()
+                .map(|h| H256::from_str(h).unwrap())
+                .collect()
+        }
+
+        #[test]
+        fn correct() {
+            verify_mempage_data(&pages(), &hashes()).unwrap();
+        }
+
+        #[test]
+        fn corrupted_data() {
+            let mut pages = pages();
+            pages[1][0] = U256::from(2);
+            verify_mempage_data(&pages, &hashes()).unwrap_err();
+        }
+
+        #[test]
+        fn wrong_order() {
+            let mut hashes = hashes();
+            hashes.swap(0, 1);
+            verify_mempage_data(&pages(), &hashes).unwrap_err();
+        }
+
+        #[test]
+        fn missing_page() {
+            verify_mempage_data(&pages()[..2], &hashes()).unwrap_err();
+        }
+    }
+}
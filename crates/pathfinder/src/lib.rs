@@ -7,3 +7,6 @@ pub mod rpc;
 pub mod sequencer;
 pub mod state;
 pub mod storage;
+
+#[cfg(test)]
+mod test_utils;
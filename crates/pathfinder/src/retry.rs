@@ -3,7 +3,8 @@ use std::{
     future::Future,
     num::{NonZeroU64, NonZeroUsize},
     result::Result,
-    time::Duration,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
 };
 use tokio_retry::{strategy::ExponentialBackoff, Retry as TokioRetry, RetryIf as TokioRetryIf};
 
@@ -13,7 +14,36 @@ where
     FutureFactory: FnMut() -> Fut,
 {
     future_factory: FutureFactory,
-    strategy: Strategy,
+    strategy: RetryPolicy,
+}
+
+/// Classifies an error for [retry].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Retriable {
+    /// The error is transient, and the operation should be retried.
+    Transient,
+    /// The error is permanent, and should be returned immediately.
+    Permanent,
+}
+
+/// Retries `op` with the backoff defined by `policy`, for as long as `classify` considers the
+/// errors [Retriable::Transient] and the retries have not been exhausted.
+///
+/// Returns the first `Ok()` value, the first [Retriable::Permanent] error, or the last
+/// [Retriable::Transient] error if the number of retries or the [RetryBudget] of the policy was
+/// exhausted.
+pub async fn retry<T, E, Fut, F, C>(policy: &RetryPolicy, classify: C, op: F) -> Result<T, E>
+where
+    Fut: Future<Output = Result<T, E>>,
+    F: FnMut() -> Fut,
+    C: Fn(&E) -> Retriable,
+{
+    Retry {
+        future_factory: op,
+        strategy: policy.clone(),
+    }
+    .when(|e| classify(e) == Retriable::Transient && policy.withdraw_retry())
+    .await
 }
 
 impl<T, E, Fut, FutureFactory> Retry<T, E, Fut, FutureFactory>
@@ -28,12 +58,7 @@ where
     pub fn exponential(future_factory: FutureFactory, base_secs: NonZeroU64) -> Self {
         Self {
             future_factory,
-            strategy: Strategy {
-                base_secs,
-                factor: NonZeroU64::new(1).unwrap(),
-                max_delay: None,
-                max_num_retries: None,
-            },
+            strategy: RetryPolicy::exponential(base_secs),
         }
     }
 
@@ -78,11 +103,126 @@ where
     }
 }
 
-struct Strategy {
+/// The backoff strategy used by [retry] and [Retry].
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
     base_secs: NonZeroU64,
     factor: NonZeroU64,
     max_delay: Option<Duration>,
     max_num_retries: Option<NonZeroUsize>,
+    budget: Option<RetryBudget>,
+}
+
+impl RetryPolicy {
+    /// Exponential backoff with initial backoff of `base_secs` seconds.
+    ///
+    /// `Nth` backoff is equal to `base_secs ^ N` seconds.
+    pub fn exponential(base_secs: NonZeroU64) -> Self {
+        Self {
+            base_secs,
+            factor: NonZeroU64::new(1).unwrap(),
+            max_delay: None,
+            max_num_retries: None,
+            budget: None,
+        }
+    }
+
+    /// Multiply backoff by this factor.
+    ///
+    /// `Nth` backoff is then equal to `base_secs ^ N * factor` seconds.
+    pub fn factor(mut self, factor: NonZeroU64) -> Self {
+        self.factor = factor;
+        self
+    }
+
+    /// Saturate backoff at `max_delay`.
+    pub fn max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = Some(max_delay);
+        self
+    }
+
+    /// Limit the number of retries to `max_num_retries`.
+    pub fn max_num_retries(mut self, max_num_retries: NonZeroUsize) -> Self {
+        self.max_num_retries = Some(max_num_retries);
+        self
+    }
+
+    /// Draw every retry from `budget`, giving up once it is exhausted.
+    pub fn budget(mut self, budget: RetryBudget) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// Takes a token for a single retry from the budget, if there is one.
+    fn withdraw_retry(&self) -> bool {
+        match &self.budget {
+            Some(budget) if !budget.try_withdraw() => {
+                tracing::debug!("Retry budget exhausted, not retrying");
+                false
+            }
+            _ => true,
+        }
+    }
+}
+
+/// A token bucket of retries, shared by all [RetryPolicies](RetryPolicy) it is cloned into.
+///
+/// Per call retry limits still compound into a storm of requests when a provider goes down and
+/// every call starts retrying at once. Drawing the retries from a shared budget bounds the total
+/// retry rate: once the budget is exhausted failing calls are returned immediately, until the
+/// budget has refilled.
+///
+/// The node creates a single [RetryBudget::default] and hands clones of it to the Ethereum and
+/// sequencer clients.
+#[derive(Clone, Debug)]
+pub struct RetryBudget(Arc<Mutex<Bucket>>);
+
+#[derive(Debug)]
+struct Bucket {
+    capacity: f64,
+    tokens: f64,
+    refill_interval: Duration,
+    last_refill: Instant,
+}
+
+impl RetryBudget {
+    /// A full budget of `capacity` retries, which regains one retry every `refill_interval`.
+    pub fn new(capacity: u32, refill_interval: Duration) -> Self {
+        Self(Arc::new(Mutex::new(Bucket {
+            capacity: capacity as f64,
+            tokens: capacity as f64,
+            refill_interval,
+            last_refill: Instant::now(),
+        })))
+    }
+
+    /// Takes a single retry from the budget. Returns `false` if the budget is exhausted.
+    pub fn try_withdraw(&self) -> bool {
+        let mut bucket = self
+            .0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        let now = Instant::now();
+        let refilled = now.duration_since(bucket.last_refill).as_secs_f64()
+            / bucket.refill_interval.as_secs_f64();
+        bucket.tokens = (bucket.tokens + refilled).min(bucket.capacity);
+        bucket.last_refill = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl Default for RetryBudget {
+    /// The budget of the node: a burst of 100 retries, refilling at 10 retries per second.
+    fn default() -> Self {
+        Self::new(100, Duration::from_millis(100))
+    }
 }
 
 enum MaybeLimited {
@@ -101,8 +241,8 @@ impl std::iter::Iterator for MaybeLimited {
     }
 }
 
-impl From<Strategy> for MaybeLimited {
-    fn from(s: Strategy) -> Self {
+impl From<RetryPolicy> for MaybeLimited {
+    fn from(s: RetryPolicy) -> Self {
         // We use milliseconds in tests
         #[cfg(test)]
         const FACTOR: u32 = 1;
@@ -133,7 +273,7 @@ impl From<Strategy> for MaybeLimited {
 
 #[cfg(test)]
 mod tests {
-    use super::Retry;
+    use super::{retry, Retriable, Retry, RetryBudget, RetryPolicy};
     use std::{
         cell::RefCell,
         iter::{IntoIterator, Iterator},
@@ -352,4 +492,108 @@ mod tests {
             assert_eq!(uut.call_count(), 3);
         }
     }
+
+    mod policy {
+        use super::*;
+
+        fn classify(e: &Failure) -> Retriable {
+            match e {
+                Failure::Retryable => Retriable::Transient,
+                Failure::Fatal => Retriable::Permanent,
+            }
+        }
+
+        #[tokio::test]
+        async fn transient_then_success() {
+            let uut = Uut::new([
+                Err(Failure::Retryable),
+                Err(Failure::Retryable),
+                Ok(Success),
+                Err(Failure::Fatal),
+            ]);
+            let policy = RetryPolicy::exponential(NonZeroU64::new(2).unwrap());
+
+            retry(&policy, classify, || uut.do_work()).await.unwrap();
+            assert_eq!(uut.call_count(), 3);
+        }
+
+        #[tokio::test]
+        async fn permanent_returns_immediately() {
+            let uut = Uut::new([Err(Failure::Fatal), Ok(Success)]);
+            let policy = RetryPolicy::exponential(NonZeroU64::new(2).unwrap());
+
+            assert_eq!(
+                retry(&policy, classify, || uut.do_work())
+                    .await
+                    .unwrap_err(),
+                Failure::Fatal
+            );
+            assert_eq!(uut.call_count(), 1);
+        }
+
+        #[tokio::test]
+        async fn transient_exhausts_retries() {
+            let uut = Uut::new([Err(Failure::Retryable); 4]);
+            let policy = RetryPolicy::exponential(NonZeroU64::new(1).unwrap())
+                .max_num_retries(NonZeroUsize::new(2).unwrap());
+
+            assert_eq!(
+                retry(&policy, classify, || uut.do_work())
+                    .await
+                    .unwrap_err(),
+                Failure::Retryable
+            );
+            // Retry limit of 2 means 3 tries altogether
+            assert_eq!(uut.call_count(), 3);
+        }
+    }
+
+    mod budget {
+        use super::*;
+
+        fn classify(_: &Failure) -> Retriable {
+            Retriable::Transient
+        }
+
+        #[tokio::test]
+        async fn exhausted_budget_fails_immediately() {
+            let budget = RetryBudget::new(2, Duration::from_secs(3600));
+            let policy =
+                RetryPolicy::exponential(NonZeroU64::new(1).unwrap()).budget(budget.clone());
+
+            let uut = Uut::new([Err(Failure::Retryable); 10]);
+            assert_eq!(
+                retry(&policy, classify, || uut.do_work())
+                    .await
+                    .unwrap_err(),
+                Failure::Retryable
+            );
+            // A budget of 2 retries means 3 tries altogether
+            assert_eq!(uut.call_count(), 3);
+
+            // The budget is shared, so other calls are not retried either
+            let uut = Uut::new([Err(Failure::Retryable), Ok(Success)]);
+            retry(&policy, classify, || uut.do_work())
+                .await
+                .unwrap_err();
+            assert_eq!(uut.call_count(), 1);
+            assert!(!budget.try_withdraw());
+        }
+
+        #[tokio::test]
+        async fn refills_over_time() {
+            let budget = RetryBudget::new(1, Duration::from_millis(50));
+            assert!(budget.try_withdraw());
+            assert!(!budget.try_withdraw());
+
+            tokio::time::sleep(Duration::from_millis(60)).await;
+            assert!(budget.try_withdraw());
+            assert!(!budget.try_withdraw());
+
+            // Never refills beyond its capacity
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            assert!(budget.try_withdraw());
+            assert!(!budget.try_withdraw());
+        }
+    }
 }
@@ -1,5 +1,6 @@
 //! StarkNet node JSON-RPC related modules.
 pub mod api;
+pub mod health;
 pub mod serde;
 pub mod types;
 
@@ -7,6 +8,7 @@ use crate::{
     core::{ContractAddress, StarknetTransactionHash, StarknetTransactionIndex},
     rpc::{
         api::RpcApi,
+        serde::NumberFormat,
         types::{
             request::OverflowingStorageAddress,
             request::{BlockResponseScope, Call},
@@ -19,13 +21,52 @@ use jsonrpsee::{
     http_server::{HttpServerBuilder, HttpServerHandle, RpcModule},
     types::Error,
 };
-use std::{net::SocketAddr, result::Result};
+use std::{net::SocketAddr, result::Result, time::Duration};
+
+/// The request size and time limits of the HTTP-RPC server.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Limits {
+    /// The maximum size of a request body in bytes, larger requests are rejected.
+    pub max_request_bytes: u32,
+    /// How long a single method call may take before it is aborted.
+    pub request_timeout: Duration,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_request_bytes: 10 * 1024 * 1024,
+            request_timeout: Duration::from_secs(30),
+        }
+    }
+}
 
-/// Helper wrapper for attaching spans to rpc method implementations
-struct RpcModuleWrapper<Context>(jsonrpsee::RpcModule<Context>);
+/// Helper wrapper for attaching spans and timeouts to rpc method implementations
+struct RpcModuleWrapper<Context> {
+    module: jsonrpsee::RpcModule<Context>,
+    request_timeout: Duration,
+    number_format: NumberFormat,
+}
+
+/// A method reply which is rendered according to the configured [NumberFormat].
+struct Formatted<R> {
+    value: R,
+    format: NumberFormat,
+}
+
+impl<R: ::serde::Serialize> ::serde::Serialize for Formatted<R> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ::serde::Serializer,
+    {
+        self.format.scope(|| self.value.serialize(serializer))
+    }
+}
 
 impl<Context: Send + Sync + 'static> RpcModuleWrapper<Context> {
-    /// This wrapper helper adds a tracing span around all rpc methods with name = method_name.
+    /// This wrapper helper adds a tracing span around all rpc methods with name = method_name,
+    /// aborts calls which take longer than the request timeout and renders replies in the
+    /// configured [NumberFormat].
     ///
     /// It could do more, for example trace the outputs, durations.
     ///
@@ -48,23 +89,50 @@ impl<Context: Send + Sync + 'static> RpcModuleWrapper<Context> {
     {
         use tracing::Instrument;
 
-        self.0.register_async_method(method_name, move |p, c| {
+        let request_timeout = self.request_timeout;
+        let format = self.number_format;
+        self.module.register_async_method(method_name, move |p, c| {
             // why info here? it's the same used in warp tracing filter for example.
             let span = tracing::info_span!("rpc_method", name = method_name);
-            callback(p, c).instrument(span)
+            async move {
+                match tokio::time::timeout(request_timeout, callback(p, c)).await {
+                    Ok(result) => result.map(|value| Formatted { value, format }),
+                    Err(_) => {
+                        tracing::warn!(timeout=?request_timeout, "Request timed out");
+                        Err(Error::RequestTimeout)
+                    }
+                }
+            }
+            .instrument(span)
         })
     }
 
     fn into_inner(self) -> jsonrpsee::RpcModule<Context> {
-        self.0
+        self.module
     }
 }
 
-/// Starts the HTTP-RPC server.
+/// Starts the HTTP-RPC server with the default [Limits].
 pub fn run_server(addr: SocketAddr, api: RpcApi) -> Result<(HttpServerHandle, SocketAddr), Error> {
-    let server = HttpServerBuilder::default().build(addr)?;
+    run_server_with_limits(addr, api, Limits::default())
+}
+
+/// Starts the HTTP-RPC server, rejecting requests which exceed the given [Limits].
+pub fn run_server_with_limits(
+    addr: SocketAddr,
+    api: RpcApi,
+    limits: Limits,
+) -> Result<(HttpServerHandle, SocketAddr), Error> {
+    let server = HttpServerBuilder::default()
+        .max_request_body_size(limits.max_request_bytes)
+        .build(addr)?;
     let local_addr = server.local_addr()?;
-    let mut module = RpcModuleWrapper(RpcModule::new(api));
+    let number_format = api.number_format();
+    let mut module = RpcModuleWrapper {
+        module: RpcModule::new(api),
+        request_timeout: limits.request_timeout,
+        number_format,
+    };
     module.register_async_method("starknet_getBlockByHash", |params, context| async move {
         #[derive(Debug, Deserialize)]
         pub struct NamedArgs {
@@ -226,6 +294,21 @@ pub fn run_server(addr: SocketAddr, api: RpcApi) -> Result<(HttpServerHandle, So
     module.register_async_method("starknet_syncing", |_, context| async move {
         context.syncing().await
     })?;
+    module.register_async_method("pathfinder_health", |_, context| async move {
+        context.health().await
+    })?;
+    module.register_async_method(
+        "pathfinder_computeContractHash",
+        |params, context| async move {
+            #[derive(Debug, Deserialize)]
+            pub struct NamedArgs {
+                pub contract_definition: String,
+            }
+            context
+                .compute_contract_hash(params.parse::<NamedArgs>()?.contract_definition)
+                .await
+        },
+    )?;
     let module = module.into_inner();
     server.start(module).map(|handle| (handle, local_addr))
 }
@@ -1540,9 +1623,7 @@ mod tests {
 
             let storage = Storage::in_memory().unwrap();
 
-            let contract_definition = include_bytes!("../fixtures/contract_definition.json.zst");
-            let buffer = zstd::decode_all(std::io::Cursor::new(contract_definition)).unwrap();
-            let contract_definition = Bytes::from(buffer);
+            let contract_definition = Bytes::from(crate::test_utils::contract_definition_fixture());
 
             {
                 let mut conn = storage.connection().unwrap();
@@ -2069,6 +2150,49 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn decimal_number_format() {
+        use crate::rpc::{
+            serde::NumberFormat,
+            types::reply::{syncing, Syncing},
+        };
+        use num_bigint::BigUint;
+
+        let storage = Storage::in_memory().unwrap();
+        let sequencer = SeqClient::new(Chain::Goerli).unwrap();
+        let sync_state = Arc::new(SyncState::default());
+        *sync_state.status.write().await = Syncing::Status(syncing::Status {
+            starting_block: StarknetBlockHash(StarkHash::from_be_slice(b"starting").unwrap()),
+            current_block: StarknetBlockHash(StarkHash::from_be_slice(b"current").unwrap()),
+            highest_block: StarknetBlockHash(StarkHash::from_be_slice(b"highest").unwrap()),
+        });
+        let api = RpcApi::new(storage, sequencer, Chain::Goerli, sync_state)
+            .with_number_format(NumberFormat::Decimal);
+        let (__handle, addr) = run_server(*LOCALHOST, api).unwrap();
+        let client = client(addr);
+
+        let syncing = client
+            .request::<serde_json::Value>("starknet_syncing", rpc_params!())
+            .await
+            .unwrap();
+        let decimal = |s: &[u8]| BigUint::from_bytes_be(s).to_str_radix(10);
+        assert_eq!(
+            syncing,
+            serde_json::json!({
+                "starting_block": decimal(b"starting"),
+                "current_block": decimal(b"current"),
+                "highest_block": decimal(b"highest"),
+            })
+        );
+
+        // Not a StarkHash based value, so it stays hex.
+        let chain_id = client
+            .request::<String>("starknet_chainId", rpc_params!())
+            .await
+            .unwrap();
+        assert_eq!(chain_id, format!("0x{}", hex::encode("SN_GOERLI")));
+    }
+
     #[tokio::test]
     #[should_panic]
     async fn pending_transactions() {
@@ -2097,6 +2221,66 @@ mod tests {
             .unwrap();
     }
 
+    mod compute_contract_hash {
+        use super::*;
+        use crate::test_utils::contract_definition_fixture;
+        use jsonrpsee::types::error::CallError;
+
+        fn api() -> RpcApi {
+            let storage = Storage::in_memory().unwrap();
+            let sequencer = SeqClient::new(Chain::Goerli).unwrap();
+            let sync_state = Arc::new(SyncState::default());
+            RpcApi::new(storage, sequencer, Chain::Goerli, sync_state)
+        }
+
+        #[tokio::test]
+        async fn hex_and_base64() {
+            let contract_definition = contract_definition_fixture();
+            let expected = crate::state::compute_contract_hash(&contract_definition).unwrap();
+
+            let hex = format!("0x{}", hex::encode(&contract_definition));
+            assert_eq!(api().compute_contract_hash(hex).await.unwrap(), expected);
+
+            let base64 = base64::encode(&contract_definition);
+            assert_eq!(api().compute_contract_hash(base64).await.unwrap(), expected);
+        }
+
+        #[tokio::test]
+        async fn invalid_encoding() {
+            let error = api()
+                .compute_contract_hash("0xnot hex".to_owned())
+                .await
+                .unwrap_err();
+            assert_matches!(error, Error::Call(CallError::InvalidParams(_)));
+        }
+
+        #[tokio::test]
+        async fn invalid_contract_definition() {
+            let error = api()
+                .compute_contract_hash(base64::encode(b"{}"))
+                .await
+                .unwrap_err();
+            assert_matches!(error, Error::Call(CallError::InvalidParams(_)));
+        }
+
+        #[tokio::test]
+        async fn over_rpc() {
+            let contract_definition = contract_definition_fixture();
+            let expected = crate::state::compute_contract_hash(&contract_definition).unwrap();
+
+            let (__handle, addr) = run_server(*LOCALHOST, api()).unwrap();
+            let params = by_name([(
+                "contract_definition",
+                json!(base64::encode(&contract_definition)),
+            )]);
+            let hash = client(addr)
+                .request::<ContractHash>("pathfinder_computeContractHash", params)
+                .await
+                .unwrap();
+            assert_eq!(hash, expected);
+        }
+    }
+
     mod syncing {
         use crate::rpc::types::reply::{syncing, Syncing};
         use pretty_assertions::assert_eq;
@@ -2,15 +2,19 @@
 use crate::{
     cairo::ext_py,
     core::{
-        CallResultValue, ContractAddress, ContractCode, GlobalRoot, StarknetBlockHash,
-        StarknetBlockNumber, StarknetBlockTimestamp, StarknetTransactionHash,
+        CallResultValue, ContractAddress, ContractCode, ContractHash, GlobalRoot,
+        StarknetBlockHash, StarknetBlockNumber, StarknetBlockTimestamp, StarknetTransactionHash,
         StarknetTransactionIndex, StorageValue,
     },
     ethereum::Chain,
-    rpc::types::{
-        reply::{Block, BlockStatus, ErrorCode, Syncing, Transaction, TransactionReceipt},
-        request::{BlockResponseScope, Call, OverflowingStorageAddress},
-        BlockHashOrTag, BlockNumberOrTag, Tag,
+    rpc::{
+        health::{health_status, HealthReport},
+        serde::NumberFormat,
+        types::{
+            reply::{Block, BlockStatus, ErrorCode, Syncing, Transaction, TransactionReceipt},
+            request::{BlockResponseScope, Call, OverflowingStorageAddress},
+            BlockHashOrTag, BlockNumberOrTag, Tag,
+        },
     },
     sequencer::{
         self,
@@ -29,6 +33,7 @@ use jsonrpsee::types::{
 use pedersen::StarkHash;
 use std::convert::TryInto;
 use std::sync::Arc;
+use web3::{transports::Http, Web3};
 
 /// Implements JSON-RPC endpoints.
 pub struct RpcApi {
@@ -36,7 +41,9 @@ pub struct RpcApi {
     sequencer: sequencer::Client,
     chain: Chain,
     call_handle: Option<ext_py::Handle>,
+    eth_transport: Option<Web3<Http>>,
     sync_state: Arc<SyncState>,
+    number_format: NumberFormat,
 }
 
 #[derive(Debug)]
@@ -63,7 +70,9 @@ impl RpcApi {
             sequencer,
             chain,
             call_handle: None,
+            eth_transport: None,
             sync_state,
+            number_format: NumberFormat::default(),
         }
     }
 
@@ -74,6 +83,27 @@ impl RpcApi {
         }
     }
 
+    /// Checks the Ethereum endpoint behind `eth_transport` in [RpcApi::health].
+    pub fn with_eth_transport(self, eth_transport: Web3<Http>) -> Self {
+        Self {
+            eth_transport: Some(eth_transport),
+            ..self
+        }
+    }
+
+    /// Renders [StarkHash] based values in replies using `number_format`.
+    pub fn with_number_format(self, number_format: NumberFormat) -> Self {
+        Self {
+            number_format,
+            ..self
+        }
+    }
+
+    /// The [NumberFormat] replies of this API are rendered in.
+    pub fn number_format(&self) -> NumberFormat {
+        self.number_format
+    }
+
     /// Get block information given the block hash.
     /// `block_hash` is the [Hash](crate::rpc::types::BlockHashOrTag::Hash) or [Tag](crate::rpc::types::BlockHashOrTag::Tag)
     /// of the requested block.
@@ -907,6 +937,46 @@ impl RpcApi {
         let value = { self.sync_state.status.read().await.clone() };
         Ok(value)
     }
+
+    /// Returns the [HealthReport] of the node, see [health_status].
+    ///
+    /// This is not part of the StarkNet spec, and fails unless an Ethereum transport was set with
+    /// [RpcApi::with_eth_transport].
+    pub async fn health(&self) -> RpcResult<HealthReport> {
+        let eth_transport = self
+            .eth_transport
+            .as_ref()
+            .ok_or_else(|| internal_server_error("Ethereum transport is not configured"))?;
+
+        health_status(&self.storage, eth_transport, &self.sequencer)
+            .await
+            .map_err(internal_server_error)
+    }
+
+    /// Computes the contract hash of `contract_definition`, which is either a `0x` prefixed hex
+    /// or a base64 encoded contract definition json blob.
+    ///
+    /// This is not part of the StarkNet spec. The size of the blob is bounded by the request size
+    /// limit of the server.
+    pub async fn compute_contract_hash(
+        &self,
+        contract_definition: String,
+    ) -> RpcResult<ContractHash> {
+        let contract_definition = decode_contract_definition(&contract_definition)
+            .map_err(|e| Error::Call(CallError::InvalidParams(e)))?;
+
+        crate::state::compute_contract_hash_async(contract_definition.into())
+            .await
+            .map_err(|e| Error::Call(CallError::InvalidParams(e)))
+    }
+}
+
+/// Decodes a `0x` prefixed hex or a base64 encoded blob.
+fn decode_contract_definition(encoded: &str) -> anyhow::Result<Vec<u8>> {
+    match encoded.strip_prefix("0x") {
+        Some(hex) => hex::decode(hex).context("Decoding hex contract definition"),
+        None => base64::decode(encoded).context("Decoding base64 contract definition"),
+    }
 }
 
 impl From<ext_py::CallFailure> for jsonrpsee::types::Error {
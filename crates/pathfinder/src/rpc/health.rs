@@ -0,0 +1,183 @@
+//! Node health reporting, intended for liveness checks of the HTTP-RPC server.
+use std::time::Duration;
+
+use anyhow::Context;
+use serde::Serialize;
+use web3::{Transport, Web3};
+
+use crate::{
+    core::StarknetBlockNumber,
+    sequencer,
+    storage::{L1StateTable, L1TableBlockId, StarknetBlocksTable, Storage},
+};
+
+/// How long an endpoint may take to respond before it is considered unreachable.
+const CONNECTIVITY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A snapshot of the node's sync progress and endpoint connectivity.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HealthReport {
+    /// The latest StarkNet state update synced from L1, if any.
+    pub l1: Option<L1Head>,
+    /// The latest StarkNet block synced from the sequencer, if any.
+    pub starknet_block_number: Option<StarknetBlockNumber>,
+    /// Whether the Ethereum endpoint responded.
+    pub ethereum_reachable: bool,
+    /// Whether the StarkNet sequencer responded.
+    pub sequencer_reachable: bool,
+}
+
+/// The latest StarkNet state update synced from L1.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct L1Head {
+    pub starknet_block_number: StarknetBlockNumber,
+    /// The Ethereum block containing the state update.
+    pub ethereum_block_number: u64,
+}
+
+impl HealthReport {
+    /// The node is healthy if all of its endpoints are reachable.
+    pub fn is_healthy(&self) -> bool {
+        self.ethereum_reachable && self.sequencer_reachable
+    }
+}
+
+/// Gathers a [HealthReport] from storage and by querying the Ethereum and sequencer endpoints.
+///
+/// Endpoints which fail or take longer than 5 seconds to respond are reported as unreachable.
+pub async fn health_status<T: Transport>(
+    storage: &Storage,
+    transport: &Web3<T>,
+    sequencer: &sequencer::Client,
+) -> anyhow::Result<HealthReport> {
+    let storage = storage.clone();
+    let (l1, starknet_block_number) = tokio::task::spawn_blocking(move || {
+        let connection = storage
+            .connection()
+            .context("Opening database connection")?;
+
+        let l1 = L1StateTable::get(&connection, L1TableBlockId::Latest)
+            .context("Reading latest L1 state update")?
+            .map(|update| L1Head {
+                starknet_block_number: update.block_number,
+                ethereum_block_number: update.origin.block.number.0,
+            });
+        let starknet_block_number = StarknetBlocksTable::get_latest_number(&connection)
+            .context("Reading latest StarkNet block number")?;
+
+        Ok::<_, anyhow::Error>((l1, starknet_block_number))
+    })
+    .await
+    .context("Database query task")??;
+
+    let ethereum_reachable = matches!(
+        tokio::time::timeout(CONNECTIVITY_TIMEOUT, transport.eth().block_number()).await,
+        Ok(Ok(_))
+    );
+    let sequencer_reachable = matches!(
+        tokio::time::timeout(CONNECTIVITY_TIMEOUT, sequencer.eth_contract_addresses()).await,
+        Ok(Ok(_))
+    );
+
+    Ok(HealthReport {
+        l1,
+        starknet_block_number,
+        ethereum_reachable,
+        sequencer_reachable,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serialization() {
+        let report = HealthReport {
+            l1: Some(L1Head {
+                starknet_block_number: StarknetBlockNumber(10),
+                ethereum_block_number: 6_000_000,
+            }),
+            starknet_block_number: Some(StarknetBlockNumber(12)),
+            ethereum_reachable: true,
+            sequencer_reachable: false,
+        };
+
+        assert!(!report.is_healthy());
+        assert_eq!(
+            serde_json::to_value(&report).unwrap(),
+            serde_json::json!({
+                "l1": {
+                    "starknet_block_number": 10,
+                    "ethereum_block_number": 6_000_000,
+                },
+                "starknet_block_number": 12,
+                "ethereum_reachable": true,
+                "sequencer_reachable": false,
+            })
+        );
+    }
+
+    /// Serves `eth_blockNumber`, the only Ethereum request of [health_status].
+    fn mock_ethereum() -> Web3<web3::transports::Http> {
+        crate::test_utils::mock_json_rpc(|request| {
+            assert_eq!(request["method"], "eth_blockNumber");
+            Ok(serde_json::json!("0x5b8d80"))
+        })
+    }
+
+    fn mock_sequencer() -> sequencer::Client {
+        use crate::sequencer::mock::MockGateway;
+
+        let url = MockGateway::default()
+            .with_contract_addresses(
+                r#"{
+                    "Starknet": "0xde29d060D45901Fb19ED6C6e959EB22d8626708e",
+                    "GpsStatementVerifier": "0xab43ba48c9edf4c2c4bb01237348d1d7b28ef168"
+                }"#,
+            )
+            .start();
+        sequencer::Client::builder(crate::ethereum::Chain::Goerli)
+            .url(url)
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn empty_storage() {
+        let storage = Storage::in_memory().unwrap();
+
+        let report = health_status(&storage, &mock_ethereum(), &mock_sequencer())
+            .await
+            .unwrap();
+        assert_eq!(report.l1, None);
+        assert_eq!(report.starknet_block_number, None);
+        assert!(report.is_healthy());
+    }
+
+    #[tokio::test]
+    async fn over_rpc() {
+        use crate::{
+            ethereum::Chain,
+            rpc::{api::RpcApi, run_server},
+            state::SyncState,
+        };
+        use jsonrpsee::{http_client::HttpClientBuilder, rpc_params, types::traits::Client};
+        use std::sync::Arc;
+
+        let storage = Storage::in_memory().unwrap();
+        let sync_state = Arc::new(SyncState::default());
+        let api = RpcApi::new(storage, mock_sequencer(), Chain::Goerli, sync_state)
+            .with_eth_transport(mock_ethereum());
+        let (_handle, addr) = run_server(([127, 0, 0, 1], 0).into(), api).unwrap();
+
+        let report = HttpClientBuilder::default()
+            .build(format!("http://{}", addr))
+            .unwrap()
+            .request::<serde_json::Value>("pathfinder_health", rpc_params!())
+            .await
+            .unwrap();
+        assert_eq!(report["ethereum_reachable"], true);
+        assert_eq!(report["sequencer_reachable"], true);
+    }
+}
@@ -8,7 +8,7 @@ use num_bigint::BigUint;
 use pedersen::{HexParseError, OverflowError, StarkHash};
 use serde_with::serde_conv;
 use std::str::FromStr;
-use web3::types::{H160, H256};
+use web3::types::H160;
 
 serde_conv!(
     pub CallParamAsDecimalStr,
@@ -73,12 +73,70 @@ serde_with::serde_conv!(
     |s: &str| bytes_from_hex_str::<{ H160::len_bytes() }>(s).map(|b| EthereumAddress(H160::from(b)))
 );
 
-serde_with::serde_conv!(
-    pub H256AsNoLeadingZerosHexStr,
-    H256,
-    |serialize_me: &H256| bytes_to_hex_str(serialize_me.as_bytes()),
-    |s: &str| bytes_from_hex_str::<{ H256::len_bytes() }>(s).map(H256::from)
-);
+/// Serializes values as "0x" prefixed hex strings without leading zeros, for use with
+/// `#[serde(with = "crate::rpc::serde::serde_hex")]`.
+///
+/// Deserialization accepts both the fixed-width (zero padded) and the variable-width form, with
+/// or without the "0x" prefix, so fields round-trip regardless of which one the source used.
+pub mod serde_hex {
+    use super::{bytes_from_hex_str, bytes_to_hex_str};
+    use pedersen::{HexParseError, StarkHash};
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+    use web3::types::{H160, H256};
+
+    /// Types which can be (de)serialized by [serde_hex](self).
+    pub trait Hex: Sized {
+        fn to_hex(&self) -> String;
+        fn from_hex(hex_str: &str) -> Result<Self, HexParseError>;
+    }
+
+    impl Hex for StarkHash {
+        fn to_hex(&self) -> String {
+            self.to_hex_str()
+        }
+
+        fn from_hex(hex_str: &str) -> Result<Self, HexParseError> {
+            StarkHash::from_hex_str(hex_str)
+        }
+    }
+
+    impl Hex for H256 {
+        fn to_hex(&self) -> String {
+            bytes_to_hex_str(self.as_bytes())
+        }
+
+        fn from_hex(hex_str: &str) -> Result<Self, HexParseError> {
+            bytes_from_hex_str::<{ H256::len_bytes() }>(hex_str).map(H256::from)
+        }
+    }
+
+    impl Hex for H160 {
+        fn to_hex(&self) -> String {
+            bytes_to_hex_str(self.as_bytes())
+        }
+
+        fn from_hex(hex_str: &str) -> Result<Self, HexParseError> {
+            bytes_from_hex_str::<{ H160::len_bytes() }>(hex_str).map(H160::from)
+        }
+    }
+
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Hex,
+        S: Serializer,
+    {
+        serializer.serialize_str(&value.to_hex())
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: Hex,
+        D: Deserializer<'de>,
+    {
+        let hex_str = String::deserialize(deserializer)?;
+        T::from_hex(&hex_str).map_err(D::Error::custom)
+    }
+}
 
 /// A helper conversion function. Only use with __sequencer API related types__.
 fn starkhash_from_biguint(b: BigUint) -> Result<StarkHash, OverflowError> {
@@ -168,6 +226,54 @@ pub(crate) fn bytes_to_hex_str(bytes: &[u8]) -> String {
     String::from_utf8(buf).unwrap()
 }
 
+/// Controls how the HTTP-RPC server renders [StarkHash] based values in its replies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumberFormat {
+    /// "0x" prefixed hex strings, as required by the specification.
+    Hex,
+    /// Decimal strings, as used by the sequencer for call data.
+    Decimal,
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        NumberFormat::Hex
+    }
+}
+
+impl FromStr for NumberFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hex" => Ok(NumberFormat::Hex),
+            "decimal" => Ok(NumberFormat::Decimal),
+            other => Err(format!("expected one of hex or decimal, got '{}'", other)),
+        }
+    }
+}
+
+impl std::fmt::Display for NumberFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NumberFormat::Hex => f.write_str("hex"),
+            NumberFormat::Decimal => f.write_str("decimal"),
+        }
+    }
+}
+
+impl NumberFormat {
+    /// Runs `f` with every [StarkHash] based value serialized within it rendered in this
+    /// format. Values which are not [StarkHash] based, such as Ethereum addresses and
+    /// hashes, are not affected.
+    pub fn scope<R>(&self, f: impl FnOnce() -> R) -> R {
+        match self {
+            NumberFormat::Hex => f(),
+            NumberFormat::Decimal => pedersen::serde::with_decimal_serialization(f),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -301,6 +407,86 @@ mod tests {
         );
     }
 
+    mod serde_hex {
+        use pedersen::{HexParseError, StarkHash};
+        use pretty_assertions::assert_eq;
+        use serde::{Deserialize, Serialize};
+        use web3::types::H256;
+
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        struct Fields {
+            #[serde(with = "crate::rpc::serde::serde_hex")]
+            hash: StarkHash,
+            #[serde(with = "crate::rpc::serde::serde_hex")]
+            tx: H256,
+        }
+
+        #[test]
+        fn round_trip() {
+            let fields = Fields {
+                hash: StarkHash::from_hex_str("0x1234567890abcdef").unwrap(),
+                tx: H256::from_low_u64_be(0xabc),
+            };
+
+            let json = serde_json::to_value(&fields).unwrap();
+            assert_eq!(
+                json,
+                serde_json::json!({"hash": "0x1234567890abcdef", "tx": "0xabc"})
+            );
+            assert_eq!(serde_json::from_value::<Fields>(json).unwrap(), fields);
+        }
+
+        #[test]
+        fn fixed_and_variable_width() {
+            let expected = Fields {
+                hash: StarkHash::from_hex_str("0xabc").unwrap(),
+                tx: H256::from_low_u64_be(0xabc),
+            };
+            let padded = "0x0000000000000000000000000000000000000000000000000000000000000abc";
+
+            for (hash, tx) in [
+                ("0xabc", "0xabc"),
+                ("abc", "ABC"),
+                (padded, padded),
+                (&padded[2..], &padded[2..]),
+            ] {
+                let fields: Fields =
+                    serde_json::from_value(serde_json::json!({"hash": hash, "tx": tx})).unwrap();
+                assert_eq!(fields, expected);
+            }
+        }
+
+        #[test]
+        fn zero() {
+            let fields = Fields {
+                hash: StarkHash::ZERO,
+                tx: H256::zero(),
+            };
+
+            let json = serde_json::to_value(&fields).unwrap();
+            assert_eq!(json, serde_json::json!({"hash": "0x0", "tx": "0x0"}));
+        }
+
+        #[test]
+        fn invalid() {
+            let overflow = "0x800000000000011000000000000000000000000000000000000000000000001";
+            for (hash, expected) in [
+                (overflow, HexParseError::Overflow),
+                ("0x12z", HexParseError::InvalidNibble(b'z')),
+            ] {
+                let error = serde_json::from_value::<Fields>(
+                    serde_json::json!({"hash": hash, "tx": "0x0"}),
+                )
+                .unwrap_err();
+                assert!(
+                    error.to_string().starts_with(&expected.to_string()),
+                    "{}",
+                    error
+                );
+            }
+        }
+    }
+
     #[test]
     fn invalid_digit() {
         use num_bigint::ParseBigIntError;
@@ -313,4 +499,72 @@ mod tests {
             Err(HexParseError::InvalidNibble(b'z'))
         );
     }
+
+    mod number_format {
+        use super::*;
+        use serde_json::json;
+
+        use crate::core::{EthereumAddress, StarknetBlockHash};
+        use serde_with::serde_as;
+        use web3::types::{H160, H256};
+
+        #[serde_as]
+        #[derive(serde::Serialize)]
+        struct Reply {
+            block_hash: StarknetBlockHash,
+            values: Vec<StarkHash>,
+            #[serde_as(as = "EthereumAddressAsHexStr")]
+            address: EthereumAddress,
+            tx_hash: H256,
+            status: &'static str,
+        }
+
+        fn reply() -> Reply {
+            Reply {
+                block_hash: StarknetBlockHash(StarkHash::from_hex_str("0x1234").unwrap()),
+                values: vec![StarkHash::ZERO, StarkHash::from_hex_str("0xa").unwrap()],
+                address: EthereumAddress(H160::from_low_u64_be(0x1234)),
+                tx_hash: H256::from_low_u64_be(0x1234),
+                status: "0x1234",
+            }
+        }
+
+        #[test]
+        fn hex_leaves_reply_unchanged() {
+            let expected = serde_json::to_value(reply()).unwrap();
+            let value = NumberFormat::Hex.scope(|| serde_json::to_value(reply()).unwrap());
+            assert_eq!(value, expected);
+        }
+
+        #[test]
+        fn decimal_only_renders_stark_hashes() {
+            let value = NumberFormat::Decimal.scope(|| serde_json::to_value(reply()).unwrap());
+            assert_eq!(
+                value,
+                json!({
+                    "block_hash": "4660",
+                    "values": ["0", "10"],
+                    "address": "0x1234",
+                    "tx_hash": format!("0x{:064x}", 0x1234),
+                    "status": "0x1234",
+                })
+            );
+
+            // Replies serialized afterwards are back to hex.
+            let value = serde_json::to_value(reply()).unwrap();
+            assert_eq!(value["block_hash"], json!("0x1234"));
+        }
+
+        #[test]
+        fn from_str_and_display() {
+            for format in [NumberFormat::Hex, NumberFormat::Decimal] {
+                assert_eq!(format.to_string().parse::<NumberFormat>(), Ok(format));
+            }
+            assert_eq!(NumberFormat::default(), NumberFormat::Hex);
+            assert_eq!(
+                "octal".parse::<NumberFormat>(),
+                Err("expected one of hex or decimal, got 'octal'".to_owned())
+            );
+        }
+    }
 }
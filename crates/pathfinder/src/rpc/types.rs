@@ -50,21 +50,16 @@ pub enum BlockNumberOrTag {
 
 /// Groups all strictly input types of the RPC API.
 pub mod request {
-    use crate::{
-        core::{CallParam, ContractAddress, EntryPoint},
-        rpc::serde::H256AsNoLeadingZerosHexStr,
-    };
+    use crate::core::{CallParam, ContractAddress, EntryPoint};
     use serde::{Deserialize, Serialize};
-    use serde_with::serde_as;
     use web3::types::H256;
 
     /// The address of a storage element for a StarkNet contract.
     ///
     /// __This type is not checked for 251 bits overflow__ in contrast to
     /// [`StarkHash`](pedersen::StarkHash).
-    #[serde_as]
     #[derive(Debug, Copy, Clone, PartialEq, Deserialize, Serialize)]
-    pub struct OverflowingStorageAddress(#[serde_as(as = "H256AsNoLeadingZerosHexStr")] pub H256);
+    pub struct OverflowingStorageAddress(#[serde(with = "crate::rpc::serde::serde_hex")] pub H256);
 
     /// Contains parameters passed to `starknet_call`.
     #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
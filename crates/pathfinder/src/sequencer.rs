@@ -1,18 +1,136 @@
 //! StarkNet L2 sequencer client.
 pub mod error;
+#[cfg(test)]
+pub mod mock;
 pub mod reply;
 pub mod request;
 
 use self::error::StarknetError;
 use crate::{
-    core::{ContractAddress, StarknetTransactionHash, StorageAddress, StorageValue},
+    config::CaBundle,
+    core::{
+        ContractAddress, StarknetBlockHash, StarknetBlockNumber, StarknetTransactionHash,
+        StorageAddress, StorageValue,
+    },
     ethereum::Chain,
+    retry::RetryBudget,
     rpc::types::{BlockHashOrTag, BlockNumberOrTag, Tag},
     sequencer::error::SequencerError,
 };
 use reqwest::Url;
 use std::{borrow::Cow, fmt::Debug, future::Future, result::Result, time::Duration};
 
+/// The default timeout of a single sequencer request, which needs to accommodate the ~500kB
+/// replies of `get_full_contract`.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Connection pool settings of the sequencer [Client].
+///
+/// All requests of a [Client] (and its clones) share a single connection pool, so that syncing
+/// many small blocks reuses the same connections instead of opening new ones.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PoolConfig {
+    /// The maximum number of idle connections kept open per host.
+    pub max_idle_per_host: usize,
+    /// How long an idle connection is kept alive in the pool.
+    pub idle_timeout: Duration,
+}
+
+impl Default for PoolConfig {
+    /// The defaults of [reqwest].
+    fn default() -> Self {
+        Self {
+            max_idle_per_host: usize::MAX,
+            idle_timeout: Duration::from_secs(90),
+        }
+    }
+}
+
+/// The URL of the sequencer of `chain`.
+fn chain_url(chain: Chain) -> Url {
+    match chain {
+        Chain::Mainnet => Url::parse("https://alpha-mainnet.starknet.io/").unwrap(),
+        Chain::Goerli => Url::parse("https://alpha4.starknet.io/").unwrap(),
+    }
+}
+
+/// Builds a sequencer [Client], see [Client::builder].
+#[derive(Debug, Clone)]
+pub struct ClientBuilder {
+    url: Url,
+    timeout: Duration,
+    pool: PoolConfig,
+    ca_bundle: Option<CaBundle>,
+    http_client: Option<reqwest::Client>,
+    retry_budget: Option<RetryBudget>,
+}
+
+impl ClientBuilder {
+    /// Connects to the sequencer at `url` instead of the one of the chain.
+    pub fn url(mut self, url: Url) -> Self {
+        self.url = url;
+        self
+    }
+
+    /// Sets the timeout of a single request, the [DEFAULT_TIMEOUT] by default.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets the connection pool settings, the [PoolConfig] defaults by default.
+    pub fn pool(mut self, pool: PoolConfig) -> Self {
+        self.pool = pool;
+        self
+    }
+
+    /// Trusts only the root certificates of `ca_bundle`, instead of the built-in ones.
+    pub fn ca_bundle(mut self, ca_bundle: &CaBundle) -> Self {
+        self.ca_bundle = Some(ca_bundle.clone());
+        self
+    }
+
+    /// Sends the requests using `http_client`, for example one configured with a proxy.
+    ///
+    /// The timeout, connection pool and CA bundle settings are then ignored, and entirely up to
+    /// the `http_client`.
+    pub fn http_client(mut self, http_client: reqwest::Client) -> Self {
+        self.http_client = Some(http_client);
+        self
+    }
+
+    /// Draws the retries of all requests from `budget`, which can be shared with other clients.
+    /// Retries are only limited per request by default.
+    pub fn retry_budget(mut self, budget: RetryBudget) -> Self {
+        self.retry_budget = Some(budget);
+        self
+    }
+
+    /// Builds the [Client].
+    pub fn build(self) -> reqwest::Result<Client> {
+        let inner = match self.http_client {
+            Some(http_client) => http_client,
+            None => {
+                let builder = reqwest::Client::builder()
+                    .timeout(self.timeout)
+                    .pool_max_idle_per_host(self.pool.max_idle_per_host)
+                    .pool_idle_timeout(self.pool.idle_timeout);
+                match &self.ca_bundle {
+                    Some(ca_bundle) => ca_bundle.apply(builder),
+                    None => builder,
+                }
+                .build()?
+            }
+        };
+
+        Ok(Client {
+            inner,
+            sequencer_url: self.url,
+            retry_budget: self.retry_budget,
+        })
+    }
+}
+
 /// StarkNet sequencer client using REST API.
 #[derive(Debug, Clone)]
 pub struct Client {
@@ -20,6 +138,8 @@ pub struct Client {
     inner: reqwest::Client,
     /// StarkNet sequencer URL.
     sequencer_url: Url,
+    /// The budget retries are drawn from, if any.
+    retry_budget: Option<RetryBudget>,
 }
 
 /// Helper function which simplifies the handling of optional block hashes in queries.
@@ -48,7 +168,50 @@ where
 {
     let resp = parse_raw(resp).await?;
     // Attempt to deserialize the actual data we are looking for
-    let resp = resp.json::<T>().await?;
+    let body = read_body(resp).await?;
+    parse_body(&body)
+}
+
+/// Reads the whole reply body, checking it against the `Content-Length` header where one is
+/// present. A body which is shorter (or longer) than declared results in
+/// [SequencerError::Truncated] instead of a transport or deserialization error.
+async fn read_body(mut resp: reqwest::Response) -> Result<bytes::Bytes, SequencerError> {
+    let expected = resp.content_length();
+    let mut body = Vec::new();
+
+    loop {
+        match resp.chunk().await {
+            Ok(Some(chunk)) => body.extend_from_slice(&chunk),
+            Ok(None) => break,
+            Err(e) => {
+                return Err(match expected {
+                    Some(expected) if (body.len() as u64) < expected => SequencerError::Truncated {
+                        expected,
+                        received: body.len() as u64,
+                    },
+                    _ => e.into(),
+                })
+            }
+        }
+    }
+
+    match expected {
+        Some(expected) if body.len() as u64 != expected => Err(SequencerError::Truncated {
+            expected,
+            received: body.len() as u64,
+        }),
+        _ => Ok(body.into()),
+    }
+}
+
+/// Deserializes a reply body, rejecting bodies which are not valid UTF-8 text with
+/// [SequencerError::InvalidResponse].
+fn parse_body<T>(body: &[u8]) -> Result<T, SequencerError>
+where
+    T: ::serde::de::DeserializeOwned,
+{
+    let body = std::str::from_utf8(body)?;
+    let resp = serde_json::from_str::<T>(body)?;
     Ok(resp)
 }
 
@@ -57,7 +220,8 @@ async fn parse_raw(resp: reqwest::Response) -> Result<reqwest::Response, Sequenc
     // Starknet specific errors end with a 500 status code
     // but the body contains a JSON object with the error description
     if resp.status() == reqwest::StatusCode::INTERNAL_SERVER_ERROR {
-        let starknet_error = resp.json::<StarknetError>().await?;
+        let body = read_body(resp).await?;
+        let starknet_error = parse_body::<StarknetError>(&body)?;
         return Err(SequencerError::StarknetError(starknet_error));
     }
     // Status codes <400;499> and <501;599> are mapped to SequencerError::TransportError
@@ -67,54 +231,100 @@ async fn parse_raw(resp: reqwest::Response) -> Result<reqwest::Response, Sequenc
 
 /// Wrapper function to allow retrying sequencer queries in an exponential manner.
 ///
-/// Initial backoff time is 2 seconds. Retrying stops after approximately 4 minutes in total.
-async fn retry<T, Fut, FutureFactory>(future_factory: FutureFactory) -> Result<T, SequencerError>
+/// Initial backoff time is 2 seconds. Retrying stops after approximately 4 minutes in total, or
+/// once `budget` is exhausted.
+async fn retry<T, Fut, FutureFactory>(
+    budget: Option<&RetryBudget>,
+    future_factory: FutureFactory,
+) -> Result<T, SequencerError>
 where
     Fut: Future<Output = Result<T, SequencerError>>,
     FutureFactory: FnMut() -> Fut,
 {
-    use crate::retry::Retry;
-    use reqwest::StatusCode;
+    use crate::retry::RetryPolicy;
     use std::num::{NonZeroU64, NonZeroUsize};
 
-    Retry::exponential(future_factory, NonZeroU64::new(2).unwrap())
+    let policy = RetryPolicy::exponential(NonZeroU64::new(2).unwrap())
         // Max number of retries of 7 gives a total accumulated timeout of 4 minutes and 15 seconds (2^8-1)
-        .max_num_retries(NonZeroUsize::new(7).unwrap())
-        .when(|e| match e {
-            SequencerError::TransportError(te) if te.is_timeout() => {
-                tracing::debug!("Retrying due to timeout");
-                true
+        .max_num_retries(NonZeroUsize::new(7).unwrap());
+    let policy = match budget {
+        Some(budget) => policy.budget(budget.clone()),
+        None => policy,
+    };
+
+    crate::retry::retry(&policy, classify, future_factory).await
+}
+
+/// Timeouts, truncated replies and overloaded gateway responses are transient, anything else is
+/// permanent.
+fn classify(e: &SequencerError) -> crate::retry::Retriable {
+    use crate::retry::Retriable;
+    use reqwest::StatusCode;
+
+    match e {
+        SequencerError::Timeout(_) => {
+            tracing::debug!("Retrying due to timeout");
+            Retriable::Transient
+        }
+        SequencerError::TransportError(_) => match e.http_status() {
+            Some(
+                status @ (StatusCode::TOO_MANY_REQUESTS
+                | StatusCode::BAD_GATEWAY
+                | StatusCode::SERVICE_UNAVAILABLE
+                | StatusCode::GATEWAY_TIMEOUT),
+            ) => {
+                tracing::debug!("Retrying due to {status}");
+                Retriable::Transient
             }
-            SequencerError::TransportError(te) => match te.status() {
-                Some(
-                    status @ (StatusCode::TOO_MANY_REQUESTS
-                    | StatusCode::BAD_GATEWAY
-                    | StatusCode::SERVICE_UNAVAILABLE
-                    | StatusCode::GATEWAY_TIMEOUT),
-                ) => {
-                    tracing::debug!("Retrying due to {status}");
-                    true
-                }
-                Some(_) | None => false,
-            },
-            _ => false,
-        })
-        .await
+            Some(_) | None => Retriable::Permanent,
+        },
+        SequencerError::Truncated { .. } => {
+            tracing::debug!("Retrying due to {e}");
+            Retriable::Transient
+        }
+        _ => Retriable::Permanent,
+    }
+}
+
+/// Records the duration of a single sequencer request in the `sequencer_request_duration_seconds`
+/// histogram, labeled by the `endpoint` and the `outcome` (`ok` or `error`) of the request.
+async fn record_duration<T, Fut>(endpoint: &'static str, request: Fut) -> Result<T, SequencerError>
+where
+    Fut: Future<Output = Result<T, SequencerError>>,
+{
+    let start = std::time::Instant::now();
+    let result = request.await;
+    let outcome = match result {
+        Ok(_) => "ok",
+        Err(_) => "error",
+    };
+    metrics::histogram!(
+        "sequencer_request_duration_seconds",
+        start.elapsed().as_secs_f64(),
+        "endpoint" => endpoint,
+        "outcome" => outcome
+    );
+    result
 }
 
 impl Client {
-    /// Creates a new Sequencer client for the given chain.
+    /// Creates a new Sequencer client for the given chain, using the [DEFAULT_TIMEOUT].
     pub fn new(chain: Chain) -> reqwest::Result<Self> {
-        let sequencer_url = match chain {
-            Chain::Mainnet => Url::parse("https://alpha-mainnet.starknet.io/").unwrap(),
-            Chain::Goerli => Url::parse("https://alpha4.starknet.io/").unwrap(),
-        };
-        Ok(Self {
-            inner: reqwest::Client::builder()
-                .timeout(Duration::from_secs(120))
-                .build()?,
-            sequencer_url,
-        })
+        Self::builder(chain).build()
+    }
+
+    /// Returns a [ClientBuilder] for a client of the sequencer of `chain`, which uses the
+    /// [DEFAULT_TIMEOUT], the default [PoolConfig] and the built-in root certificates unless
+    /// configured otherwise.
+    pub fn builder(chain: Chain) -> ClientBuilder {
+        ClientBuilder {
+            url: chain_url(chain),
+            timeout: DEFAULT_TIMEOUT,
+            pool: PoolConfig::default(),
+            ca_bundle: None,
+            http_client: None,
+            retry_budget: None,
+        }
     }
 
     /// Gets block by number.
@@ -124,13 +334,31 @@ impl Client {
         block_number: BlockNumberOrTag,
     ) -> Result<reply::Block, SequencerError> {
         let number = block_number_str(block_number);
-        retry(|| async {
-            let resp = self
-                .inner
-                .get(self.build_query("get_block", &[("blockNumber", &number)]))
-                .send()
-                .await?;
-            parse::<reply::Block>(resp).await
+        retry(self.retry_budget.as_ref(), || {
+            record_duration("get_block", async {
+                let resp = self
+                    .inner
+                    .get(self.build_query("get_block", &[("blockNumber", &number)]))
+                    .send()
+                    .await?;
+                parse::<reply::Block>(resp).await
+            })
+        })
+        .await
+    }
+
+    /// Gets the pending block, which holds the transactions accepted since the latest block.
+    #[tracing::instrument(skip(self))]
+    pub async fn pending_block(&self) -> Result<reply::PendingBlock, SequencerError> {
+        retry(self.retry_budget.as_ref(), || {
+            record_duration("get_block", async {
+                let resp = self
+                    .inner
+                    .get(self.build_query("get_block", &[("blockNumber", "pending")]))
+                    .send()
+                    .await?;
+                parse::<reply::PendingBlock>(resp).await
+            })
         })
         .await
     }
@@ -142,13 +370,35 @@ impl Client {
         block_hash: BlockHashOrTag,
     ) -> Result<reply::Block, SequencerError> {
         let (tag, hash) = block_hash_str(block_hash);
-        retry(|| async {
-            let resp = self
-                .inner
-                .get(self.build_query("get_block", &[(tag, &hash)]))
-                .send()
-                .await?;
-            parse::<reply::Block>(resp).await
+        retry(self.retry_budget.as_ref(), || {
+            record_duration("get_block", async {
+                let resp = self
+                    .inner
+                    .get(self.build_query("get_block", &[(tag, &hash)]))
+                    .send()
+                    .await?;
+                parse::<reply::Block>(resp).await
+            })
+        })
+        .await
+    }
+
+    /// Gets the hash of the block with the given number, without fetching the whole block.
+    #[tracing::instrument(skip(self))]
+    pub async fn block_hash_by_number(
+        &self,
+        block_number: StarknetBlockNumber,
+    ) -> Result<StarknetBlockHash, SequencerError> {
+        let number = block_number.0.to_string();
+        retry(self.retry_budget.as_ref(), || {
+            record_duration("get_block_hash_by_id", async {
+                let resp = self
+                    .inner
+                    .get(self.build_query("get_block_hash_by_id", &[("blockId", &number)]))
+                    .send()
+                    .await?;
+                parse(resp).await
+            })
         })
         .await
     }
@@ -161,14 +411,16 @@ impl Client {
         block_hash: BlockHashOrTag,
     ) -> Result<reply::Call, SequencerError> {
         let (tag, hash) = block_hash_str(block_hash);
-        retry(|| async {
-            let resp = self
-                .inner
-                .post(self.build_query("call_contract", &[(tag, &hash)]))
-                .json(&payload)
-                .send()
-                .await?;
-            parse(resp).await
+        retry(self.retry_budget.as_ref(), || {
+            record_duration("call_contract", async {
+                let resp = self
+                    .inner
+                    .post(self.build_query("call_contract", &[(tag, &hash)]))
+                    .json(&payload)
+                    .send()
+                    .await?;
+                parse(resp).await
+            })
         })
         .await
     }
@@ -179,18 +431,20 @@ impl Client {
         &self,
         contract_addr: ContractAddress,
     ) -> Result<bytes::Bytes, SequencerError> {
-        retry(|| async {
-            let resp = self
-                .inner
-                .get(self.build_query(
-                    "get_full_contract",
-                    &[("contractAddress", &contract_addr.0.to_hex_str())],
-                ))
-                .send()
-                .await?;
-            let resp = parse_raw(resp).await?;
-            let resp = resp.bytes().await?;
-            Ok(resp)
+        retry(self.retry_budget.as_ref(), || {
+            record_duration("get_full_contract", async {
+                let resp = self
+                    .inner
+                    .get(self.build_query(
+                        "get_full_contract",
+                        &[("contractAddress", &contract_addr.0.to_hex_str())],
+                    ))
+                    .send()
+                    .await?;
+                let resp = parse_raw(resp).await?;
+                let resp = read_body(resp).await?;
+                Ok(resp)
+            })
         })
         .await
     }
@@ -206,20 +460,22 @@ impl Client {
         use crate::rpc::serde::starkhash_to_dec_str;
 
         let (tag, hash) = block_hash_str(block_hash);
-        retry(|| async {
-            let resp = self
-                .inner
-                .get(self.build_query(
-                    "get_storage_at",
-                    &[
-                        ("contractAddress", &contract_addr.0.to_hex_str()),
-                        ("key", &starkhash_to_dec_str(&key.0)),
-                        (tag, &hash),
-                    ],
-                ))
-                .send()
-                .await?;
-            parse::<StorageValue>(resp).await
+        retry(self.retry_budget.as_ref(), || {
+            record_duration("get_storage_at", async {
+                let resp = self
+                    .inner
+                    .get(self.build_query(
+                        "get_storage_at",
+                        &[
+                            ("contractAddress", &contract_addr.0.to_hex_str()),
+                            ("key", &starkhash_to_dec_str(&key.0)),
+                            (tag, &hash),
+                        ],
+                    ))
+                    .send()
+                    .await?;
+                parse::<StorageValue>(resp).await
+            })
         })
         .await
     }
@@ -230,16 +486,18 @@ impl Client {
         &self,
         transaction_hash: StarknetTransactionHash,
     ) -> Result<reply::Transaction, SequencerError> {
-        retry(|| async {
-            let resp = self
-                .inner
-                .get(self.build_query(
-                    "get_transaction",
-                    &[("transactionHash", &transaction_hash.0.to_hex_str())],
-                ))
-                .send()
-                .await?;
-            parse(resp).await
+        retry(self.retry_budget.as_ref(), || {
+            record_duration("get_transaction", async {
+                let resp = self
+                    .inner
+                    .get(self.build_query(
+                        "get_transaction",
+                        &[("transactionHash", &transaction_hash.0.to_hex_str())],
+                    ))
+                    .send()
+                    .await?;
+                parse(resp).await
+            })
         })
         .await
     }
@@ -250,16 +508,18 @@ impl Client {
         &self,
         transaction_hash: StarknetTransactionHash,
     ) -> Result<reply::TransactionStatus, SequencerError> {
-        retry(|| async {
-            let resp = self
-                .inner
-                .get(self.build_query(
-                    "get_transaction_status",
-                    &[("transactionHash", &transaction_hash.0.to_hex_str())],
-                ))
-                .send()
-                .await?;
-            parse(resp).await
+        retry(self.retry_budget.as_ref(), || {
+            record_duration("get_transaction_status", async {
+                let resp = self
+                    .inner
+                    .get(self.build_query(
+                        "get_transaction_status",
+                        &[("transactionHash", &transaction_hash.0.to_hex_str())],
+                    ))
+                    .send()
+                    .await?;
+                parse(resp).await
+            })
         })
         .await
     }
@@ -271,13 +531,15 @@ impl Client {
         block_hash: BlockHashOrTag,
     ) -> Result<reply::StateUpdate, SequencerError> {
         let (tag, hash) = block_hash_str(block_hash);
-        retry(|| async {
-            let resp = self
-                .inner
-                .get(self.build_query("get_state_update", &[(tag, &hash)]))
-                .send()
-                .await?;
-            parse(resp).await
+        retry(self.retry_budget.as_ref(), || {
+            record_duration("get_state_update", async {
+                let resp = self
+                    .inner
+                    .get(self.build_query("get_state_update", &[(tag, &hash)]))
+                    .send()
+                    .await?;
+                parse(resp).await
+            })
         })
         .await
     }
@@ -288,32 +550,60 @@ impl Client {
         &self,
         block_number: BlockNumberOrTag,
     ) -> Result<reply::StateUpdate, SequencerError> {
-        retry(|| async {
-            let resp = self
-                .inner
-                .get(self.build_query(
-                    "get_state_update",
-                    &[("block_number", &block_number_str(block_number))],
-                ))
-                .send()
-                .await?;
-            parse(resp).await
+        retry(self.retry_budget.as_ref(), || {
+            record_duration("get_state_update", async {
+                let resp = self
+                    .inner
+                    .get(self.build_query(
+                        "get_state_update",
+                        &[("block_number", &block_number_str(block_number))],
+                    ))
+                    .send()
+                    .await?;
+                parse(resp).await
+            })
         })
         .await
     }
 
+    /// Gets the state update of a block, identified either by its hash or by its number.
+    pub async fn state_update(
+        &self,
+        block: request::BlockId,
+    ) -> Result<reply::StateUpdate, SequencerError> {
+        use request::BlockId;
+
+        match block {
+            BlockId::Hash(hash) => self.state_update_by_hash(BlockHashOrTag::Hash(hash)).await,
+            BlockId::Number(number) => {
+                self.state_update_by_number(BlockNumberOrTag::Number(number))
+                    .await
+            }
+            BlockId::Latest => {
+                self.state_update_by_number(BlockNumberOrTag::Tag(Tag::Latest))
+                    .await
+            }
+            BlockId::Pending => {
+                self.state_update_by_number(BlockNumberOrTag::Tag(Tag::Pending))
+                    .await
+            }
+        }
+    }
+
     /// Gets addresses of the Ethereum contracts crucial to Starknet operation.
     #[tracing::instrument(skip(self))]
     pub async fn eth_contract_addresses(
         &self,
     ) -> Result<reply::EthContractAddresses, SequencerError> {
-        retry(|| async {
-            let resp = self
-                .inner
-                .get(self.build_query("get_contract_addresses", &[]))
-                .send()
-                .await?;
-            parse(resp).await
+        retry(self.retry_budget.as_ref(), || {
+            record_duration("get_contract_addresses", async {
+                let resp = self
+                    .inner
+                    .get(self.build_query("get_contract_addresses", &[]))
+                    .send()
+                    .await?;
+                parse(resp).await
+            })
         })
         .await
     }
@@ -386,7 +676,6 @@ pub mod test_utils {
 #[cfg(test)]
 mod tests {
     use super::{error::StarknetErrorCode, test_utils::*, *};
-    use crate::core::{StarknetBlockHash, StarknetBlockNumber};
     use assert_matches::assert_matches;
     use pedersen::StarkHash;
 
@@ -474,6 +763,18 @@ mod tests {
         }
     }
 
+    mod pending_block {
+        use super::*;
+
+        #[tokio::test]
+        #[ignore = "The pending block is volatile and can be briefly empty or malformed."]
+        async fn goerli() {
+            let block = client().pending_block().await.unwrap();
+            assert_eq!(block.status, reply::Status::Pending);
+            assert_eq!(block.transactions.len(), block.transaction_receipts.len());
+        }
+    }
+
     mod block_by_number {
         use super::*;
 
@@ -514,6 +815,42 @@ mod tests {
         }
     }
 
+    mod block_hash_by_number {
+        use super::*;
+
+        #[tokio::test]
+        async fn genesis() {
+            let hash = client()
+                .block_hash_by_number(StarknetBlockNumber(0))
+                .await
+                .unwrap();
+            assert_eq!(BlockHashOrTag::Hash(hash), *GENESIS_BLOCK_HASH);
+        }
+
+        #[tokio::test]
+        async fn matches_block() {
+            let number = StarknetBlockNumber(50000);
+            let hash = client().block_hash_by_number(number).await.unwrap();
+            let block = client()
+                .block_by_number(BlockNumberOrTag::Number(number))
+                .await
+                .unwrap();
+            assert_eq!(Some(hash), block.block_hash);
+        }
+
+        #[tokio::test]
+        async fn invalid() {
+            let error = client()
+                .block_hash_by_number(StarknetBlockNumber(u64::MAX))
+                .await
+                .unwrap_err();
+            assert_matches!(
+                error,
+                SequencerError::StarknetError(e) => assert_eq!(e.code, StarknetErrorCode::BlockNotFound)
+            );
+        }
+    }
+
     mod call {
         use super::*;
         use pretty_assertions::assert_eq;
@@ -934,11 +1271,674 @@ mod tests {
         }
     }
 
+    mod state_update {
+        use super::*;
+        use request::BlockId;
+
+        #[tokio::test]
+        async fn deployed_contract() {
+            let update = client()
+                .state_update((*DEPLOY_CONTRACT_BLOCK_HASH).into())
+                .await
+                .unwrap();
+
+            assert!(update
+                .state_diff
+                .deployed_contracts
+                .iter()
+                .any(|contract| contract.address == *VALID_CONTRACT_ADDR));
+        }
+
+        #[tokio::test]
+        async fn storage_diff() {
+            let update = client()
+                .state_update((*INVOKE_CONTRACT_BLOCK_HASH).into())
+                .await
+                .unwrap();
+
+            let diff = update
+                .state_diff
+                .storage_diffs
+                .get(&*VALID_CONTRACT_ADDR)
+                .expect("Invoked contract should have a storage diff")
+                .iter()
+                .find(|diff| diff.key == *VALID_KEY)
+                .expect("Storage diff should contain the invoked key")
+                .clone();
+
+            let value = client()
+                .storage(
+                    *VALID_CONTRACT_ADDR,
+                    *VALID_KEY,
+                    *INVOKE_CONTRACT_BLOCK_HASH,
+                )
+                .await
+                .unwrap();
+            assert_eq!(diff.value, value);
+        }
+
+        #[tokio::test]
+        async fn latest() {
+            client().state_update(BlockId::Latest).await.unwrap();
+        }
+
+        #[tokio::test]
+        async fn pending() {
+            client().state_update(BlockId::Pending).await.unwrap();
+        }
+    }
+
     #[tokio::test]
     async fn eth_contract_addresses() {
         client().eth_contract_addresses().await.unwrap();
     }
 
+    mod mock_gateway {
+        use super::*;
+        use crate::sequencer::mock::MockGateway;
+        use pretty_assertions::assert_eq;
+
+        /// A synthetic block reply, see the `block_fixture` tests of [reply].
+        const BLOCK: &str = include_str!("../fixtures/sequencer/block.json");
+
+        fn block_hash() -> StarknetBlockHash {
+            StarknetBlockHash::from_hex_str(
+                "0x3fa1cfb3b8b0cb2ecb39e6bbbd5f8c1b2c1f2e29c3fcb70ca35b81d3b87c4a1",
+            )
+            .unwrap()
+        }
+
+        #[tokio::test]
+        async fn full_contract() {
+            let contract_definition = crate::test_utils::contract_definition_fixture();
+            let url = MockGateway::default()
+                .with_full_contract(*VALID_CONTRACT_ADDR, contract_definition.clone())
+                .start();
+            let client = Client::builder(Chain::Goerli).url(url).build().unwrap();
+
+            let bytes = client.full_contract(*VALID_CONTRACT_ADDR).await.unwrap();
+            assert_eq!(bytes, contract_definition);
+
+            let error = client
+                .full_contract(*INVALID_CONTRACT_ADDR)
+                .await
+                .unwrap_err();
+            assert_matches!(
+                error,
+                SequencerError::StarknetError(e) => assert_eq!(e.code, StarknetErrorCode::UninitializedContract)
+            );
+        }
+
+        #[tokio::test]
+        async fn block() {
+            let url = MockGateway::default().with_block(BLOCK).start();
+            let client = Client::builder(Chain::Goerli).url(url).build().unwrap();
+
+            let by_number = client
+                .block_by_number(BlockNumberOrTag::Number(StarknetBlockNumber(1716)))
+                .await
+                .unwrap();
+            assert_eq!(by_number.block_hash, Some(block_hash()));
+
+            let by_hash = client
+                .block_by_hash(BlockHashOrTag::Hash(block_hash()))
+                .await
+                .unwrap();
+            assert_eq!(by_hash, by_number);
+
+            let latest = client
+                .block_by_number(BlockNumberOrTag::Tag(Tag::Latest))
+                .await
+                .unwrap();
+            assert_eq!(latest, by_number);
+
+            let error = client
+                .block_by_number(*GENESIS_BLOCK_NUMBER)
+                .await
+                .unwrap_err();
+            assert_matches!(
+                error,
+                SequencerError::StarknetError(e) => assert_eq!(e.code, StarknetErrorCode::BlockNotFound)
+            );
+        }
+
+        #[tokio::test]
+        async fn pending_block() {
+            // Both fixtures are synthetic, see `fixtures/sequencer/README.md`.
+            let url = MockGateway::default()
+                .with_block(BLOCK)
+                .with_pending_block(include_str!("../fixtures/sequencer/pending_block.json"))
+                .start();
+            let client = Client::builder(Chain::Goerli).url(url).build().unwrap();
+
+            let pending = client.pending_block().await.unwrap();
+            assert_eq!(pending.status, reply::Status::Pending);
+            assert_eq!(
+                pending.parent_block_hash.0,
+                StarkHash::from_hex_str(
+                    "0x5d7b0f2d3aa28d6d1a5a1e5c54d8aa6d70f6a9ab17c8d1a6f0ff5d16e4f96e7"
+                )
+                .unwrap()
+            );
+
+            // No pending block served
+            let url = MockGateway::default().with_block(BLOCK).start();
+            let error = Client::builder(Chain::Goerli)
+                .url(url)
+                .build()
+                .unwrap()
+                .pending_block()
+                .await
+                .unwrap_err();
+            assert_matches!(
+                error,
+                SequencerError::StarknetError(e) => assert_eq!(e.code, StarknetErrorCode::BlockNotFound)
+            );
+        }
+
+        #[tokio::test]
+        async fn block_hash_by_number() {
+            let url = MockGateway::default().with_block(BLOCK).start();
+            let client = Client::builder(Chain::Goerli).url(url).build().unwrap();
+
+            let hash = client
+                .block_hash_by_number(StarknetBlockNumber(1716))
+                .await
+                .unwrap();
+            assert_eq!(hash, block_hash());
+
+            let error = client
+                .block_hash_by_number(StarknetBlockNumber(0))
+                .await
+                .unwrap_err();
+            assert_matches!(
+                error,
+                SequencerError::StarknetError(e) => assert_eq!(e.code, StarknetErrorCode::BlockNotFound)
+            );
+        }
+
+        #[tokio::test]
+        async fn fail_with() {
+            let url = MockGateway::default()
+                .with_block(BLOCK)
+                .fail_with(StarknetErrorCode::OutOfRangeBlockHash)
+                .start();
+            let client = Client::builder(Chain::Goerli).url(url).build().unwrap();
+
+            let error = client
+                .block_by_hash(BlockHashOrTag::Hash(block_hash()))
+                .await
+                .unwrap_err();
+            assert_matches!(
+                error,
+                SequencerError::StarknetError(e) => assert_eq!(e.code, StarknetErrorCode::OutOfRangeBlockHash)
+            );
+        }
+    }
+
+    mod metrics {
+        use super::*;
+        use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+
+        #[tokio::test]
+        async fn request_duration_is_recorded() {
+            use warp::Filter;
+
+            let recorder = DebuggingRecorder::per_thread();
+            let snapshotter = recorder.snapshotter();
+            recorder.install().unwrap();
+
+            let reply = warp::path!("feeder_gateway" / "get_contract_addresses").map(|| {
+                r#"{
+                    "Starknet": "0xde29d060D45901Fb19ED6C6e959EB22d8626708e",
+                    "GpsStatementVerifier": "0xab43ba48c9edf4c2c4bb01237348d1d7b28ef168"
+                }"#
+            });
+            let (addr, run_srv) = warp::serve(reply).bind_ephemeral(([127, 0, 0, 1], 0));
+            let _jh = tokio::spawn(run_srv);
+
+            let url = reqwest::Url::parse(&format!("http://{}/", addr)).unwrap();
+            Client::builder(Chain::Goerli)
+                .url(url)
+                .build()
+                .unwrap()
+                .eth_contract_addresses()
+                .await
+                .unwrap();
+
+            let durations = snapshotter
+                .snapshot()
+                .into_vec()
+                .into_iter()
+                .filter_map(|(key, _, _, value)| {
+                    let key = key.key();
+                    let labels = key
+                        .labels()
+                        .map(|l| (l.key().to_owned(), l.value().to_owned()))
+                        .collect::<Vec<_>>();
+                    match value {
+                        DebugValue::Histogram(values)
+                            if key.name() == "sequencer_request_duration_seconds" =>
+                        {
+                            Some((labels, values.len()))
+                        }
+                        _ => None,
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            assert_eq!(
+                durations,
+                vec![(
+                    vec![
+                        ("endpoint".to_owned(), "get_contract_addresses".to_owned()),
+                        ("outcome".to_owned(), "ok".to_owned())
+                    ],
+                    1
+                )]
+            );
+        }
+    }
+
+    mod timeout {
+        use super::*;
+        use std::sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        };
+        use warp::Filter;
+
+        /// Serves `get_contract_addresses`, taking `delay` to reply to the first `slow` requests.
+        fn slow_server(delay: Duration, slow: usize) -> Url {
+            let count = Arc::new(AtomicUsize::new(0));
+            let reply = warp::path!("feeder_gateway" / "get_contract_addresses").then(move || {
+                let count = count.clone();
+                async move {
+                    if count.fetch_add(1, Ordering::Relaxed) < slow {
+                        tokio::time::sleep(delay).await;
+                    }
+                    r#"{
+                        "Starknet": "0xde29d060D45901Fb19ED6C6e959EB22d8626708e",
+                        "GpsStatementVerifier": "0xab43ba48c9edf4c2c4bb01237348d1d7b28ef168"
+                    }"#
+                }
+            });
+            let (addr, run_srv) = warp::serve(reply).bind_ephemeral(([127, 0, 0, 1], 0));
+            tokio::spawn(run_srv);
+
+            Url::parse(&format!("http://{}/", addr)).unwrap()
+        }
+
+        #[tokio::test]
+        async fn exceeded_is_timeout_error() {
+            let url = slow_server(Duration::from_secs(5), usize::MAX);
+            let client = Client::builder(Chain::Goerli)
+                .url(url)
+                .timeout(Duration::from_millis(100))
+                .build()
+                .unwrap();
+
+            let result = client
+                .inner
+                .get(client.build_query("get_contract_addresses", &[]))
+                .send()
+                .await
+                .map_err(SequencerError::from);
+
+            assert_matches!(result, Err(SequencerError::Timeout(_)));
+        }
+
+        #[tokio::test]
+        async fn is_retried() {
+            let url = slow_server(Duration::from_secs(5), 1);
+            let client = Client::builder(Chain::Goerli)
+                .url(url)
+                .timeout(Duration::from_millis(100))
+                .build()
+                .unwrap();
+
+            client.eth_contract_addresses().await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn with_http_client() {
+        use warp::Filter;
+
+        let reply = warp::path!("feeder_gateway" / "get_contract_addresses")
+            .and(warp::header::exact("x-injected", "yes"))
+            .map(|| {
+                r#"{
+                    "Starknet": "0xde29d060D45901Fb19ED6C6e959EB22d8626708e",
+                    "GpsStatementVerifier": "0xab43ba48c9edf4c2c4bb01237348d1d7b28ef168"
+                }"#
+            });
+        let (addr, run_srv) = warp::serve(reply).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(run_srv);
+        let url = Url::parse(&format!("http://{}/", addr)).unwrap();
+
+        // Without the header of the injected client the request is rejected.
+        Client::builder(Chain::Goerli)
+            .url(url.clone())
+            .build()
+            .unwrap()
+            .eth_contract_addresses()
+            .await
+            .unwrap_err();
+
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(
+            "x-injected",
+            reqwest::header::HeaderValue::from_static("yes"),
+        );
+        let http_client = reqwest::Client::builder()
+            .default_headers(headers)
+            .build()
+            .unwrap();
+
+        Client::builder(Chain::Goerli)
+            .url(url)
+            .http_client(http_client)
+            .build()
+            .unwrap()
+            .eth_contract_addresses()
+            .await
+            .unwrap();
+    }
+
+    mod parse_body {
+        use super::*;
+
+        #[test]
+        fn valid() {
+            let addresses = super::super::parse_body::<reply::EthContractAddresses>(
+                br#"{
+                    "Starknet": "0xde29d060D45901Fb19ED6C6e959EB22d8626708e",
+                    "GpsStatementVerifier": "0xab43ba48c9edf4c2c4bb01237348d1d7b28ef168"
+                }"#,
+            );
+            assert!(addresses.is_ok());
+        }
+
+        #[test]
+        fn invalid_utf8() {
+            let result = super::super::parse_body::<serde_json::Value>(b"{\"key\": \"\xff\xfe\"}");
+            assert_matches!(result, Err(SequencerError::InvalidResponse(_)));
+        }
+
+        #[test]
+        fn invalid_json() {
+            let result = super::super::parse_body::<serde_json::Value>(b"{\"key\": ");
+            assert_matches!(result, Err(SequencerError::DeserializationError(_)));
+        }
+
+        #[tokio::test]
+        async fn binary_reply_is_not_a_panic() {
+            use warp::Filter;
+
+            let reply = warp::path!("feeder_gateway" / "get_contract_addresses")
+                .map(|| vec![0xffu8, 0xfe, 0xfd]);
+            let (addr, run_srv) = warp::serve(reply).bind_ephemeral(([127, 0, 0, 1], 0));
+            tokio::spawn(run_srv);
+
+            let url = Url::parse(&format!("http://{}/", addr)).unwrap();
+            let result = Client::builder(Chain::Goerli)
+                .url(url)
+                .build()
+                .unwrap()
+                .eth_contract_addresses()
+                .await;
+            assert_matches!(result, Err(SequencerError::InvalidResponse(_)));
+        }
+    }
+
+    mod truncated {
+        use super::*;
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        /// Serves one reply per connection, in order. Each reply declares a `Content-Length` of
+        /// `declared` bytes but closes the connection after sending `body`.
+        async fn serve(replies: Vec<(usize, &'static str)>) -> Url {
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let addr = listener.local_addr().unwrap();
+
+            tokio::spawn(async move {
+                for (declared, body) in replies {
+                    let (mut stream, _) = listener.accept().await.unwrap();
+                    // Wait for the request before replying.
+                    let mut request = [0u8; 1024];
+                    let _ = stream.read(&mut request).await;
+
+                    let reply = format!(
+                        "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                        declared, body
+                    );
+                    stream.write_all(reply.as_bytes()).await.unwrap();
+                    stream.shutdown().await.unwrap();
+                }
+            });
+
+            Url::parse(&format!("http://{}/", addr)).unwrap()
+        }
+
+        const COMPLETE: &str = r#"{
+                "Starknet": "0xde29d060D45901Fb19ED6C6e959EB22d8626708e",
+                "GpsStatementVerifier": "0xab43ba48c9edf4c2c4bb01237348d1d7b28ef168"
+            }"#;
+
+        #[tokio::test]
+        async fn short_body() {
+            let body = r#"{"Starknet": "0xde29d060D45901Fb19ED6C6e"#;
+            let url = serve(vec![(100, body)]).await;
+
+            // Parsed without retrying, as a retry would find nothing listening.
+            let resp = reqwest::get(url).await.unwrap();
+            let error = super::parse::<reply::EthContractAddresses>(resp)
+                .await
+                .unwrap_err();
+            assert_matches!(
+                error,
+                SequencerError::Truncated { expected, received } => {
+                    assert_eq!(expected, 100);
+                    assert_eq!(received, body.len() as u64);
+                }
+            );
+        }
+
+        #[tokio::test]
+        async fn complete_body() {
+            let url = serve(vec![(COMPLETE.len(), COMPLETE)]).await;
+
+            Client::builder(Chain::Goerli)
+                .url(url)
+                .build()
+                .unwrap()
+                .eth_contract_addresses()
+                .await
+                .unwrap();
+        }
+
+        #[tokio::test]
+        async fn is_retried() {
+            let body = r#"{"Starknet": "0xde29d060D45901Fb19ED6C6e"#;
+            let url = serve(vec![(100, body), (COMPLETE.len(), COMPLETE)]).await;
+
+            Client::builder(Chain::Goerli)
+                .url(url)
+                .build()
+                .unwrap()
+                .eth_contract_addresses()
+                .await
+                .unwrap();
+        }
+
+        #[test]
+        fn is_transient() {
+            let error = SequencerError::Truncated {
+                expected: 100,
+                received: 10,
+            };
+            assert_eq!(super::classify(&error), crate::retry::Retriable::Transient);
+        }
+    }
+
+    mod full_contract_errors {
+        use super::*;
+        use warp::Filter;
+
+        fn serve(status: warp::http::StatusCode, body: &'static str) -> Url {
+            let reply = warp::path!("feeder_gateway" / "get_full_contract")
+                .map(move || warp::reply::with_status(body, status));
+            let (addr, run_srv) = warp::serve(reply).bind_ephemeral(([127, 0, 0, 1], 0));
+            tokio::spawn(run_srv);
+
+            Url::parse(&format!("http://{}/", addr)).unwrap()
+        }
+
+        #[tokio::test]
+        async fn network() {
+            // Nothing listens on the port once the listener is dropped.
+            let addr = std::net::TcpListener::bind("127.0.0.1:0")
+                .unwrap()
+                .local_addr()
+                .unwrap();
+            let url = Url::parse(&format!("http://{}/", addr)).unwrap();
+
+            let error = Client::builder(Chain::Goerli)
+                .url(url)
+                .build()
+                .unwrap()
+                .full_contract(*VALID_CONTRACT_ADDR)
+                .await
+                .unwrap_err();
+            assert_matches!(error, SequencerError::TransportError(_));
+            assert_eq!(error.http_status(), None);
+        }
+
+        #[tokio::test]
+        async fn http_status() {
+            let url = serve(warp::http::StatusCode::NOT_FOUND, "Not found");
+
+            let error = Client::builder(Chain::Goerli)
+                .url(url)
+                .build()
+                .unwrap()
+                .full_contract(*VALID_CONTRACT_ADDR)
+                .await
+                .unwrap_err();
+            assert_matches!(error, SequencerError::TransportError(_));
+            assert_eq!(error.http_status(), Some(reqwest::StatusCode::NOT_FOUND));
+        }
+
+        #[tokio::test]
+        async fn bad_json() {
+            let url = serve(warp::http::StatusCode::INTERNAL_SERVER_ERROR, "{\"code\": ");
+
+            let error = Client::builder(Chain::Goerli)
+                .url(url)
+                .build()
+                .unwrap()
+                .full_contract(*VALID_CONTRACT_ADDR)
+                .await
+                .unwrap_err();
+            assert_matches!(error, SequencerError::DeserializationError(_));
+            assert_eq!(error.http_status(), None);
+        }
+
+        #[tokio::test]
+        async fn starknet() {
+            let url = serve(
+                warp::http::StatusCode::INTERNAL_SERVER_ERROR,
+                r#"{"code": "StarknetErrorCode.UNINITIALIZED_CONTRACT", "message": "Not deployed"}"#,
+            );
+
+            let error = Client::builder(Chain::Goerli)
+                .url(url)
+                .build()
+                .unwrap()
+                .full_contract(*VALID_CONTRACT_ADDR)
+                .await
+                .unwrap_err();
+            assert_matches!(
+                error,
+                SequencerError::StarknetError(e) => assert_eq!(e.code, StarknetErrorCode::UninitializedContract)
+            );
+        }
+    }
+
+    mod pool {
+        use super::*;
+        use std::sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        };
+        use warp::Filter;
+
+        /// Serves `get_contract_addresses`, counting the number of accepted connections.
+        async fn counting_server() -> (Url, Arc<AtomicUsize>) {
+            let reply = warp::path!("feeder_gateway" / "get_contract_addresses").map(|| {
+                r#"{
+                    "Starknet": "0xde29d060D45901Fb19ED6C6e959EB22d8626708e",
+                    "GpsStatementVerifier": "0xab43ba48c9edf4c2c4bb01237348d1d7b28ef168"
+                }"#
+            });
+
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let addr = listener.local_addr().unwrap();
+
+            let connections = Arc::new(AtomicUsize::new(0));
+            let counter = connections.clone();
+            let incoming = futures::stream::unfold(listener, move |listener| {
+                let counter = counter.clone();
+                async move {
+                    let connection = listener.accept().await.map(|(stream, _)| {
+                        counter.fetch_add(1, Ordering::Relaxed);
+                        stream
+                    });
+                    Some((connection, listener))
+                }
+            });
+            tokio::spawn(warp::serve(reply).run_incoming(incoming));
+
+            let url = Url::parse(&format!("http://{}/", addr)).unwrap();
+            (url, connections)
+        }
+
+        #[tokio::test]
+        async fn connection_is_reused() {
+            let (url, connections) = counting_server().await;
+            let client = Client::builder(Chain::Goerli).url(url).build().unwrap();
+
+            for _ in 0..3 {
+                client.eth_contract_addresses().await.unwrap();
+            }
+            // Clones share the pool.
+            client.clone().eth_contract_addresses().await.unwrap();
+
+            assert_eq!(connections.load(Ordering::Relaxed), 1);
+        }
+
+        #[tokio::test]
+        async fn no_idle_connections() {
+            let (url, connections) = counting_server().await;
+            let pool = PoolConfig {
+                max_idle_per_host: 0,
+                ..Default::default()
+            };
+            let client = Client::builder(Chain::Goerli)
+                .url(url)
+                .pool(pool)
+                .build()
+                .unwrap();
+
+            for _ in 0..3 {
+                client.eth_contract_addresses().await.unwrap();
+            }
+
+            assert_eq!(connections.load(Ordering::Relaxed), 3);
+        }
+    }
+
     mod retry {
         use super::{SequencerError, StarknetErrorCode};
         use assert_matches::assert_matches;
@@ -949,6 +1949,14 @@ mod tests {
 
         async fn run_retry(
             statuses: VecDeque<(StatusCode, &'static str)>,
+        ) -> Result<String, SequencerError> {
+            // A budget of its own, so that other tests cannot exhaust it.
+            run_retry_with_budget(statuses, crate::retry::RetryBudget::default()).await
+        }
+
+        async fn run_retry_with_budget(
+            statuses: VecDeque<(StatusCode, &'static str)>,
+            budget: crate::retry::RetryBudget,
         ) -> Result<String, SequencerError> {
             use http::response::Builder;
             use std::{
@@ -969,7 +1977,7 @@ mod tests {
             let _jh = tokio::spawn(run_srv);
 
             // super::retry is the UUT here
-            let result = super::retry(|| async {
+            let result = super::retry(Some(&budget), || async {
                 let mut url = reqwest::Url::parse("http://localhost/").unwrap();
                 url.set_port(Some(addr.port())).unwrap();
                 let resp = reqwest::get(url).await.unwrap();
@@ -1021,6 +2029,21 @@ mod tests {
             );
         }
 
+        #[tokio::test]
+        async fn stop_on_exhausted_budget() {
+            let statuses = VecDeque::from([
+                (StatusCode::SERVICE_UNAVAILABLE, ""),
+                (StatusCode::OK, r#""Not reached""#),
+            ]);
+            let budget = crate::retry::RetryBudget::new(0, std::time::Duration::from_secs(3600));
+
+            let error = run_retry_with_budget(statuses, budget).await.unwrap_err();
+            assert_matches!(
+                error,
+                SequencerError::TransportError(te) => assert_eq!(te.status(), Some(StatusCode::SERVICE_UNAVAILABLE))
+            );
+        }
+
         #[tokio::test]
         #[traced_test]
         async fn stop_on_max_retry_count() {
@@ -14,7 +14,40 @@ pub enum SequencerError {
     StarknetError(#[from] StarknetError),
     /// Networking and protocol related errors.
     #[error("Sequencer transport error: {0}")]
-    TransportError(#[from] reqwest::Error),
+    TransportError(reqwest::Error),
+    /// The request did not complete within the client's timeout.
+    #[error("Sequencer request timed out: {0}")]
+    Timeout(reqwest::Error),
+    /// The reply body was not valid UTF-8 text, for example a corrupted or binary response.
+    #[error("Invalid sequencer reply: {0}")]
+    InvalidResponse(#[from] std::str::Utf8Error),
+    /// The reply body ended before the length declared in its `Content-Length` header, for
+    /// example due to a dropped connection.
+    #[error("Truncated sequencer reply: received {received} of {expected} bytes")]
+    Truncated { expected: u64, received: u64 },
+}
+
+impl From<reqwest::Error> for SequencerError {
+    fn from(e: reqwest::Error) -> Self {
+        match e.is_timeout() {
+            true => SequencerError::Timeout(e),
+            false => SequencerError::TransportError(e),
+        }
+    }
+}
+
+impl SequencerError {
+    /// The HTTP status of a [SequencerError::TransportError] caused by an error status reply,
+    /// [None] for connection level failures and all other errors.
+    ///
+    /// Starknet errors are replied to with a 500 status, but they are surfaced as
+    /// [SequencerError::StarknetError] instead.
+    pub fn http_status(&self) -> Option<reqwest::StatusCode> {
+        match self {
+            SequencerError::TransportError(e) => e.status(),
+            _ => None,
+        }
+    }
 }
 
 impl From<SequencerError> for rpc::Error {
@@ -44,7 +77,15 @@ impl From<SequencerError> for rpc::Error {
                 }
                 _ => rpc::Error::Call(rpc::CallError::Failed(e.into())),
             },
-            SequencerError::TransportError(e) => rpc::Error::Call(rpc::CallError::Failed(e.into())),
+            SequencerError::TransportError(e) | SequencerError::Timeout(e) => {
+                rpc::Error::Call(rpc::CallError::Failed(e.into()))
+            }
+            SequencerError::InvalidResponse(e) => {
+                rpc::Error::Call(rpc::CallError::Failed(e.into()))
+            }
+            e @ SequencerError::Truncated { .. } => {
+                rpc::Error::Call(rpc::CallError::Failed(e.into()))
+            }
         }
     }
 }
@@ -0,0 +1,216 @@
+//! A mock feeder gateway which serves canned replies, so that tests can exercise the sequencer
+//! [Client](super::Client) offline.
+use super::error::{StarknetError, StarknetErrorCode};
+use crate::core::{ContractAddress, StarknetBlockHash, StarknetBlockNumber};
+use pedersen::StarkHash;
+use reqwest::Url;
+use std::{collections::HashMap, sync::Arc};
+use warp::{http::StatusCode, Filter, Reply};
+
+/// Serves canned `get_full_contract`, `get_block` (including the pending block),
+/// `get_block_hash_by_id` and `get_contract_addresses` replies.
+///
+/// Requests for unknown contracts or blocks get the 500 error reply of the feeder gateway, as do
+/// all requests once [MockGateway::fail_with] is set.
+#[derive(Debug, Default, Clone)]
+pub struct MockGateway {
+    contracts: HashMap<ContractAddress, Vec<u8>>,
+    blocks: Vec<RecordedBlock>,
+    pending_block: Option<String>,
+    contract_addresses: Option<String>,
+    error: Option<StarknetErrorCode>,
+}
+
+#[derive(Debug, Clone)]
+struct RecordedBlock {
+    number: StarknetBlockNumber,
+    hash: StarknetBlockHash,
+    body: String,
+}
+
+type Query = HashMap<String, String>;
+
+impl MockGateway {
+    /// Serves `body` as the `get_full_contract` reply of `address`.
+    pub fn with_full_contract(
+        mut self,
+        address: ContractAddress,
+        body: impl Into<Vec<u8>>,
+    ) -> Self {
+        self.contracts.insert(address, body.into());
+        self
+    }
+
+    /// Serves `body` as the `get_block` reply of the block number and hash it contains. The
+    /// block with the highest number is served as the latest block.
+    pub fn with_block(mut self, body: impl Into<String>) -> Self {
+        #[derive(serde::Deserialize)]
+        struct Header {
+            block_hash: StarknetBlockHash,
+            block_number: StarknetBlockNumber,
+        }
+
+        let body = body.into();
+        let header = serde_json::from_str::<Header>(&body).expect("Block reply has a header");
+        self.blocks.push(RecordedBlock {
+            number: header.block_number,
+            hash: header.block_hash,
+            body,
+        });
+        self
+    }
+
+    /// Serves `body` as the `get_block` reply of the pending block.
+    pub fn with_pending_block(mut self, body: impl Into<String>) -> Self {
+        self.pending_block = Some(body.into());
+        self
+    }
+
+    /// Serves `body` as the `get_contract_addresses` reply.
+    pub fn with_contract_addresses(mut self, body: impl Into<String>) -> Self {
+        self.contract_addresses = Some(body.into());
+        self
+    }
+
+    /// Replies to every request with the feeder gateway error of `code`.
+    pub fn fail_with(mut self, code: StarknetErrorCode) -> Self {
+        self.error = Some(code);
+        self
+    }
+
+    /// Starts serving on an ephemeral localhost port and returns the URL to point the
+    /// [Client](super::Client) at. The server runs until the runtime shuts down.
+    pub fn start(self) -> Url {
+        let gateway = Arc::new(self);
+
+        let full_contract = {
+            let gateway = gateway.clone();
+            warp::path!("feeder_gateway" / "get_full_contract")
+                .and(warp::query::<Query>())
+                .map(move |query: Query| gateway.full_contract(&query))
+        };
+        let block = {
+            let gateway = gateway.clone();
+            warp::path!("feeder_gateway" / "get_block")
+                .and(warp::query::<Query>())
+                .map(move |query: Query| gateway.block(&query))
+        };
+        let block_hash = {
+            let gateway = gateway.clone();
+            warp::path!("feeder_gateway" / "get_block_hash_by_id")
+                .and(warp::query::<Query>())
+                .map(move |query: Query| gateway.block_hash(&query))
+        };
+        let contract_addresses = warp::path!("feeder_gateway" / "get_contract_addresses")
+            .map(move || gateway.contract_addresses());
+
+        let routes = full_contract
+            .or(block)
+            .unify()
+            .or(block_hash)
+            .unify()
+            .or(contract_addresses)
+            .unify();
+        let (addr, run_srv) = warp::serve(routes).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(run_srv);
+
+        Url::parse(&format!("http://{}/", addr)).unwrap()
+    }
+
+    fn full_contract(&self, query: &Query) -> warp::reply::Response {
+        if let Some(code) = self.error {
+            return error(code, "Injected failure");
+        }
+
+        let address = match query
+            .get("contractAddress")
+            .map(|a| StarkHash::from_hex_str(a))
+        {
+            Some(Ok(address)) => ContractAddress(address),
+            _ => {
+                return error(
+                    StarknetErrorCode::MalformedRequest,
+                    "Invalid contractAddress",
+                )
+            }
+        };
+
+        match self.contracts.get(&address) {
+            Some(body) => body.clone().into_response(),
+            None => error(
+                StarknetErrorCode::UninitializedContract,
+                &format!("Contract with address {} is not deployed.", address.0),
+            ),
+        }
+    }
+
+    fn block(&self, query: &Query) -> warp::reply::Response {
+        if let Some(code) = self.error {
+            return error(code, "Injected failure");
+        }
+
+        let block = match (query.get("blockNumber"), query.get("blockHash")) {
+            (Some(number), None) if number == "pending" => {
+                return match &self.pending_block {
+                    Some(body) => body.clone().into_response(),
+                    None => error(StarknetErrorCode::BlockNotFound, "No pending block"),
+                };
+            }
+            (Some(number), None) if number == "null" => {
+                self.blocks.iter().max_by_key(|block| block.number.0)
+            }
+            (Some(number), None) => match number.parse::<u64>() {
+                Ok(number) => self.blocks.iter().find(|block| block.number.0 == number),
+                Err(_) => None,
+            },
+            (None, Some(hash)) => match StarkHash::from_hex_str(hash) {
+                Ok(hash) => self.blocks.iter().find(|block| block.hash.0 == hash),
+                Err(_) => None,
+            },
+            _ => return error(StarknetErrorCode::MalformedRequest, "Invalid block id"),
+        };
+
+        match block {
+            Some(block) => block.body.clone().into_response(),
+            None => error(StarknetErrorCode::BlockNotFound, "Block not found"),
+        }
+    }
+
+    fn block_hash(&self, query: &Query) -> warp::reply::Response {
+        if let Some(code) = self.error {
+            return error(code, "Injected failure");
+        }
+
+        let number = match query.get("blockId").map(|id| id.parse::<u64>()) {
+            Some(Ok(number)) => number,
+            _ => return error(StarknetErrorCode::MalformedRequest, "Invalid blockId"),
+        };
+
+        match self.blocks.iter().find(|block| block.number.0 == number) {
+            Some(block) => warp::reply::json(&block.hash).into_response(),
+            None => error(StarknetErrorCode::BlockNotFound, "Block not found"),
+        }
+    }
+
+    fn contract_addresses(&self) -> warp::reply::Response {
+        if let Some(code) = self.error {
+            return error(code, "Injected failure");
+        }
+
+        match &self.contract_addresses {
+            Some(body) => body.clone().into_response(),
+            None => error(StarknetErrorCode::MalformedRequest, "No contract addresses"),
+        }
+    }
+}
+
+/// The 500 reply of the feeder gateway, e.g.
+/// `{"code": "StarknetErrorCode.UNINITIALIZED_CONTRACT", "message": "..."}`.
+fn error(code: StarknetErrorCode, message: &str) -> warp::reply::Response {
+    let error = StarknetError {
+        code,
+        message: message.to_owned(),
+    };
+    warp::reply::with_status(warp::reply::json(&error), StatusCode::INTERNAL_SERVER_ERROR)
+        .into_response()
+}
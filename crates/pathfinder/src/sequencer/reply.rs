@@ -24,6 +24,20 @@ pub struct Block {
     pub transactions: Vec<transaction::Transaction>,
 }
 
+/// Used to deserialize a reply from [Client::pending_block](crate::sequencer::Client::pending_block).
+///
+/// Unlike a [Block], the pending block has not been closed yet, so it has no hash, number or
+/// state root.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct PendingBlock {
+    pub parent_block_hash: StarknetBlockHash,
+    pub status: Status,
+    pub timestamp: u64,
+    pub transaction_receipts: Vec<transaction::Receipt>,
+    pub transactions: Vec<transaction::Transaction>,
+}
+
 /// Block and transaction status values.
 #[derive(Copy, Clone, Debug, Deserialize, PartialEq)]
 #[serde(deny_unknown_fields)]
@@ -46,6 +60,14 @@ pub enum Status {
     Aborted,
 }
 
+impl Status {
+    /// Returns `true` if the block (or transaction) has been accepted on L1, after which it can
+    /// no longer be reverted by a reorg of L2.
+    pub fn is_final(&self) -> bool {
+        matches!(self, Status::AcceptedOnL1)
+    }
+}
+
 /// Used to deserialize a reply from [Client::call](crate::sequencer::Client::call).
 #[derive(Clone, Debug, Deserialize, PartialEq)]
 #[serde(deny_unknown_fields)]
@@ -206,10 +228,10 @@ pub mod transaction {
     #[serde(deny_unknown_fields)]
     pub struct Event {
         #[serde_as(as = "Vec<EventDataAsDecimalStr>")]
-        data: Vec<EventData>,
-        from_address: ContractAddress,
+        pub data: Vec<EventData>,
+        pub from_address: ContractAddress,
         #[serde_as(as = "Vec<EventKeyAsDecimalStr>")]
-        keys: Vec<EventKey>,
+        pub keys: Vec<EventKey>,
     }
 
     /// Represents deserialized object containing L2 contract address and transaction type.
@@ -266,8 +288,9 @@ pub mod transaction {
     }
 }
 
-/// Used to deserialize a reply from
-/// [`Client::state_update_by_hash`](crate::sequencer::Client::state_update_by_hash).
+/// Used to deserialize a reply from [`Client::state_update`](crate::sequencer::Client::state_update),
+/// [`Client::state_update_by_hash`](crate::sequencer::Client::state_update_by_hash) and
+/// [`Client::state_update_by_number`](crate::sequencer::Client::state_update_by_number).
 #[derive(Clone, Debug, Deserialize, PartialEq)]
 pub struct StateUpdate {
     // At the moment when querying by block hash there is an additional `block_hash` field available.
@@ -279,7 +302,8 @@ pub struct StateUpdate {
 
 /// Types used when deserializing state update related data.
 pub mod state_update {
-    use crate::core::{ContractAddress, ContractHash, StorageAddress, StorageValue};
+    use crate::core::{ContractAddress, ContractHash, ContractNonce, StorageAddress, StorageValue};
+    use pedersen::StarkHash;
     use serde::Deserialize;
     use serde_with::serde_as;
     use std::collections::HashMap;
@@ -292,6 +316,20 @@ pub mod state_update {
         #[serde_as(as = "HashMap<_, Vec<_>>")]
         pub storage_diffs: HashMap<ContractAddress, Vec<StorageDiff>>,
         pub deployed_contracts: Vec<Contract>,
+        /// Hashes of the contract classes declared in this block. Older replies omit this field,
+        /// newer ones call it `old_declared_contracts`.
+        #[serde(default, alias = "old_declared_contracts")]
+        pub declared_contracts: Vec<ContractHash>,
+        /// The new nonces of the contracts whose nonce changed. Older replies omit this field.
+        #[serde(default)]
+        pub nonces: HashMap<ContractAddress, ContractNonce>,
+        /// Classes declared in this block along with their compiled class hash. Older replies
+        /// omit this field.
+        #[serde(default)]
+        pub declared_classes: Vec<DeclaredClass>,
+        /// Contracts whose class was replaced in this block. Older replies omit this field.
+        #[serde(default)]
+        pub replaced_classes: Vec<ReplacedClass>,
     }
 
     /// L2 storage diff.
@@ -307,8 +345,26 @@ pub mod state_update {
     #[serde(deny_unknown_fields)]
     pub struct Contract {
         pub address: ContractAddress,
+        /// Newer replies call this `class_hash`.
+        #[serde(alias = "class_hash")]
         pub contract_hash: ContractHash,
     }
+
+    /// A class declared within state diff.
+    #[derive(Clone, Debug, Deserialize, PartialEq)]
+    #[serde(deny_unknown_fields)]
+    pub struct DeclaredClass {
+        pub class_hash: ContractHash,
+        pub compiled_class_hash: StarkHash,
+    }
+
+    /// A contract whose class was replaced within state diff.
+    #[derive(Clone, Debug, Deserialize, PartialEq)]
+    #[serde(deny_unknown_fields)]
+    pub struct ReplacedClass {
+        pub address: ContractAddress,
+        pub class_hash: ContractHash,
+    }
 }
 
 /// Used to deserialize a reply from [Client::eth_contract_addresses](crate::sequencer::Client::eth_contract_addresses).
@@ -323,3 +379,350 @@ pub struct EthContractAddresses {
     #[serde(rename = "GpsStatementVerifier")]
     pub gps_statement_verifier: EthereumAddress,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod status {
+        use super::super::Status;
+
+        #[test]
+        fn deserialize() {
+            for (s, expected) in [
+                ("NOT_RECEIVED", Status::NotReceived),
+                ("RECEIVED", Status::Received),
+                ("PENDING", Status::Pending),
+                ("REJECTED", Status::Rejected),
+                ("ACCEPTED_ON_L1", Status::AcceptedOnL1),
+                ("ACCEPTED_ON_L2", Status::AcceptedOnL2),
+                ("REVERTED", Status::Reverted),
+                ("ABORTED", Status::Aborted),
+            ] {
+                let status = serde_json::from_str::<Status>(&format!("\"{}\"", s)).unwrap();
+                assert_eq!(status, expected, "{}", s);
+            }
+        }
+
+        #[test]
+        fn unknown_status_is_an_error() {
+            serde_json::from_str::<Status>(r#""ACCEPTED_ON_L3""#).unwrap_err();
+        }
+
+        #[test]
+        fn is_final() {
+            assert!(Status::AcceptedOnL1.is_final());
+
+            for status in [
+                Status::NotReceived,
+                Status::Received,
+                Status::Pending,
+                Status::Rejected,
+                Status::AcceptedOnL2,
+                Status::Reverted,
+                Status::Aborted,
+            ] {
+                assert!(!status.is_final(), "{:?}", status);
+            }
+        }
+    }
+
+    /// Deserializes `fixtures/sequencer/block.json`, a synthetic `get_block` reply containing a
+    /// deploy and an invoke transaction. It follows the shape of the feeder gateway replies, but
+    /// was written by hand: its hashes and roots are made up and don't belong to any real block.
+    mod block_fixture {
+        use super::*;
+        use crate::core::{
+            CallParam, ConstructorParam, ContractAddress, EventData, EventKey,
+            StarknetTransactionIndex, TransactionSignatureElem,
+        };
+        use pedersen::StarkHash;
+        use pretty_assertions::assert_eq;
+        use transaction::{execution_resources::BuiltinInstanceCounter, EntryPointType, Type};
+
+        fn block() -> Block {
+            serde_json::from_str(include_str!("../../fixtures/sequencer/block.json")).unwrap()
+        }
+
+        fn felt(hex: &str) -> StarkHash {
+            StarkHash::from_hex_str(hex).unwrap()
+        }
+
+        const CONTRACT: &str = "0x6fb6ee2e34e1e4c3a59e1d3ecb7b01d0ea5b1e9e1e6f5a4b3c2d1e0f9a8b7c6";
+
+        #[test]
+        fn header() {
+            let block = block();
+            assert_eq!(
+                block.block_hash,
+                Some(StarknetBlockHash(felt(
+                    "0x3fa1cfb3b8b0cb2ecb39e6bbbd5f8c1b2c1f2e29c3fcb70ca35b81d3b87c4a1"
+                )))
+            );
+            assert_eq!(block.block_number, Some(StarknetBlockNumber(1716)));
+            assert_eq!(
+                block.state_root,
+                Some(GlobalRoot(felt(
+                    "0235b3a3f9e3d4e2ad2d5a4e0bbcd29bb0b36d5bfb1c8b5dd6cf3b3ff91b8a2e"
+                )))
+            );
+            assert_eq!(block.status, Status::AcceptedOnL1);
+            assert_eq!(block.timestamp, 1640000000);
+            assert_eq!(block.transactions.len(), block.transaction_receipts.len());
+        }
+
+        #[test]
+        fn deploy_transaction() {
+            let tx = &block().transactions[0];
+            assert_eq!(tx.r#type, Type::Deploy);
+            assert_eq!(tx.contract_address, ContractAddress(felt(CONTRACT)));
+            assert_eq!(
+                tx.constructor_calldata,
+                Some(vec![ConstructorParam(
+                    StarkHash::from_be_slice(&[42]).unwrap()
+                )])
+            );
+            assert!(tx.contract_address_salt.is_some());
+            assert_eq!(tx.calldata, None);
+            assert_eq!(tx.entry_point_selector, None);
+        }
+
+        #[test]
+        fn invoke_transaction() {
+            let tx = &block().transactions[1];
+            assert_eq!(tx.r#type, Type::InvokeFunction);
+            assert_eq!(tx.entry_point_type, Some(EntryPointType::External));
+            assert_eq!(
+                tx.calldata,
+                Some(vec![
+                    CallParam(StarkHash::from_be_slice(&[5]).unwrap()),
+                    CallParam(StarkHash::from_be_slice(&[7]).unwrap()),
+                ])
+            );
+            assert_eq!(
+                tx.signature,
+                Some(vec![
+                    TransactionSignatureElem(StarkHash::from_be_slice(&[111]).unwrap()),
+                    TransactionSignatureElem(StarkHash::from_be_slice(&[222]).unwrap()),
+                ])
+            );
+            assert_eq!(tx.constructor_calldata, None);
+        }
+
+        #[test]
+        fn receipts() {
+            let block = block();
+
+            let deploy = &block.transaction_receipts[0];
+            assert_eq!(deploy.transaction_index, StarknetTransactionIndex(0));
+            assert_eq!(
+                deploy.transaction_hash,
+                block.transactions[0].transaction_hash
+            );
+            assert!(deploy.events.is_empty());
+            assert_eq!(
+                deploy.execution_resources.builtin_instance_counter,
+                BuiltinInstanceCounter::Empty(
+                    transaction::execution_resources::EmptyBuiltinInstanceCounter {}
+                )
+            );
+
+            let invoke = &block.transaction_receipts[1];
+            assert_eq!(invoke.transaction_index, StarknetTransactionIndex(1));
+            assert_eq!(invoke.execution_resources.n_steps, 310);
+            assert_eq!(invoke.execution_resources.n_memory_holes, 12);
+            assert_eq!(invoke.l2_to_l1_messages.len(), 1);
+            assert_eq!(invoke.l1_to_l2_consumed_message, None);
+        }
+
+        #[test]
+        fn event() {
+            let block = block();
+            let events = &block.transaction_receipts[1].events;
+            assert_eq!(
+                events,
+                &vec![transaction::Event {
+                    data: vec![
+                        EventData(StarkHash::from_be_slice(&[1]).unwrap()),
+                        EventData(StarkHash::from_be_slice(&[2]).unwrap()),
+                    ],
+                    from_address: ContractAddress(felt(CONTRACT)),
+                    keys: vec![EventKey(
+                        StarkHash::from_be_slice(&1234u16.to_be_bytes()).unwrap()
+                    )],
+                }]
+            );
+        }
+    }
+
+    /// Deserializes `fixtures/sequencer/pending_block.json`, a synthetic fixture written by hand
+    /// after the shape of a feeder gateway `get_block?blockNumber=pending` reply. Its hashes are
+    /// made up, and its parent is the equally synthetic `fixtures/sequencer/block.json`.
+    mod pending_block_fixture {
+        use super::*;
+        use pedersen::StarkHash;
+        use pretty_assertions::assert_eq;
+
+        const PENDING_BLOCK: &str = include_str!("../../fixtures/sequencer/pending_block.json");
+
+        #[test]
+        fn header() {
+            let block: PendingBlock = serde_json::from_str(PENDING_BLOCK).unwrap();
+            assert_eq!(
+                block.parent_block_hash,
+                StarknetBlockHash(
+                    StarkHash::from_hex_str(
+                        "0x5d7b0f2d3aa28d6d1a5a1e5c54d8aa6d70f6a9ab17c8d1a6f0ff5d16e4f96e7"
+                    )
+                    .unwrap()
+                )
+            );
+            assert_eq!(block.status, Status::Pending);
+            assert_eq!(block.timestamp, 1640000120);
+            assert_eq!(block.transactions.len(), 2);
+            assert_eq!(block.transactions.len(), block.transaction_receipts.len());
+        }
+
+        #[test]
+        fn is_not_a_block() {
+            // The pending block has none of the block_hash, block_number and state_root of a
+            // closed block, which are optional in Block.
+            let block: Block = serde_json::from_str(PENDING_BLOCK).unwrap();
+            assert_eq!(block.block_hash, None);
+            assert_eq!(block.block_number, None);
+            assert_eq!(block.state_root, None);
+        }
+
+        #[test]
+        fn closed_block_is_not_pending() {
+            serde_json::from_str::<PendingBlock>(include_str!(
+                "../../fixtures/sequencer/block.json"
+            ))
+            .unwrap_err();
+        }
+    }
+
+    /// Deserializes `fixtures/sequencer/state_update.json`, a synthetic fixture written by hand
+    /// after the shape of a newer feeder gateway `get_state_update` reply including a nonce
+    /// change. Its hashes, addresses and roots are made up.
+    mod state_update_fixture {
+        use super::state_update::{Contract, DeclaredClass, ReplacedClass, StorageDiff};
+        use super::*;
+        use crate::core::{
+            ContractAddress, ContractHash, ContractNonce, StorageAddress, StorageValue,
+        };
+        use pedersen::StarkHash;
+        use pretty_assertions::assert_eq;
+
+        fn felt(hex: &str) -> StarkHash {
+            StarkHash::from_hex_str(hex).unwrap()
+        }
+
+        const CONTRACT: &str = "0x6fb6ee2e34e1e4c3a59e1d3ecb7b01d0ea5b1e9e1e6f5a4b3c2d1e0f9a8b7c6";
+
+        fn state_update() -> StateUpdate {
+            serde_json::from_str(include_str!("../../fixtures/sequencer/state_update.json"))
+                .unwrap()
+        }
+
+        #[test]
+        fn roots() {
+            let update = state_update();
+            assert_eq!(
+                update.new_root,
+                GlobalRoot(felt(
+                    "0235b3a3f9e3d4e2ad2d5a4e0bbcd29bb0b36d5bfb1c8b5dd6cf3b3ff91b8a2e"
+                ))
+            );
+            assert_eq!(
+                update.old_root,
+                GlobalRoot(felt(
+                    "04ae5b3c1dfa1a9f02f2ec8e2d98c3d8b9d6a8f5e4c2b1a0f9e8d7c6b5a49382"
+                ))
+            );
+        }
+
+        #[test]
+        fn nonces() {
+            let diff = state_update().state_diff;
+            assert_eq!(diff.nonces.len(), 1);
+            assert_eq!(
+                diff.nonces[&ContractAddress(felt(CONTRACT))],
+                ContractNonce(felt("0x3"))
+            );
+        }
+
+        #[test]
+        fn storage_diffs() {
+            let diff = state_update().state_diff;
+            assert_eq!(
+                diff.storage_diffs[&ContractAddress(felt(CONTRACT))],
+                vec![StorageDiff {
+                    key: StorageAddress(felt("0x5")),
+                    value: StorageValue(felt("0x22b")),
+                }]
+            );
+        }
+
+        #[test]
+        fn classes() {
+            let diff = state_update().state_diff;
+            assert_eq!(
+                diff.deployed_contracts,
+                vec![Contract {
+                    address: ContractAddress(felt(
+                        "0x1c3f1b5e9a0d4e7c2b6a8f3d5e1c9b7a4f2e6d8c0b3a5f7e9d1c4b6a8e2f0d3"
+                    )),
+                    contract_hash: ContractHash(felt(
+                        "0x10455c752b86932ce552f2b0fe81a880746649b9aee7e0d842bf3f52378f9f8"
+                    )),
+                }]
+            );
+            assert_eq!(
+                diff.declared_contracts,
+                vec![ContractHash(felt(
+                    "0x7b40f8e3b4a6c0f2d1e5a9c8b7f6e5d4c3b2a1f0e9d8c7b6a5f4e3d2c1b0a99"
+                ))]
+            );
+            assert_eq!(
+                diff.declared_classes,
+                vec![DeclaredClass {
+                    class_hash: ContractHash(felt(
+                        "0x2d1e5a9c8b7f6e5d4c3b2a1f0e9d8c7b6a5f4e3d2c1b0a997b40f8e3b4a6c0f"
+                    )),
+                    compiled_class_hash: felt(
+                        "0x5c3b2a1f0e9d8c7b6a5f4e3d2c1b0a997b40f8e3b4a6c0f2d1e5a9c8b7f6e5d"
+                    ),
+                }]
+            );
+            assert_eq!(
+                diff.replaced_classes,
+                vec![ReplacedClass {
+                    address: ContractAddress(felt(CONTRACT)),
+                    class_hash: ContractHash(felt(
+                        "0x7b40f8e3b4a6c0f2d1e5a9c8b7f6e5d4c3b2a1f0e9d8c7b6a5f4e3d2c1b0a99"
+                    )),
+                }]
+            );
+        }
+
+        #[test]
+        fn older_replies_omit_new_fields() {
+            let update = serde_json::from_str::<StateUpdate>(
+                r#"{
+                    "new_root": "0x1",
+                    "old_root": "0x0",
+                    "state_diff": {
+                        "storage_diffs": {},
+                        "deployed_contracts": [{"address": "0x2", "contract_hash": "0x3"}]
+                    }
+                }"#,
+            )
+            .unwrap();
+            assert!(update.state_diff.nonces.is_empty());
+            assert!(update.state_diff.declared_contracts.is_empty());
+            assert!(update.state_diff.declared_classes.is_empty());
+            assert!(update.state_diff.replaced_classes.is_empty());
+        }
+    }
+}
@@ -1,9 +1,12 @@
 //! Structures used for serializing requests to Starkware's sequencer REST API.
 use crate::{
-    core::{CallParam, CallSignatureElem, ContractAddress, EntryPoint},
+    core::{
+        CallParam, CallSignatureElem, ContractAddress, EntryPoint, StarknetBlockHash,
+        StarknetBlockNumber,
+    },
     rpc::{
         serde::{CallParamAsDecimalStr, CallSignatureElemAsDecimalStr},
-        types::request as rpc,
+        types::{request as rpc, BlockHashOrTag, BlockNumberOrTag, Tag},
     },
 };
 use serde::Serialize;
@@ -33,3 +36,33 @@ impl From<rpc::Call> for Call {
         }
     }
 }
+
+/// Identifies a block in queries which accept either a block hash or a block number,
+/// such as [Client::state_update](crate::sequencer::Client::state_update).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum BlockId {
+    Hash(StarknetBlockHash),
+    Number(StarknetBlockNumber),
+    Latest,
+    Pending,
+}
+
+impl From<BlockHashOrTag> for BlockId {
+    fn from(block: BlockHashOrTag) -> Self {
+        match block {
+            BlockHashOrTag::Hash(hash) => BlockId::Hash(hash),
+            BlockHashOrTag::Tag(Tag::Latest) => BlockId::Latest,
+            BlockHashOrTag::Tag(Tag::Pending) => BlockId::Pending,
+        }
+    }
+}
+
+impl From<BlockNumberOrTag> for BlockId {
+    fn from(block: BlockNumberOrTag) -> Self {
+        match block {
+            BlockNumberOrTag::Number(number) => BlockId::Number(number),
+            BlockNumberOrTag::Tag(Tag::Latest) => BlockId::Latest,
+            BlockNumberOrTag::Tag(Tag::Pending) => BlockId::Pending,
+        }
+    }
+}
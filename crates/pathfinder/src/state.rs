@@ -15,7 +15,22 @@ pub(crate) mod merkle_tree;
 pub(crate) mod state_tree;
 mod sync;
 
-pub use contract_hash::compute_contract_hash;
+#[cfg(feature = "bench")]
+pub use contract_hash::bench as contract_hash_bench;
+pub use contract_hash::{
+    compute_abi_hash, compute_contract_address, compute_contract_hash,
+    compute_contract_hash_and_counts, compute_contract_hash_async, compute_contract_hash_with,
+    compute_event_hash, compute_non_canonical_contract_hash, contract_abi,
+    contract_hash_debug_info, contract_stats, contract_stats_with, extract_hints,
+    finalize_with_count, log_hash_debug_info, log_hash_mismatch, strip_non_hashed_fields,
+    ContractHasher, ContractStats, HashChainCounts, HashDebugInfo, NonCanonicalContractHash,
+    ParseMode, ParseOptions,
+};
+#[cfg(feature = "contract-tools")]
+pub use contract_hash::{
+    compute_contract_hashes_with_concurrency, compute_hashes_from_archive, export_hashes_jsonl,
+    hash_directory,
+};
 pub use sync::{sync, State as SyncState};
 
 #[derive(Clone)]
@@ -80,22 +95,31 @@ pub(crate) fn update_contract_state(
 
 /// Calculates the contract state hash from its preimage.
 fn calculate_contract_state_hash(hash: ContractHash, root: ContractRoot) -> ContractStateHash {
-    const RESERVED: StarkHash = StarkHash::ZERO;
+    // The nonce occupies the currently reserved, always zero, element.
+    ContractStateHash(contract_state_hash(hash, root.0, StarkHash::ZERO))
+}
+
+/// Calculates the contract state hash, which is the contribution of a contract to the global state
+/// tree, out of the contract's class hash, storage root and nonce.
+pub fn contract_state_hash(
+    class_hash: ContractHash,
+    storage_root: StarkHash,
+    nonce: StarkHash,
+) -> StarkHash {
     const CONTRACT_VERSION: StarkHash = StarkHash::ZERO;
 
-    // The contract state hash is defined as H(H(H(hash, root), RESERVED), CONTRACT_VERSION)
-    let hash = pedersen_hash(hash.0, root.0);
-    let hash = pedersen_hash(hash, RESERVED);
-    let hash = pedersen_hash(hash, CONTRACT_VERSION);
+    // The contract state hash is defined as H(H(H(hash, root), nonce), CONTRACT_VERSION)
+    let hash = pedersen_hash(class_hash.0, storage_root);
+    let hash = pedersen_hash(hash, nonce);
 
     // Compare this with the HashChain construction used in the contract_hash: the number of
     // elements is not hashed to this hash, and this is supposed to be different.
-    ContractStateHash(hash)
+    pedersen_hash(hash, CONTRACT_VERSION)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{calculate_contract_state_hash, sync};
+    use super::{calculate_contract_state_hash, contract_state_hash, sync};
     use crate::core::{ContractHash, ContractRoot, ContractStateHash};
     use pedersen::StarkHash;
 
@@ -122,6 +146,35 @@ mod tests {
         let result = calculate_contract_state_hash(hash, root);
 
         assert_eq!(result, expected);
+        assert_eq!(
+            contract_state_hash(hash, root.0, StarkHash::ZERO),
+            expected.0
+        );
+    }
+
+    #[test]
+    fn hash_with_nonce() {
+        let root = StarkHash::from_hex_str(
+            "04fb440e8ca9b74fc12a22ebffe0bc0658206337897226117b985434c239c028",
+        )
+        .unwrap();
+        let hash = ContractHash(
+            StarkHash::from_hex_str(
+                "02ff4903e17f87b298ded00c44bfeb22874c5f73be2ced8f1d9d9556fb509779",
+            )
+            .unwrap(),
+        );
+        let nonce = StarkHash::from_hex_str("0x5").unwrap();
+
+        // Computed with a Pedersen implementation independent of this crate, which reproduces
+        // the nonce-less vector of the test above.
+        let expected = StarkHash::from_hex_str(
+            "038b2eff7a08a26bca8c5c8ddbcffdc3d24e373d6d5e2a57f889bba3b994e6f9",
+        )
+        .unwrap();
+
+        let result = contract_state_hash(hash, root, nonce);
+        assert_eq!(result, expected);
     }
 
     #[test]
@@ -466,9 +519,21 @@ mod tests {
         let transport = crate::ethereum::test_transport(chain);
         let sequencer = crate::sequencer::Client::new(chain).unwrap();
         let state = std::sync::Arc::new(sync::State::default());
-
-        sync::sync(storage, transport, chain, sequencer, state)
-            .await
-            .unwrap();
+        let (_shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+
+        sync::sync(
+            storage,
+            transport,
+            chain,
+            sequencer,
+            state,
+            true,
+            crate::retry::RetryBudget::default(),
+            0,
+            Default::default(),
+            shutdown_rx,
+        )
+        .await
+        .unwrap();
     }
 }
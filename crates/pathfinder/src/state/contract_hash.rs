@@ -1,10 +1,19 @@
 use anyhow::{Context, Error, Result};
-use pedersen::{pedersen_hash, StarkHash};
+use pedersen::{pedersen_hash, HexParseError, StarkHash};
 use serde::Serialize;
 use sha3::Digest;
 
 use crate::core::ContractHash;
 
+#[cfg(feature = "contract-tools")]
+mod tools;
+
+#[cfg(feature = "contract-tools")]
+pub use tools::{
+    compute_contract_hashes_with_concurrency, compute_hashes_from_archive, export_hashes_jsonl,
+    hash_directory,
+};
+
 /// Computes the starknet contract hash for given contract definition json blob.
 ///
 /// The structure of the blob is not strictly defined, so it lives in privacy under `json` module
@@ -27,22 +36,220 @@ use crate::core::ContractHash;
 /// [cairo-compute]: https://github.com/starkware-libs/cairo-lang/blob/64a7f6aed9757d3d8d6c28bd972df73272b0cb0a/src/starkware/starknet/core/os/contract_hash.py
 /// [cairo-contract]: https://github.com/starkware-libs/cairo-lang/blob/64a7f6aed9757d3d8d6c28bd972df73272b0cb0a/src/starkware/starknet/core/os/contracts.cairo#L76-L118
 /// [py-sortkeys]: https://github.com/starkware-libs/cairo-lang/blob/64a7f6aed9757d3d8d6c28bd972df73272b0cb0a/src/starkware/starknet/core/os/contract_hash.py#L58-L71
+///
+/// A leading UTF-8 byte order mark and surrounding whitespace are ignored, as the hashed json is
+/// re-serialized from the parsed definition.
 pub fn compute_contract_hash(contract_definition_dump: &[u8]) -> Result<ContractHash> {
-    let contract_definition =
-        serde_json::from_slice::<json::ContractDefinition>(contract_definition_dump)
-            .context("Failed to parse contract_definition")?;
+    compute_contract_hash_with(contract_definition_dump, ParseOptions::default())
+}
+
+/// [`compute_contract_hash`] using the given [`ParseOptions`].
+pub fn compute_contract_hash_with(
+    contract_definition_dump: &[u8],
+    options: ParseOptions,
+) -> Result<ContractHash> {
+    let contract_definition = parse_contract_definition(contract_definition_dump, options)?;
 
     compute_contract_hash0(contract_definition).context("Compute contract hash")
 }
 
+/// The number of elements hashed into each of the inner hash chains of [`compute_contract_hash`].
+///
+/// Each entry point contributes two elements, its selector and offset. An unexpected count, for
+/// example zero builtins, often explains a mismatching hash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HashChainCounts {
+    /// Elements of the `EXTERNAL` entry points chain.
+    pub external: usize,
+    /// Elements of the `L1_HANDLER` entry points chain.
+    pub l1_handler: usize,
+    /// Elements of the `CONSTRUCTOR` entry points chain.
+    pub constructor: usize,
+    /// Elements of the builtins chain.
+    pub builtins: usize,
+    /// Elements of the bytecode chain.
+    pub bytecode: usize,
+}
+
+/// [`compute_contract_hash`] which also returns the [`HashChainCounts`] of the hash.
+pub fn compute_contract_hash_and_counts(
+    contract_definition_dump: &[u8],
+) -> Result<(ContractHash, HashChainCounts)> {
+    let mut contract_definition =
+        parse_contract_definition(contract_definition_dump, ParseOptions::default())?;
+    contract_definition.program.debug_info = None;
+
+    let truncated_keccak = compute_definition_keccak(&contract_definition)?;
+
+    compute_hash_chains_and_counts(&contract_definition, truncated_keccak)
+        .context("Compute contract hash")
+}
+
+/// Diagnostics of a contract hash computation, see [`contract_hash_debug_info`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HashDebugInfo {
+    /// Length in bytes of the serialized contract definition hashed with Keccak256. Compare it
+    /// to the length of the python serialization to find a wrongly serialized spot.
+    pub serialized_len: usize,
+    /// The number of elements in each of the hash chains.
+    pub counts: HashChainCounts,
+}
+
+/// Computes the [`HashDebugInfo`] of the contract definition, for diagnosing a mismatching hash.
+///
+/// This repeats most of the work of [`compute_contract_hash`] and buffers the whole serialized
+/// definition, so it should only be used once a mismatch is found.
+pub fn contract_hash_debug_info(
+    contract_definition_dump: &[u8],
+    options: ParseOptions,
+) -> Result<HashDebugInfo> {
+    let mut contract_definition = parse_contract_definition(contract_definition_dump, options)?;
+    contract_definition.program.debug_info = None;
+
+    let serialized = serialize_for_keccak(&contract_definition, Vec::new())?;
+    let truncated_keccak = truncated_keccak(<[u8; 32]>::from(sha3::Keccak256::digest(&serialized)));
+
+    let (_, counts) = compute_hash_chains_and_counts(&contract_definition, truncated_keccak)
+        .context("Compute contract hash")?;
+
+    Ok(HashDebugInfo {
+        serialized_len: serialized.len(),
+        counts,
+    })
+}
+
+/// Logs a structured warning of a contract whose computed hash does not match the `expected`
+/// one. Its [`HashDebugInfo`] can be logged afterwards with [`log_hash_debug_info`].
+pub fn log_hash_mismatch(
+    address: crate::core::ContractAddress,
+    expected: ContractHash,
+    computed: ContractHash,
+) {
+    tracing::warn!(
+        address = %address.0,
+        expected = %expected.0,
+        computed = %computed.0,
+        "Contract hash mismatch"
+    );
+}
+
+/// Logs the [`HashDebugInfo`] of a contract previously reported by [`log_hash_mismatch`].
+pub fn log_hash_debug_info(address: crate::core::ContractAddress, debug_info: &HashDebugInfo) {
+    tracing::warn!(
+        address = %address.0,
+        serialized_len = debug_info.serialized_len,
+        external = debug_info.counts.external,
+        l1_handler = debug_info.counts.l1_handler,
+        constructor = debug_info.counts.constructor,
+        builtins = debug_info.counts.builtins,
+        bytecode = debug_info.counts.bytecode,
+        "Contract hash mismatch debug info"
+    );
+}
+
+/// A contract hash computed by [`compute_non_canonical_contract_hash`], which leaves the ABI out.
+///
+/// This is __not__ the contract hash used by StarkNet and is kept as a separate type so that it
+/// cannot be mistaken for a [`ContractHash`]. Its [`Display`](std::fmt::Display) marks it as
+/// non-canonical.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NonCanonicalContractHash(pub StarkHash);
+
+impl std::fmt::Display for NonCanonicalContractHash {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (non-canonical)", self.0.to_hex_str())
+    }
+}
+
+/// Experimental: computes a structural hash of a contract definition whose ABI cannot be hashed,
+/// for example some very old contracts, for indexing purposes.
+///
+/// This is [`compute_contract_hash`] except that the truncated Keccak256 is only computed over the
+/// `program.builtins`, `program.data` and the entry points, leaving out the ABI and the rest of
+/// the program. The result never matches the real contract hash, see
+/// [`NonCanonicalContractHash`].
+pub fn compute_non_canonical_contract_hash(
+    contract_definition_dump: &[u8],
+) -> Result<NonCanonicalContractHash> {
+    let contract_definition =
+        parse_contract_definition(contract_definition_dump, ParseOptions::default())?;
+
+    let truncated_keccak = compute_structural_keccak(&contract_definition)?;
+
+    compute_hash_chains(&contract_definition, truncated_keccak)
+        .map(|hash| NonCanonicalContractHash(hash.0))
+        .context("Compute non-canonical contract hash")
+}
+
+/// [`compute_contract_hash`] on a blocking thread, so that hashing large contract definitions does
+/// not stall the async runtime.
+pub async fn compute_contract_hash_async(
+    contract_definition_dump: std::sync::Arc<[u8]>,
+) -> Result<ContractHash> {
+    tokio::task::spawn_blocking(move || compute_contract_hash(&contract_definition_dump))
+        .await
+        .context("Contract hash computation task")?
+}
+
+/// The stark field prime, which the `program.prime` of every StarkNet contract is expected to be.
+pub const STARK_PRIME: &str = "0x800000000000011000000000000000000000000000000000000000000000001";
+
+/// Controls how a contract definition is checked before hashing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Rejects definitions whose `program.prime` is not [`STARK_PRIME`]. Only a devnet with a
+    /// custom field would turn this off.
+    pub check_prime: bool,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions { check_prime: true }
+    }
+}
+
+/// Computes the contract hash of a contract definition which is written to it in parts, for
+/// example using [`std::io::copy`].
+///
+/// The hash can only be computed once the whole contract definition has been parsed, so this
+/// buffers all of the written bytes until [`ContractHasher::finalize`].
+#[derive(Debug, Default)]
+pub struct ContractHasher {
+    buffer: Vec<u8>,
+}
+
+impl ContractHasher {
+    /// Computes the [`compute_contract_hash`] of all of the bytes written so far.
+    pub fn finalize(self) -> Result<ContractHash> {
+        compute_contract_hash(&self.buffer)
+    }
+}
+
+impl std::io::Write for ContractHasher {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
 /// Sibling functionality to only [`compute_contract_hash`], returning also the ABI, and bytecode
 /// parts as json bytes.
 pub(crate) fn extract_abi_code_hash(
     contract_definition_dump: &[u8],
 ) -> Result<(Vec<u8>, Vec<u8>, ContractHash)> {
-    let contract_definition =
-        serde_json::from_slice::<json::ContractDefinition>(contract_definition_dump)
-            .context("Failed to parse contract_definition")?;
+    extract_abi_code_hash_with(contract_definition_dump, ParseOptions::default())
+}
+
+/// [`extract_abi_code_hash`] using the given [`ParseOptions`].
+pub(crate) fn extract_abi_code_hash_with(
+    contract_definition_dump: &[u8],
+    options: ParseOptions,
+) -> Result<(Vec<u8>, Vec<u8>, ContractHash)> {
+    let contract_definition = parse_contract_definition(contract_definition_dump, options)?;
 
     // just in case we'd accidentially modify these in the compute_contract_hash0
     let abi = serde_json::to_vec(&contract_definition.abi)
@@ -55,25 +262,411 @@ pub(crate) fn extract_abi_code_hash(
     Ok((abi, code, hash))
 }
 
+/// Fetches the full contract definition of the contract at `address` and returns only its ABI.
+///
+/// The sequencer does not offer an ABI-only endpoint, so the whole definition is downloaded but
+/// only the `abi` is parsed into a value; the program is skipped over.
+pub async fn contract_abi(
+    client: &crate::sequencer::Client,
+    address: crate::core::ContractAddress,
+) -> Result<serde_json::Value> {
+    #[derive(serde::Deserialize)]
+    struct AbiOnly {
+        abi: serde_json::Value,
+    }
+
+    let contract_definition = client
+        .full_contract(address)
+        .await
+        .context("Download contract definition")?;
+
+    let contract = serde_json::from_slice::<AbiOnly>(strip_bom(&contract_definition))
+        .context("Failed to parse contract_definition abi")?;
+
+    Ok(contract.abi)
+}
+
+/// Computes the [`truncated_keccak`] of only the `abi` of a contract definition, serialized the
+/// same way as for [`compute_contract_hash`].
+///
+/// The bytecode and the rest of the definition are ignored, so the hash changes only when the ABI
+/// does, which makes it suitable for watching contracts for ABI changes.
+pub fn compute_abi_hash(contract_definition_dump: &[u8]) -> Result<StarkHash> {
+    #[derive(serde::Deserialize)]
+    struct AbiOnly {
+        abi: serde_json::Value,
+    }
+
+    let contract = serde_json::from_slice::<AbiOnly>(strip_bom(contract_definition_dump))
+        .context("Failed to parse contract_definition abi")?;
+
+    let mut ser =
+        serde_json::Serializer::with_formatter(KeccakWriter::default(), PythonDefaultFormatter);
+    contract
+        .abi
+        .serialize(&mut ser)
+        .context("Serializing contract_definition.abi for Keccak256")?;
+    let KeccakWriter(hash) = ser.into_inner();
+
+    Ok(truncated_keccak(<[u8; 32]>::from(hash.finalize())))
+}
+
+/// Cheap statistics of a contract definition, see [`contract_stats`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContractStats {
+    /// Number of `EXTERNAL` entry points.
+    pub external_entry_points: usize,
+    /// Number of `L1_HANDLER` entry points.
+    pub l1_handler_entry_points: usize,
+    /// Number of `CONSTRUCTOR` entry points.
+    pub constructor_entry_points: usize,
+    /// Number of builtins used by the program.
+    pub builtins: usize,
+    /// Number of elements in the program bytecode (`program.data`).
+    pub bytecode_len: usize,
+    /// Number of `program.data` elements which repeat an earlier element verbatim.
+    ///
+    /// Repeated words are legal, this is purely informational and has no effect on the hash.
+    pub duplicate_data_entries: usize,
+    /// Size of the compact json serialization of the ABI in bytes.
+    pub abi_bytes: usize,
+}
+
+/// How strictly a contract definition is parsed by the metadata accessors like
+/// [`contract_stats_with`].
+///
+/// Hashing always uses [`ParseMode::Strict`], as silently ignoring a field would produce a wrong
+/// contract hash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseMode {
+    /// Unknown fields are rejected.
+    Strict,
+    /// Unknown fields and entry point types are ignored, so that the metadata of definitions with
+    /// newer, forward-compatible additions can still be read.
+    Lenient,
+}
+
+impl Default for ParseMode {
+    fn default() -> Self {
+        ParseMode::Strict
+    }
+}
+
+/// Computes [`ContractStats`] for given contract definition json blob, without hashing it.
+pub fn contract_stats(contract_definition_dump: &[u8]) -> Result<ContractStats> {
+    contract_stats_with(contract_definition_dump, ParseMode::Strict)
+}
+
+/// [`contract_stats`] using the given [`ParseMode`].
+pub fn contract_stats_with(
+    contract_definition_dump: &[u8],
+    mode: ParseMode,
+) -> Result<ContractStats> {
+    use json::EntryPointType::*;
+
+    if mode == ParseMode::Lenient {
+        return lenient_contract_stats(contract_definition_dump);
+    }
+
+    let contract_definition =
+        serde_json::from_slice::<json::ContractDefinition>(strip_bom(contract_definition_dump))
+            .context("Failed to parse contract_definition")?;
+
+    let entry_points = |key| {
+        contract_definition
+            .entry_points_by_type
+            .get(&key)
+            .map(Vec::len)
+            .unwrap_or_default()
+    };
+
+    let abi_bytes = serde_json::to_vec(&contract_definition.abi)
+        .context("Serialize contract_definition.abi")?
+        .len();
+
+    Ok(ContractStats {
+        external_entry_points: entry_points(External),
+        l1_handler_entry_points: entry_points(L1Handler),
+        constructor_entry_points: entry_points(Constructor),
+        builtins: contract_definition.program.builtins.len(),
+        bytecode_len: contract_definition.program.data.len(),
+        duplicate_data_entries: count_duplicates(&contract_definition.program.data),
+        abi_bytes,
+    })
+}
+
+/// Counts the elements of `items` which are equal to an earlier element.
+fn count_duplicates<T: std::hash::Hash + Eq>(items: &[T]) -> usize {
+    let mut seen = std::collections::HashSet::with_capacity(items.len());
+    items.iter().filter(|item| !seen.insert(*item)).count()
+}
+
+/// [`contract_stats`] for [`ParseMode::Lenient`], parsing only the parts of the definition which
+/// the statistics need.
+fn lenient_contract_stats(contract_definition_dump: &[u8]) -> Result<ContractStats> {
+    use serde::de::IgnoredAny;
+    use std::collections::HashMap;
+
+    #[derive(serde::Deserialize)]
+    struct ContractDefinition {
+        abi: serde_json::Value,
+        program: Program,
+        #[serde(default)]
+        entry_points_by_type: HashMap<String, Vec<IgnoredAny>>,
+    }
+
+    #[derive(serde::Deserialize)]
+    struct Program {
+        #[serde(default)]
+        builtins: Vec<IgnoredAny>,
+        #[serde(default)]
+        data: Vec<String>,
+    }
+
+    let contract_definition =
+        serde_json::from_slice::<ContractDefinition>(strip_bom(contract_definition_dump))
+            .context("Failed to parse contract_definition")?;
+
+    let entry_points = |key| {
+        contract_definition
+            .entry_points_by_type
+            .get(key)
+            .map(Vec::len)
+            .unwrap_or_default()
+    };
+
+    let abi_bytes = serde_json::to_vec(&contract_definition.abi)
+        .context("Serialize contract_definition.abi")?
+        .len();
+
+    Ok(ContractStats {
+        external_entry_points: entry_points("EXTERNAL"),
+        l1_handler_entry_points: entry_points("L1_HANDLER"),
+        constructor_entry_points: entry_points("CONSTRUCTOR"),
+        builtins: contract_definition.program.builtins.len(),
+        bytecode_len: contract_definition.program.data.len(),
+        duplicate_data_entries: count_duplicates(&contract_definition.program.data),
+        abi_bytes,
+    })
+}
+
+/// Extracts the `program.hints` of a contract definition, keyed by the program counter (the index
+/// into `program.data`) they apply to, in numeric order.
+///
+/// Errors if any hint is for a program counter beyond the end of the bytecode, which indicates a
+/// corrupt contract definition.
+pub fn extract_hints(
+    contract_definition_dump: &[u8],
+) -> Result<std::collections::BTreeMap<u64, Vec<serde_json::Value>>> {
+    let contract_definition =
+        parse_contract_definition(contract_definition_dump, ParseOptions::default())?;
+    let program = contract_definition.program;
+
+    let bytecode_len = program.data.len() as u64;
+    if let Some((&pc, _)) = program.hints.range(bytecode_len..).next() {
+        anyhow::bail!(
+            "Hint for program counter {} is out of range of the bytecode of {} elements",
+            pc,
+            bytecode_len
+        );
+    }
+
+    Ok(program.hints)
+}
+
+/// Returns the contract definition normalized the way it is serialized for the Keccak256 of
+/// [`compute_contract_hash`]: `program.debug_info` is nulled, empty `program.attributes` are
+/// left out, and keys are sorted and formatted as python's `json.dumps` would. The
+/// `entry_points_by_type` are left out as well, as they are hashed separately.
+pub fn strip_non_hashed_fields(contract_definition_dump: &[u8]) -> Result<Vec<u8>> {
+    let mut contract_definition =
+        parse_contract_definition(contract_definition_dump, ParseOptions::default())?;
+    contract_definition.program.debug_info = None;
+
+    serialize_for_keccak(&contract_definition, Vec::new())
+}
+
+/// Entry points for `benches/contract_hash.rs`, allowing the phases of [`compute_contract_hash`]
+/// to be measured separately. All of these include the parsing of the blob.
+#[cfg(feature = "bench")]
+pub mod bench {
+    use super::*;
+
+    /// Only parses the contract definition.
+    pub fn parse(contract_definition_dump: &[u8]) -> Result<()> {
+        serde_json::from_slice::<json::ContractDefinition>(contract_definition_dump)
+            .context("Failed to parse contract_definition")?;
+        Ok(())
+    }
+
+    /// Parses and computes the truncated Keccak256 over the sorted serialization.
+    pub fn keccak(contract_definition_dump: &[u8]) -> Result<StarkHash> {
+        let mut contract_definition =
+            serde_json::from_slice::<json::ContractDefinition>(contract_definition_dump)
+                .context("Failed to parse contract_definition")?;
+        contract_definition.program.debug_info = None;
+
+        compute_definition_keccak(&contract_definition)
+    }
+
+    /// Parses and computes the [`pedersen_hash`] chains, using zero in place of the Keccak256.
+    pub fn hash_chains(contract_definition_dump: &[u8]) -> Result<ContractHash> {
+        let contract_definition =
+            serde_json::from_slice::<json::ContractDefinition>(contract_definition_dump)
+                .context("Failed to parse contract_definition")?;
+
+        compute_hash_chains(&contract_definition, StarkHash::ZERO)
+    }
+}
+
+/// Strips the UTF-8 byte order mark some sources prepend, which [serde_json] rejects.
+fn strip_bom(contract_definition_dump: &[u8]) -> &[u8] {
+    const BOM: &[u8] = b"\xEF\xBB\xBF";
+    contract_definition_dump
+        .strip_prefix(BOM)
+        .unwrap_or(contract_definition_dump)
+}
+
+/// Parses the contract definition and applies the checks enabled in `options`.
+fn parse_contract_definition(
+    contract_definition_dump: &[u8],
+    options: ParseOptions,
+) -> Result<json::ContractDefinition<'_>> {
+    let contract_definition =
+        serde_json::from_slice::<json::ContractDefinition>(strip_bom(contract_definition_dump))
+            .context("Failed to parse contract_definition")?;
+
+    if options.check_prime {
+        check_prime(&contract_definition.program.prime)?;
+    }
+
+    Ok(contract_definition)
+}
+
+/// Errors if `prime` is not [`STARK_PRIME`], ignoring the case and leading zeros of the hex digits.
+fn check_prime(prime: &str) -> Result<()> {
+    fn digits(hex: &str) -> &str {
+        let hex = hex.strip_prefix("0x").unwrap_or(hex);
+        hex.trim_start_matches('0')
+    }
+
+    anyhow::ensure!(
+        digits(prime).eq_ignore_ascii_case(digits(STARK_PRIME)),
+        "Unexpected program prime {}, expected {}",
+        prime,
+        STARK_PRIME
+    );
+
+    Ok(())
+}
+
+/// Errors on an empty `program.data`, which is almost always a truncated or otherwise corrupt
+/// download. `code` is the serialized program data, as returned by [`extract_abi_code_hash`].
+pub(crate) fn check_program_data(code: &[u8]) -> Result<()> {
+    let data = serde_json::from_slice::<Vec<serde::de::IgnoredAny>>(code)
+        .context("Deserialize program data")?;
+    anyhow::ensure!(!data.is_empty(), "Empty program data");
+
+    Ok(())
+}
+
 fn compute_contract_hash0(
     mut contract_definition: json::ContractDefinition<'_>,
 ) -> Result<ContractHash> {
-    use json::EntryPointType::*;
-
     // the other modification is handled by skipping if the attributes vec is empty
     contract_definition.program.debug_info = None;
 
-    let truncated_keccak = {
-        let mut ser =
-            serde_json::Serializer::with_formatter(KeccakWriter::default(), PythonDefaultFormatter);
+    let truncated_keccak = compute_definition_keccak(&contract_definition)?;
 
-        contract_definition
-            .serialize(&mut ser)
-            .context("Serializing contract_definition for Keccak256")?;
+    compute_hash_chains(&contract_definition, truncated_keccak)
+}
 
-        let KeccakWriter(hash) = ser.into_inner();
-        truncated_keccak(<[u8; 32]>::from(hash.finalize()))
-    };
+/// First phase of [`compute_contract_hash`]: the truncated Keccak256 over the python-style sorted
+/// serialization of the contract definition. Expects `program.debug_info` to be already removed.
+fn compute_definition_keccak(
+    contract_definition: &json::ContractDefinition<'_>,
+) -> Result<StarkHash> {
+    let KeccakWriter(hash) = serialize_for_keccak(contract_definition, KeccakWriter::default())?;
+    Ok(truncated_keccak(<[u8; 32]>::from(hash.finalize())))
+}
+
+/// The truncated Keccak256 of [`compute_non_canonical_contract_hash`], over the python-style
+/// serialization of only the builtins, bytecode and entry points.
+fn compute_structural_keccak(
+    contract_definition: &json::ContractDefinition<'_>,
+) -> Result<StarkHash> {
+    // serde_json::Map is sorted, as the preserve_order feature is disabled
+    let entry_points_by_type = contract_definition
+        .entry_points_by_type
+        .iter()
+        .map(|(key, entry_points)| {
+            let entry_points = entry_points
+                .iter()
+                .map(|entry_point| {
+                    serde_json::json!({
+                        "offset": entry_point.offset,
+                        "selector": entry_point.selector,
+                    })
+                })
+                .collect::<Vec<_>>();
+            (key.to_string(), serde_json::Value::from(entry_points))
+        })
+        .collect::<serde_json::Map<_, _>>();
+
+    let structure = serde_json::json!({
+        "builtins": contract_definition.program.builtins,
+        "data": contract_definition.program.data,
+        "entry_points_by_type": entry_points_by_type,
+    });
+
+    let mut ser =
+        serde_json::Serializer::with_formatter(KeccakWriter::default(), PythonDefaultFormatter);
+    structure
+        .serialize(&mut ser)
+        .context("Serializing contract structure for Keccak256")?;
+    let KeccakWriter(hash) = ser.into_inner();
+
+    Ok(truncated_keccak(<[u8; 32]>::from(hash.finalize())))
+}
+
+/// Writes the python-style sorted serialization hashed by [`compute_definition_keccak`] to
+/// `writer`.
+fn serialize_for_keccak<W: std::io::Write>(
+    contract_definition: &json::ContractDefinition<'_>,
+    writer: W,
+) -> Result<W> {
+    #[cfg(debug_assertions)]
+    if !object_keys_sorted(contract_definition) {
+        tracing::warn!(
+            "Contract definition object keys are not sorted, the contract hash will be wrong. \
+            Was serde_json built with the preserve_order feature?"
+        );
+    }
+
+    let mut ser = serde_json::Serializer::with_formatter(writer, PythonDefaultFormatter);
+
+    contract_definition
+        .serialize(&mut ser)
+        .context("Serializing contract_definition for Keccak256")?;
+
+    Ok(ser.into_inner())
+}
+
+/// Second phase of [`compute_contract_hash`]: the [`pedersen_hash`] based hash chains over the
+/// entry points, builtins, the `truncated_keccak` of the first phase and the bytecode.
+fn compute_hash_chains(
+    contract_definition: &json::ContractDefinition<'_>,
+    truncated_keccak: StarkHash,
+) -> Result<ContractHash> {
+    compute_hash_chains_and_counts(contract_definition, truncated_keccak).map(|(hash, _)| hash)
+}
+
+/// [`compute_hash_chains`] which also returns the [`HashChainCounts`] of the inner chains.
+fn compute_hash_chains_and_counts(
+    contract_definition: &json::ContractDefinition<'_>,
+    truncated_keccak: StarkHash,
+) -> Result<(ContractHash, HashChainCounts)> {
+    use json::EntryPointType::*;
 
     // what follows is defined over at the contract.cairo
 
@@ -89,9 +682,13 @@ fn compute_contract_hash0(
     // It is important process the different entrypoint hashchains in correct order.
     // Each of the entrypoint lists gets updated into the `outer` hashchain.
     //
+    // A missing entry point type is the same as an empty list of them (for example many contracts
+    // have no constructor): the empty chain still contributes its `H(0, 0)` into `outer`.
+    //
     // This implementation doesn't preparse the strings, which makes it a bit more noisy. Late
     // parsing is made in an attempt to lean on the one big string allocation we've already got,
     // but these three hash chains could be constructed at deserialization time.
+    let mut entry_point_counts = [0; 3];
     [External, L1Handler, Constructor]
         .iter()
         .map(|key| {
@@ -114,8 +711,13 @@ fn compute_contract_hash0(
                         })
                         .map(move |res| {
                             res.and_then(|(field, hex)| {
-                                StarkHash::from_hex_str(hex).with_context(|| {
-                                    format!("Entry point invalid hex under {key} at index {i} entry ({field})")
+                                StarkHash::from_hex_str(hex).map_err(|e| match e {
+                                    HexParseError::Overflow => anyhow::anyhow!(
+                                        "Entry point {field} out of field range under {key} at index {i}"
+                                    ),
+                                    e => Error::new(e).context(format!(
+                                        "Entry point invalid hex under {key} at index {i} entry ({field})"
+                                    )),
                                 })
                             })
                         })
@@ -125,8 +727,11 @@ fn compute_contract_hash0(
                     Result::<_, Error>::Ok(hc)
                 })
         })
-        .try_for_each(|x| {
-            outer.update(x?.finalize());
+        .zip(entry_point_counts.iter_mut())
+        .try_for_each(|(x, count)| {
+            let x = x?;
+            *count = x.count;
+            outer.update(x.finalize());
             Result::<_, Error>::Ok(())
         })
         .context("Failed to process contract_definition.entry_points_by_type")?;
@@ -136,9 +741,23 @@ fn compute_contract_hash0(
         .builtins
         .iter()
         .enumerate()
-        .map(|(i, s)| (i, s.as_bytes()))
         .map(|(i, s)| {
-            StarkHash::from_be_slice(s).with_context(|| format!("Invalid builtin at index {i}"))
+            // builtin names are encoded as short strings: big-endian ascii bytes in a single felt
+            anyhow::ensure!(
+                s.is_ascii(),
+                "Builtin {:?} at index {} contains non-ASCII characters",
+                s,
+                i
+            );
+            anyhow::ensure!(
+                s.len() <= 31,
+                "Builtin {:?} at index {} is {} bytes long, at most 31 bytes are supported",
+                s,
+                i,
+                s.len()
+            );
+            StarkHash::from_be_slice(s.as_bytes())
+                .with_context(|| format!("Invalid builtin at index {i}"))
         })
         .try_fold(HashChain::default(), |mut hc, next| {
             hc.update(next?);
@@ -146,6 +765,7 @@ fn compute_contract_hash0(
         })
         .context("Failed to process contract_definition.program.builtins")?;
 
+    let builtins_count = builtins.count;
     outer.update(builtins.finalize());
 
     outer.update(truncated_keccak);
@@ -164,35 +784,131 @@ fn compute_contract_hash0(
         })
         .context("Failed to process contract_definition.program.data")?;
 
+    let counts = HashChainCounts {
+        external: entry_point_counts[0],
+        l1_handler: entry_point_counts[1],
+        constructor: entry_point_counts[2],
+        builtins: builtins_count,
+        bytecode: bytecodes.count,
+    };
     outer.update(bytecodes.finalize());
 
-    Ok(ContractHash(outer.finalize()))
+    Ok((ContractHash(outer.finalize()), counts))
+}
+
+/// Computes the address of a contract deployed by `deployer` (zero for `DEPLOY` transactions) with
+/// the given `salt`, `class_hash` and constructor `calldata`.
+///
+/// The address is the hash chain of the `STARKNET_CONTRACT_ADDRESS` prefix, `deployer`, `salt`,
+/// `class_hash` and the hash chain of `calldata`, as in [cairo-lang][cairo-address]. Like there,
+/// the result is taken modulo `2**251`.
+///
+/// [cairo-address]: https://github.com/starkware-libs/cairo-lang/blob/64a7f6aed9757d3d8d6c28bd972df73272b0cb0a/src/starkware/starknet/core/os/contract_address/contract_address.py
+pub fn compute_contract_address(
+    deployer: crate::core::ContractAddress,
+    salt: StarkHash,
+    class_hash: ContractHash,
+    calldata: &[StarkHash],
+) -> crate::core::ContractAddress {
+    let prefix = StarkHash::from_be_slice(b"STARKNET_CONTRACT_ADDRESS")
+        .expect("prefix is smaller than 251-bits");
+
+    let mut calldata_hash = HashChain::default();
+    calldata
+        .iter()
+        .for_each(|&value| calldata_hash.update(value));
+
+    let mut address = HashChain::default();
+    address.update(prefix);
+    address.update(deployer.0);
+    address.update(salt);
+    address.update(class_hash.0);
+    address.update(calldata_hash.finalize());
+
+    // The field modulus is less than 2**252, so taking the value modulo 2**251 only needs to
+    // clear the 252nd bit.
+    let mut address = address.finalize().to_be_bytes();
+    address[0] &= 0x07;
+    let address = StarkHash::from_be_bytes(address).expect("smaller than the value it came from");
+
+    crate::core::ContractAddress(address)
+}
+
+/// Computes the hash of an event emitted by `from_address` with the given `keys` and `data`.
+///
+/// The hash is the hash chain of `from_address`, the hash chain of `keys` and the hash chain of
+/// `data`, which is what cairo-lang commits to for each event of a block.
+pub fn compute_event_hash(
+    from_address: crate::core::ContractAddress,
+    keys: &[StarkHash],
+    data: &[StarkHash],
+) -> StarkHash {
+    let mut keys_hash = HashChain::default();
+    keys.iter().for_each(|&key| keys_hash.update(key));
+
+    let mut data_hash = HashChain::default();
+    data.iter().for_each(|&value| data_hash.update(value));
+
+    let mut event = HashChain::default();
+    event.update(from_address.0);
+    event.update(keys_hash.finalize());
+    event.update(data_hash.finalize());
+    event.finalize()
+}
+
+/// A two-to-one hash function over field elements, used as the `H` of [`GenericHashChain`].
+trait FeltHasher {
+    fn hash(a: StarkHash, b: StarkHash) -> StarkHash;
+}
+
+/// [`FeltHasher`] using the [`pedersen_hash`] function, as required by the contract hash.
+struct PedersenHasher;
+
+impl FeltHasher for PedersenHasher {
+    fn hash(a: StarkHash, b: StarkHash) -> StarkHash {
+        pedersen_hash(a, b)
+    }
 }
 
+/// The [`GenericHashChain`] used for the contract hash.
+type HashChain = GenericHashChain<PedersenHasher>;
+
 /// HashChain is the structure used over at cairo side to represent the hash construction needed
 /// for computing the contract hash.
 ///
-/// Empty hash chained value equals `H(0, 0)` where `H` is the [`pedersen_hash`] function, and the
-/// second value is the number of values hashed together in this chain. For other values, the
-/// accumulator is on each update replaced with the `H(hash, value)` and the number of count
-/// incremented by one.
-struct HashChain {
+/// Empty hash chained value equals `H(0, 0)` where `H` is the [`FeltHasher`] (for the contract
+/// hash the [`pedersen_hash`] function), and the second value is the number of values hashed
+/// together in this chain. For other values, the accumulator is on each update replaced with the
+/// `H(hash, value)` and the number of count incremented by one.
+struct GenericHashChain<H: FeltHasher> {
     hash: StarkHash,
     count: usize,
+    hasher: std::marker::PhantomData<H>,
 }
 
-impl Default for HashChain {
+impl<H: FeltHasher> Default for GenericHashChain<H> {
     fn default() -> Self {
-        HashChain {
+        GenericHashChain {
             hash: StarkHash::ZERO,
             count: 0,
+            hasher: std::marker::PhantomData,
         }
     }
 }
 
-impl HashChain {
+impl<H: FeltHasher> GenericHashChain<H> {
+    /// Resumes a chain from an accumulator `hash` over `count` values, for example one computed
+    /// elsewhere.
+    fn from_parts(hash: StarkHash, count: usize) -> Self {
+        GenericHashChain {
+            hash,
+            count,
+            hasher: std::marker::PhantomData,
+        }
+    }
+
     fn update(&mut self, value: StarkHash) {
-        self.hash = pedersen_hash(self.hash, value);
+        self.hash = H::hash(self.hash, value);
         self.count = self
             .count
             .checked_add(1)
@@ -202,17 +918,65 @@ impl HashChain {
     fn finalize(self) -> StarkHash {
         let count = StarkHash::from_be_slice(&self.count.to_be_bytes())
             .expect("usize is smaller than 251-bits");
-        pedersen_hash(self.hash, count)
+        H::hash(self.hash, count)
     }
 }
 
+/// Applies only the final step of the [`pedersen_hash`] chain used for contract hashes: the hash of
+/// the accumulator `hash` of `count` values with the `count`.
+///
+/// Useful to cross-check chains computed elsewhere, which only expose their accumulator.
+pub fn finalize_with_count(hash: StarkHash, count: usize) -> StarkHash {
+    HashChain::from_parts(hash, count).finalize()
+}
+
+/// The top byte mask of [`truncated_keccak`]: python code masks with (2**250 - 1) which starts
+/// 0x03 and is followed by 31 0xff in be.
+const KECCAK_TOP_BYTE_MASK: u8 = 0x03;
+
 /// See:
 /// <https://github.com/starkware-libs/cairo-lang/blob/64a7f6aed9757d3d8d6c28bd972df73272b0cb0a/src/starkware/starknet/public/abi.py#L21-L26>
-pub(crate) fn truncated_keccak(mut plain: [u8; 32]) -> StarkHash {
-    // python code masks with (2**250 - 1) which starts 0x03 and is followed by 31 0xff in be
+pub(crate) fn truncated_keccak(plain: [u8; 32]) -> StarkHash {
     // truncation is needed not to overflow the field element.
-    plain[0] &= 0x03;
-    StarkHash::from_be_bytes(plain).expect("cannot overflow: smaller than modulus")
+    let masked = mask_keccak(plain, KECCAK_TOP_BYTE_MASK);
+    StarkHash::from_be_bytes(masked).expect("cannot overflow: smaller than modulus")
+}
+
+/// Masks the top byte of the keccak digest `plain` with `top_byte_mask`.
+///
+/// [`truncated_keccak`] uses the standard mask; `0xff` leaves the digest untouched, which helps
+/// telling apart truncation differences from serialization differences when comparing against
+/// other hashers. Anything but the standard mask may not fit a field element.
+fn mask_keccak(mut plain: [u8; 32], top_byte_mask: u8) -> [u8; 32] {
+    plain[0] &= top_byte_mask;
+    plain
+}
+
+/// Checks that all of the json objects within the contract definition iterate (and so serialize)
+/// their keys in sorted order, as python's `sort_keys=True` would output them. This holds unless
+/// serde_json feature `preserve_order` has been enabled by accident.
+///
+/// Arrays are left as is, they must not be sorted.
+#[cfg(any(debug_assertions, test))]
+fn object_keys_sorted(contract_definition: &json::ContractDefinition<'_>) -> bool {
+    fn sorted(value: &serde_json::Value) -> bool {
+        use serde_json::Value::*;
+        match value {
+            Object(map) => {
+                map.keys().zip(map.keys().skip(1)).all(|(a, b)| a < b) && map.values().all(sorted)
+            }
+            Array(values) => values.iter().all(sorted),
+            _ => true,
+        }
+    }
+
+    let program = &contract_definition.program;
+
+    sorted(&contract_definition.abi)
+        && program.attributes.iter().all(sorted)
+        && program.hints.values().flatten().all(sorted)
+        && sorted(&program.identifiers)
+        && sorted(&program.reference_manager)
 }
 
 /// `std::io::Write` adapter for Keccak256; we don't need the serialized version in
@@ -403,29 +1167,22 @@ mod json {
             )
             .unwrap();
 
-            // this is quite big payload, ~500kB
-            let resp = reqwest::get("https://external.integration.starknet.io/feeder_gateway/get_full_contract?contractAddress=0x4ae0618c330c59559a59a27d143dd1c07cd74cf4e5e5a7cd85d53c6bf0e89dc")
-                .await
-                .unwrap();
-
-            let payload = resp.text().await.expect("response wasn't a string");
+            // this is quite big payload, ~500kB, so it is cached between runs
+            let payload = crate::test_utils::cached_get("https://external.integration.starknet.io/feeder_gateway/get_full_contract?contractAddress=0x4ae0618c330c59559a59a27d143dd1c07cd74cf4e5e5a7cd85d53c6bf0e89dc")
+                .await;
 
             // for bad urls the response looks like:
             // 500
             // {"code": "StarknetErrorCode.UNINITIALIZED_CONTRACT", "message": "Contract with address 2116724861677265616176388745625154424116334641142188761834194304782006389228 is not deployed."}
 
-            let hash = super::super::compute_contract_hash(payload.as_bytes()).unwrap();
+            let hash = super::super::compute_contract_hash(&payload).unwrap();
 
             assert_eq!(hash.0, expected);
         }
 
         #[test]
         fn second() {
-            let contract_definition = zstd::decode_all(
-                // opening up a file requires a path relative to the test running
-                &include_bytes!("../../fixtures/contract_definition.json.zst")[..],
-            )
-            .unwrap();
+            let contract_definition = crate::test_utils::contract_definition_fixture();
 
             let hash = super::super::compute_contract_hash(&contract_definition).unwrap();
 
@@ -440,19 +1197,9 @@ mod json {
 
         #[tokio::test]
         async fn genesis_contract() {
-            use pedersen::StarkHash;
-            let contract = StarkHash::from_hex_str(
-                "0x0546BA9763D33DC59A070C0D87D94F2DCAFA82C4A93B5E2BF5AE458B0013A9D3",
-            )
-            .unwrap();
-            let contract = crate::core::ContractAddress(contract);
-
-            let chain = crate::ethereum::Chain::Goerli;
-            let sequencer = crate::sequencer::Client::new(chain).unwrap();
-            let contract_definition = sequencer
-                .full_contract(contract)
-                .await
-                .expect("Download contract from sequencer");
+            // Goerli sequencer, cached between runs
+            let contract_definition = crate::test_utils::cached_get("https://alpha4.starknet.io/feeder_gateway/get_full_contract?contractAddress=0x0546ba9763d33dc59a070c0d87d94f2dcafa82c4a93b5e2bf5ae458b0013a9d3")
+                .await;
 
             let _ = crate::state::contract_hash::compute_contract_hash(&contract_definition)
                 .expect("Extract and compute  hash");
@@ -531,4 +1278,960 @@ mod tests {
             .unwrap()
         );
     }
+
+    mod abi_hash {
+        use super::super::compute_abi_hash;
+
+        fn fixture() -> serde_json::Value {
+            let contract_definition = crate::test_utils::contract_definition_fixture();
+            serde_json::from_slice(&contract_definition).unwrap()
+        }
+
+        fn abi_hash(contract_definition: &serde_json::Value) -> pedersen::StarkHash {
+            compute_abi_hash(&serde_json::to_vec(contract_definition).unwrap()).unwrap()
+        }
+
+        #[test]
+        fn stable_across_bytecode_change() {
+            let original = fixture();
+
+            let mut changed = original.clone();
+            changed["program"]["data"][0] = serde_json::json!("0x1234");
+            changed["program"]["data"]
+                .as_array_mut()
+                .unwrap()
+                .push(serde_json::json!("0x5678"));
+
+            assert_eq!(abi_hash(&changed), abi_hash(&original));
+        }
+
+        #[test]
+        fn changes_with_abi() {
+            let original = fixture();
+
+            let mut changed = original.clone();
+            changed["abi"][0]["name"] = serde_json::json!("renamed");
+
+            assert_ne!(abi_hash(&changed), abi_hash(&original));
+        }
+
+        #[test]
+        fn missing_abi() {
+            compute_abi_hash(br#"{"program": {}}"#).unwrap_err();
+        }
+    }
+
+    #[test]
+    fn masked_and_unmasked_keccak() {
+        use super::{mask_keccak, truncated_keccak, KECCAK_TOP_BYTE_MASK};
+        use sha3::{Digest, Keccak256};
+
+        let all_set = <[u8; 32]>::from(Keccak256::digest(&[0xffu8; 32]));
+
+        let masked = mask_keccak(all_set, KECCAK_TOP_BYTE_MASK);
+        let unmasked = mask_keccak(all_set, 0xff);
+
+        assert_eq!(masked, truncated_keccak(all_set).to_be_bytes());
+        assert_eq!(unmasked, all_set);
+
+        // only the top bits differ
+        assert_ne!(masked, unmasked);
+        assert_eq!(masked[0], unmasked[0] & 0x03);
+        assert_eq!(masked[1..], unmasked[1..]);
+    }
+
+    mod empty_entry_points {
+        use super::super::compute_contract_hash;
+        use crate::test_utils::minimal_contract;
+
+        #[test]
+        fn missing_keys_hash_deterministically() {
+            let contract = minimal_contract("{}");
+
+            let first = compute_contract_hash(&contract).unwrap();
+            let second = compute_contract_hash(&contract).unwrap();
+
+            assert_eq!(first, second);
+        }
+
+        #[test]
+        fn missing_keys_equal_empty_lists() {
+            let missing = compute_contract_hash(&minimal_contract("{}")).unwrap();
+            let empty = compute_contract_hash(&minimal_contract(
+                r#"{"CONSTRUCTOR": [], "EXTERNAL": [], "L1_HANDLER": []}"#,
+            ))
+            .unwrap();
+
+            assert_eq!(missing, empty);
+        }
+
+        #[test]
+        fn entry_point_changes_hash() {
+            let empty = compute_contract_hash(&minimal_contract("{}")).unwrap();
+            let constructor = compute_contract_hash(&minimal_contract(
+                r#"{"CONSTRUCTOR": [{"selector": "0x1", "offset": "0x0"}]}"#,
+            ))
+            .unwrap();
+
+            assert_ne!(empty, constructor);
+        }
+    }
+
+    mod non_canonical_hash {
+        use super::super::{compute_contract_hash, compute_non_canonical_contract_hash};
+        use crate::test_utils::minimal_contract;
+
+        const ENTRY_POINTS: &str = r#"{"EXTERNAL": [{"selector": "0x1", "offset": "0x2"}]}"#;
+
+        fn with_abi(abi: &str) -> Vec<u8> {
+            String::from_utf8(minimal_contract(ENTRY_POINTS))
+                .unwrap()
+                .replacen(r#""abi": []"#, &format!(r#""abi": {}"#, abi), 1)
+                .into_bytes()
+        }
+
+        #[test]
+        fn differs_from_contract_hash() {
+            let contract = minimal_contract(ENTRY_POINTS);
+
+            let canonical = compute_contract_hash(&contract).unwrap();
+            let non_canonical = compute_non_canonical_contract_hash(&contract).unwrap();
+
+            assert_ne!(canonical.0, non_canonical.0);
+        }
+
+        #[test]
+        fn ignores_abi() {
+            let without = with_abi("[]");
+            let with = with_abi(r#"[{"name": "foo", "type": "function", "inputs": []}]"#);
+
+            assert_ne!(
+                compute_contract_hash(&without).unwrap(),
+                compute_contract_hash(&with).unwrap()
+            );
+            assert_eq!(
+                compute_non_canonical_contract_hash(&without).unwrap(),
+                compute_non_canonical_contract_hash(&with).unwrap()
+            );
+        }
+
+        #[test]
+        fn depends_on_entry_points() {
+            let other = minimal_contract(r#"{"EXTERNAL": [{"selector": "0x1", "offset": "0x3"}]}"#);
+
+            assert_ne!(
+                compute_non_canonical_contract_hash(&minimal_contract(ENTRY_POINTS)).unwrap(),
+                compute_non_canonical_contract_hash(&other).unwrap()
+            );
+        }
+
+        #[test]
+        fn display_is_marked() {
+            let hash =
+                compute_non_canonical_contract_hash(&minimal_contract(ENTRY_POINTS)).unwrap();
+
+            assert_eq!(
+                hash.to_string(),
+                format!("{} (non-canonical)", hash.0.to_hex_str())
+            );
+        }
+    }
+
+    mod invalid_entry_points {
+        use super::super::compute_contract_hash;
+        use crate::test_utils::minimal_contract;
+
+        fn error(entry_points_by_type: &str) -> String {
+            let err = compute_contract_hash(&minimal_contract(entry_points_by_type)).unwrap_err();
+            format!("{:#}", err)
+        }
+
+        #[test]
+        fn selector_over_modulus() {
+            let err = error(
+                r#"{"EXTERNAL": [
+                    {"selector": "0x1", "offset": "0x0"},
+                    {"selector": "0x800000000000011000000000000000000000000000000000000000000000001", "offset": "0x1"}
+                ]}"#,
+            );
+            assert!(
+                err.contains("Entry point selector out of field range under EXTERNAL at index 1"),
+                "{}",
+                err
+            );
+        }
+
+        #[test]
+        fn offset_over_modulus() {
+            let err = error(
+                r#"{"CONSTRUCTOR": [{"selector": "0x1", "offset": "0x800000000000011000000000000000000000000000000000000000000000002"}]}"#,
+            );
+            assert!(
+                err.contains("Entry point offset out of field range under CONSTRUCTOR at index 0"),
+                "{}",
+                err
+            );
+        }
+
+        #[test]
+        fn malformed_hex_is_distinct() {
+            let err = error(r#"{"EXTERNAL": [{"selector": "0xzz", "offset": "0x0"}]}"#);
+            assert!(
+                err.contains("Entry point invalid hex under EXTERNAL at index 0 entry (selector)"),
+                "{}",
+                err
+            );
+            assert!(!err.contains("out of field range"), "{}", err);
+        }
+    }
+
+    mod invalid_builtins {
+        use super::super::compute_contract_hash;
+        use crate::test_utils::minimal_contract;
+
+        fn contract_with_builtins(builtins: &str) -> Vec<u8> {
+            let mut contract: serde_json::Value =
+                serde_json::from_slice(&minimal_contract("{}")).unwrap();
+            contract["program"]["builtins"] = serde_json::from_str(builtins).unwrap();
+            serde_json::to_vec(&contract).unwrap()
+        }
+
+        #[test]
+        fn valid() {
+            compute_contract_hash(&contract_with_builtins(r#"["pedersen", "range_check"]"#))
+                .unwrap();
+        }
+
+        #[test]
+        fn too_long() {
+            let name = "a_builtin_name_which_is_way_too_long";
+            let builtins = format!(r#"["pedersen", "{}"]"#, name);
+
+            let error = compute_contract_hash(&contract_with_builtins(&builtins)).unwrap_err();
+            let message = format!("{:#}", error);
+
+            assert!(message.contains(name), "{}", message);
+            assert!(message.contains("index 1"), "{}", message);
+        }
+
+        #[test]
+        fn non_ascii() {
+            let error =
+                compute_contract_hash(&contract_with_builtins(r#"["pedersén"]"#)).unwrap_err();
+            let message = format!("{:#}", error);
+
+            assert!(message.contains("non-ASCII"), "{}", message);
+            assert!(message.contains("index 0"), "{}", message);
+        }
+    }
+
+    #[tokio::test]
+    async fn async_matches_sync() {
+        use super::{compute_contract_hash, compute_contract_hash_async};
+
+        let contract_definition = crate::test_utils::contract_definition_fixture();
+
+        let expected = compute_contract_hash(&contract_definition).unwrap();
+        let hash = compute_contract_hash_async(contract_definition.into())
+            .await
+            .unwrap();
+
+        assert_eq!(hash, expected);
+    }
+
+    #[test]
+    fn contract_hasher_matches_one_shot() {
+        use super::{compute_contract_hash, ContractHasher};
+        use std::io::Write;
+
+        let contract_definition = crate::test_utils::contract_definition_fixture();
+
+        let mut hasher = ContractHasher::default();
+        for chunk in contract_definition.chunks(1000) {
+            hasher.write_all(chunk).unwrap();
+        }
+
+        assert_eq!(
+            hasher.finalize().unwrap(),
+            compute_contract_hash(&contract_definition).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn contract_abi() {
+        use crate::{
+            ethereum::Chain,
+            sequencer::{test_utils::*, Client},
+        };
+        use warp::Filter;
+
+        let fixture = crate::test_utils::contract_definition_fixture();
+        let expected =
+            serde_json::from_slice::<serde_json::Value>(&fixture).unwrap()["abi"].clone();
+        assert!(expected.is_array());
+
+        let reply =
+            warp::path!("feeder_gateway" / "get_full_contract").map(move || fixture.clone());
+        let (addr, run_srv) = warp::serve(reply).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(run_srv);
+
+        let url = reqwest::Url::parse(&format!("http://{}/", addr)).unwrap();
+        let client = Client::builder(Chain::Goerli).url(url).build().unwrap();
+
+        let abi = super::contract_abi(&client, *VALID_CONTRACT_ADDR)
+            .await
+            .unwrap();
+        assert_eq!(abi, expected);
+    }
+
+    #[tokio::test]
+    async fn compute_contract_address_of_goerli_deploy() {
+        use crate::core::ContractAddress;
+        use crate::sequencer::{reply::transaction::Type, test_utils::*, Client};
+        use pedersen::StarkHash;
+
+        let client = Client::new(crate::ethereum::Chain::Goerli).unwrap();
+        let block = client
+            .block_by_hash(*DEPLOY_CONTRACT_BLOCK_HASH)
+            .await
+            .unwrap();
+        let state_update = client
+            .state_update((*DEPLOY_CONTRACT_BLOCK_HASH).into())
+            .await
+            .unwrap();
+
+        let deploy = block
+            .transactions
+            .iter()
+            .find(|tx| tx.r#type == Type::Deploy && tx.contract_address == *VALID_CONTRACT_ADDR)
+            .unwrap();
+        let class_hash = state_update
+            .state_diff
+            .deployed_contracts
+            .iter()
+            .find(|contract| contract.address == *VALID_CONTRACT_ADDR)
+            .unwrap()
+            .contract_hash;
+
+        let calldata = deploy
+            .constructor_calldata
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|param| param.0)
+            .collect::<Vec<_>>();
+
+        let address = super::compute_contract_address(
+            ContractAddress(StarkHash::ZERO),
+            deploy.contract_address_salt.unwrap().0,
+            class_hash,
+            &calldata,
+        );
+        assert_eq!(address, *VALID_CONTRACT_ADDR);
+    }
+
+    mod event_hash {
+        use super::super::{compute_event_hash, HashChain};
+        use crate::core::ContractAddress;
+        use crate::sequencer::reply::Block;
+        use pedersen::StarkHash;
+
+        fn chain(values: &[StarkHash]) -> StarkHash {
+            let mut chain = HashChain::default();
+            values.iter().for_each(|value| chain.update(*value));
+            chain.finalize()
+        }
+
+        /// The fixture block is synthetic, so this is not a network vector. The expected hash
+        /// was computed with an independent implementation of the Pedersen hash chain, which
+        /// reproduces `pedersen(1, 2)` of the pedersen crate's tests.
+        #[test]
+        fn of_synthetic_block_fixture_event() {
+            let block: Block =
+                serde_json::from_str(include_str!("../../fixtures/sequencer/block.json")).unwrap();
+            let event = &block.transaction_receipts[1].events[0];
+
+            let keys = event.keys.iter().map(|key| key.0).collect::<Vec<_>>();
+            let data = event.data.iter().map(|value| value.0).collect::<Vec<_>>();
+
+            let expected = StarkHash::from_hex_str(
+                "0x138948449764c6342b2cda1eaaf52e5e65cf458dc4dd09308c5647b350c2ff7",
+            )
+            .unwrap();
+            assert_eq!(
+                compute_event_hash(event.from_address, &keys, &data),
+                expected
+            );
+        }
+
+        #[test]
+        fn empty_keys_and_data() {
+            let from_address = ContractAddress(StarkHash::from_hex_str("0x1234").unwrap());
+            let expected = chain(&[from_address.0, chain(&[]), chain(&[])]);
+
+            assert_eq!(compute_event_hash(from_address, &[], &[]), expected);
+        }
+
+        #[test]
+        fn keys_and_data_are_not_interchangeable() {
+            let from_address = ContractAddress(StarkHash::from_hex_str("0x1234").unwrap());
+            let value = [StarkHash::from_hex_str("0x1").unwrap()];
+
+            assert_ne!(
+                compute_event_hash(from_address, &value, &[]),
+                compute_event_hash(from_address, &[], &value)
+            );
+        }
+    }
+
+    mod surrounding_bytes {
+        use super::super::compute_contract_hash;
+        use crate::test_utils::contract_definition_fixture;
+
+        #[test]
+        fn bom_prefix() {
+            let clean = contract_definition_fixture();
+            let mut with_bom = b"\xEF\xBB\xBF".to_vec();
+            with_bom.extend_from_slice(&clean);
+
+            assert_eq!(
+                compute_contract_hash(&with_bom).unwrap(),
+                compute_contract_hash(&clean).unwrap()
+            );
+        }
+
+        #[test]
+        fn trailing_whitespace() {
+            let clean = contract_definition_fixture();
+            let mut with_whitespace = clean.clone();
+            with_whitespace.extend_from_slice(b"\r\n\n \t");
+
+            assert_eq!(
+                compute_contract_hash(&with_whitespace).unwrap(),
+                compute_contract_hash(&clean).unwrap()
+            );
+        }
+
+        #[test]
+        fn bom_and_trailing_newline() {
+            let clean = contract_definition_fixture();
+            let mut blob = b"\xEF\xBB\xBF".to_vec();
+            blob.extend_from_slice(&clean);
+            blob.push(b'\n');
+
+            assert_eq!(
+                compute_contract_hash(&blob).unwrap(),
+                compute_contract_hash(&clean).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn fixture_object_keys_are_sorted() {
+        let contract_definition = crate::test_utils::contract_definition_fixture();
+        let contract_definition =
+            serde_json::from_slice::<super::json::ContractDefinition<'_>>(&contract_definition)
+                .unwrap();
+
+        assert!(super::object_keys_sorted(&contract_definition));
+    }
+
+    mod hash_chain {
+        use super::super::{GenericHashChain, HashChain, PedersenHasher};
+        use pedersen::{pedersen_hash, StarkHash};
+
+        fn felt(value: u8) -> StarkHash {
+            StarkHash::from_be_slice(&[value]).unwrap()
+        }
+
+        #[test]
+        fn empty_is_hash_of_zeros() {
+            assert_eq!(
+                HashChain::default().finalize(),
+                pedersen_hash(StarkHash::ZERO, StarkHash::ZERO)
+            );
+        }
+
+        #[test]
+        fn pedersen_matches_manual_construction() {
+            let mut chain = GenericHashChain::<PedersenHasher>::default();
+            chain.update(felt(1));
+            chain.update(felt(2));
+            chain.update(felt(3));
+
+            let expected = pedersen_hash(StarkHash::ZERO, felt(1));
+            let expected = pedersen_hash(expected, felt(2));
+            let expected = pedersen_hash(expected, felt(3));
+            let expected = pedersen_hash(expected, felt(3));
+
+            assert_eq!(chain.finalize(), expected);
+        }
+
+        #[test]
+        fn alias_is_pedersen() {
+            let mut alias = HashChain::default();
+            for value in 0..10 {
+                alias.update(felt(value));
+            }
+
+            // `compute_hash_on_elements(range(10))` of cairo-lang, computed with a Pedersen
+            // implementation independent of this crate.
+            let expected = StarkHash::from_hex_str(
+                "0139d3a982791339f9728bae26def9c069205faae1a0867f0488c001e1f31cb9",
+            )
+            .unwrap();
+
+            assert_eq!(alias.finalize(), expected);
+        }
+
+        #[test]
+        fn from_parts_matches_built_chain() {
+            let mut built = HashChain::default();
+            let mut accumulator = StarkHash::ZERO;
+            for value in 1..=5 {
+                built.update(felt(value));
+                accumulator = pedersen_hash(accumulator, felt(value));
+            }
+
+            assert_eq!(
+                HashChain::from_parts(accumulator, 5).finalize(),
+                built.finalize()
+            );
+        }
+
+        #[test]
+        fn from_parts_can_be_updated() {
+            let mut built = HashChain::default();
+            built.update(felt(1));
+            built.update(felt(2));
+
+            let mut resumed = HashChain::from_parts(pedersen_hash(StarkHash::ZERO, felt(1)), 1);
+            resumed.update(felt(2));
+
+            assert_eq!(resumed.finalize(), built.finalize());
+        }
+
+        #[test]
+        fn finalize_with_count() {
+            let mut built = HashChain::default();
+            built.update(felt(7));
+
+            let accumulator = pedersen_hash(StarkHash::ZERO, felt(7));
+            assert_eq!(
+                super::super::finalize_with_count(accumulator, 1),
+                built.finalize()
+            );
+            assert_eq!(
+                super::super::finalize_with_count(StarkHash::ZERO, 0),
+                HashChain::default().finalize()
+            );
+        }
+    }
+
+    mod hash_chain_properties {
+        use super::super::HashChain;
+        use pedersen::{pedersen_hash, StarkHash};
+        use proptest::prelude::*;
+        use rand::{rngs::StdRng, SeedableRng};
+
+        /// Reference implementation of the hash chain over a whole slice, as done by cairo-lang's
+        /// `compute_hash_on_elements`.
+        fn hash_array(values: &[StarkHash]) -> StarkHash {
+            let hash = values
+                .iter()
+                .fold(StarkHash::ZERO, |acc, value| pedersen_hash(acc, *value));
+            let len = StarkHash::from_be_slice(&values.len().to_be_bytes()).unwrap();
+            pedersen_hash(hash, len)
+        }
+
+        fn chain(values: &[StarkHash]) -> StarkHash {
+            let mut chain = HashChain::default();
+            values.iter().for_each(|value| chain.update(*value));
+            chain.finalize()
+        }
+
+        /// Random field elements from a seed, leaving proptest to pick (and shrink) the seed and
+        /// the length.
+        fn felts(seed: u64, len: usize) -> Vec<StarkHash> {
+            let mut rng = StdRng::seed_from_u64(seed);
+            (0..len).map(|_| StarkHash::random(&mut rng)).collect()
+        }
+
+        /// Pins the reference, and the [pedersen_hash] both it and [HashChain] build on, to
+        /// values computed with a Pedersen implementation independent of this crate.
+        #[test]
+        fn reference_matches_known_vectors() {
+            let felts = |values: std::ops::Range<u8>| {
+                values
+                    .map(|value| StarkHash::from_be_slice(&[value]).unwrap())
+                    .collect::<Vec<_>>()
+            };
+            let hex = |hex: &str| StarkHash::from_hex_str(hex).unwrap();
+
+            assert_eq!(
+                pedersen_hash(hex("0x1"), hex("0x2")),
+                hex("05bb9440e27889a364bcb678b1f679ecd1347acdedcbf36e83494f857cc58026")
+            );
+            assert_eq!(
+                hash_array(&[]),
+                hex("049ee3eba8c1600700ee1b87eb599f16716b0b1022947733551fde4050ca6804")
+            );
+            assert_eq!(
+                hash_array(&felts(1..3)),
+                hex("0501a3a8e6cd4f5241c639c74052aaa34557aafa84dd4ba983d6443c590ab7df")
+            );
+            assert_eq!(
+                hash_array(&felts(0..10)),
+                hex("0139d3a982791339f9728bae26def9c069205faae1a0867f0488c001e1f31cb9")
+            );
+        }
+
+        proptest! {
+            // pedersen_hash is slow in debug builds
+            #![proptest_config(ProptestConfig::with_cases(32))]
+
+            #[test]
+            fn equals_hash_array(seed in any::<u64>(), len in 0usize..8) {
+                let values = felts(seed, len);
+                prop_assert_eq!(chain(&values), hash_array(&values));
+            }
+
+            #[test]
+            fn appending_changes_result(seed in any::<u64>(), len in 0usize..8) {
+                let values = felts(seed, len + 1);
+                let appended = chain(&values);
+
+                prop_assert_ne!(chain(&values[..len]), appended);
+            }
+
+            #[test]
+            fn prepending_changes_result(seed in any::<u64>(), len in 0usize..8) {
+                let values = felts(seed, len + 1);
+                let prepended = chain(&values);
+
+                prop_assert_ne!(chain(&values[1..]), prepended);
+            }
+
+            #[test]
+            fn trailing_zero_is_counted(seed in any::<u64>(), len in 0usize..8) {
+                let mut values = felts(seed, len);
+                let without = chain(&values);
+                values.push(StarkHash::ZERO);
+
+                prop_assert_ne!(chain(&values), without);
+            }
+        }
+    }
+
+    mod parse_mode {
+        use super::super::{compute_contract_hash, contract_stats_with, ParseMode};
+
+        /// The fixture with an unknown top-level and an unknown program field.
+        fn with_extra_fields() -> Vec<u8> {
+            let fixture = crate::test_utils::contract_definition_fixture();
+            let mut definition = serde_json::from_slice::<serde_json::Value>(&fixture).unwrap();
+            definition["compiler_version"] = serde_json::json!("9.9.9");
+            definition["program"]["future_field"] = serde_json::json!({"key": [1, 2, 3]});
+            serde_json::to_vec(&definition).unwrap()
+        }
+
+        #[test]
+        fn strict_rejects_extra_fields() {
+            let contract_definition = with_extra_fields();
+
+            compute_contract_hash(&contract_definition).unwrap_err();
+            contract_stats_with(&contract_definition, ParseMode::Strict).unwrap_err();
+        }
+
+        #[test]
+        fn lenient_reads_metadata() {
+            let fixture = crate::test_utils::contract_definition_fixture();
+            let expected = contract_stats_with(&fixture, ParseMode::Strict).unwrap();
+
+            let stats = contract_stats_with(&with_extra_fields(), ParseMode::Lenient).unwrap();
+            assert_eq!(stats, expected);
+        }
+
+        #[test]
+        fn lenient_matches_strict() {
+            let fixture = crate::test_utils::contract_definition_fixture();
+
+            assert_eq!(
+                contract_stats_with(&fixture, ParseMode::Lenient).unwrap(),
+                contract_stats_with(&fixture, ParseMode::Strict).unwrap()
+            );
+        }
+    }
+
+    mod extract_hints {
+        use super::super::extract_hints;
+        use crate::test_utils::minimal_contract;
+
+        /// A minimal contract definition with two bytecode elements and the given hints.
+        fn with_hints(hints: serde_json::Value) -> Vec<u8> {
+            let mut contract: serde_json::Value =
+                serde_json::from_slice(&minimal_contract("{}")).unwrap();
+            contract["program"]["data"] = serde_json::json!(["0x40780017fff7fff", "0x1"]);
+            contract["program"]["hints"] = hints;
+            serde_json::to_vec(&contract).unwrap()
+        }
+
+        #[test]
+        fn valid() {
+            let hint = serde_json::json!({"code": "memory[ap] = 1", "accessible_scopes": []});
+            let contract_definition = with_hints(serde_json::json!({
+                "1": [hint.clone()],
+                "0": [hint.clone(), hint.clone()],
+            }));
+
+            let hints = extract_hints(&contract_definition).unwrap();
+            assert_eq!(hints.keys().copied().collect::<Vec<_>>(), [0, 1]);
+            assert_eq!(hints[&0], [hint.clone(), hint.clone()]);
+            assert_eq!(hints[&1], [hint]);
+        }
+
+        #[test]
+        fn out_of_range() {
+            let hint = serde_json::json!({"code": "memory[ap] = 1", "accessible_scopes": []});
+            let contract_definition = with_hints(serde_json::json!({
+                "0": [hint.clone()],
+                "2": [hint],
+            }));
+
+            let message = extract_hints(&contract_definition).unwrap_err().to_string();
+            assert!(message.contains("program counter 2"), "{}", message);
+        }
+
+        #[test]
+        fn fixture() {
+            let fixture = crate::test_utils::contract_definition_fixture();
+
+            extract_hints(&fixture).unwrap();
+        }
+    }
+
+    mod strip_non_hashed_fields {
+        use super::super::{
+            compute_definition_keccak, parse_contract_definition, strip_non_hashed_fields,
+            truncated_keccak, ParseOptions,
+        };
+        use crate::test_utils::contract_definition_fixture;
+        use sha3::Digest;
+
+        fn with_debug_info() -> Vec<u8> {
+            let mut contract_definition =
+                serde_json::from_slice::<serde_json::Value>(&contract_definition_fixture())
+                    .unwrap();
+            contract_definition["program"]["debug_info"] = serde_json::json!({
+                "file_contents": {"contract.cairo": "func main():\n    ret\nend\n"},
+                "instruction_locations": {},
+            });
+            serde_json::to_vec(&contract_definition).unwrap()
+        }
+
+        #[test]
+        fn yields_the_keccak_input() {
+            let contract_definition = with_debug_info();
+
+            let stripped = strip_non_hashed_fields(&contract_definition).unwrap();
+
+            let mut parsed =
+                parse_contract_definition(&contract_definition, ParseOptions::default()).unwrap();
+            parsed.program.debug_info = None;
+            let expected = compute_definition_keccak(&parsed).unwrap();
+
+            let keccak = truncated_keccak(<[u8; 32]>::from(sha3::Keccak256::digest(&stripped)));
+            assert_eq!(keccak, expected);
+        }
+
+        #[test]
+        fn removes_debug_info() {
+            let stripped = strip_non_hashed_fields(&with_debug_info()).unwrap();
+            let stripped = std::str::from_utf8(&stripped).unwrap();
+
+            assert!(stripped.contains(r#""debug_info": null"#));
+            assert!(!stripped.contains("file_contents"));
+            assert!(!stripped.contains("entry_points_by_type"));
+            assert!(!stripped.contains(r#""attributes""#));
+
+            assert_eq!(
+                stripped.as_bytes(),
+                strip_non_hashed_fields(&contract_definition_fixture()).unwrap()
+            );
+        }
+    }
+
+    mod check_prime {
+        use super::super::{compute_contract_hash, compute_contract_hash_with, ParseOptions};
+
+        fn with_prime(prime: &str) -> Vec<u8> {
+            let contract_definition = crate::test_utils::contract_definition_fixture();
+            let mut contract_definition =
+                serde_json::from_slice::<serde_json::Value>(&contract_definition).unwrap();
+            contract_definition["program"]["prime"] = serde_json::Value::from(prime);
+            serde_json::to_vec(&contract_definition).unwrap()
+        }
+
+        #[test]
+        fn stark_prime() {
+            compute_contract_hash(&with_prime(super::super::STARK_PRIME)).unwrap();
+            compute_contract_hash(&with_prime(
+                "0x0800000000000011000000000000000000000000000000000000000000000001",
+            ))
+            .unwrap();
+        }
+
+        #[test]
+        fn wrong_prime() {
+            let contract_definition =
+                with_prime("0x800000000000011000000000000000000000000000000000000000000000003");
+
+            let err = compute_contract_hash(&contract_definition).unwrap_err();
+            assert!(
+                format!("{:#}", err).contains("Unexpected program prime"),
+                "{:#}",
+                err
+            );
+
+            let unchecked = ParseOptions { check_prime: false };
+            compute_contract_hash_with(&contract_definition, unchecked).unwrap();
+        }
+    }
+
+    mod check_program_data {
+        use super::super::{check_program_data, extract_abi_code_hash};
+        use crate::test_utils::minimal_contract;
+
+        #[test]
+        fn empty_data() {
+            let err = check_program_data(b"[]").unwrap_err();
+            assert_eq!(err.to_string(), "Empty program data");
+
+            // Extracting does not check, so that stored definitions can still be migrated.
+            let (_, code, _) = extract_abi_code_hash(&minimal_contract("{}")).unwrap();
+            let err = check_program_data(&code).unwrap_err();
+            assert_eq!(err.to_string(), "Empty program data");
+        }
+
+        #[test]
+        fn non_empty_data() {
+            let fixture = crate::test_utils::contract_definition_fixture();
+            let (_, code, _) = extract_abi_code_hash(&fixture).unwrap();
+            check_program_data(&code).unwrap();
+        }
+    }
+
+    #[test]
+    fn hash_chain_counts_of_fixture() {
+        use super::{compute_contract_hash, compute_contract_hash_and_counts, HashChainCounts};
+
+        let contract_definition = crate::test_utils::contract_definition_fixture();
+
+        let (hash, counts) = compute_contract_hash_and_counts(&contract_definition).unwrap();
+
+        assert_eq!(hash, compute_contract_hash(&contract_definition).unwrap());
+        assert_eq!(
+            counts,
+            HashChainCounts {
+                external: 6,
+                l1_handler: 0,
+                constructor: 0,
+                builtins: 1,
+                bytecode: 132,
+            }
+        );
+    }
+
+    mod log_hash_mismatch {
+        use super::super::{
+            contract_hash_debug_info, log_hash_debug_info, log_hash_mismatch, ParseOptions,
+        };
+        use crate::core::{ContractAddress, ContractHash};
+        use pedersen::StarkHash;
+        use tracing_test::traced_test;
+
+        #[test]
+        fn debug_info_of_fixture() {
+            let contract_definition = crate::test_utils::contract_definition_fixture();
+
+            let debug_info =
+                contract_hash_debug_info(&contract_definition, ParseOptions::default()).unwrap();
+
+            assert_eq!(debug_info.counts.external, 6);
+            assert_eq!(debug_info.counts.bytecode, 132);
+            // debug_info is left out of the serialization
+            assert!(debug_info.serialized_len > 0);
+            assert!(debug_info.serialized_len < contract_definition.len());
+        }
+
+        #[traced_test]
+        #[test]
+        fn all_fields_are_logged() {
+            let contract_definition = crate::test_utils::contract_definition_fixture();
+            let debug_info =
+                contract_hash_debug_info(&contract_definition, ParseOptions::default()).unwrap();
+
+            let address = ContractAddress(StarkHash::from_hex_str("0x123").unwrap());
+            let expected = ContractHash(StarkHash::from_hex_str("0x456").unwrap());
+            let computed = ContractHash(StarkHash::from_hex_str("0x789").unwrap());
+
+            log_hash_mismatch(address, expected, computed);
+            log_hash_debug_info(address, &debug_info);
+
+            assert!(logs_contain("Contract hash mismatch"));
+            assert!(logs_contain("Contract hash mismatch debug info"));
+            assert!(logs_contain(&format!("address={}", address.0)));
+            assert!(logs_contain(&format!("expected={}", expected.0)));
+            assert!(logs_contain(&format!("computed={}", computed.0)));
+            assert!(logs_contain(&format!(
+                "serialized_len={}",
+                debug_info.serialized_len
+            )));
+            assert!(logs_contain("external=6"));
+            assert!(logs_contain("l1_handler=0"));
+            assert!(logs_contain("constructor=0"));
+            assert!(logs_contain("builtins=1"));
+            assert!(logs_contain("bytecode=132"));
+        }
+    }
+
+    #[test]
+    fn contract_stats_of_fixture() {
+        use super::{contract_stats, ContractStats};
+
+        let contract_definition = crate::test_utils::contract_definition_fixture();
+
+        let stats = contract_stats(&contract_definition).unwrap();
+
+        assert_eq!(
+            stats,
+            ContractStats {
+                external_entry_points: 3,
+                l1_handler_entry_points: 0,
+                constructor_entry_points: 0,
+                builtins: 1,
+                bytecode_len: 132,
+                duplicate_data_entries: 70,
+                abi_bytes: 436,
+            }
+        );
+    }
+
+    #[test]
+    fn contract_stats_counts_duplicate_data() {
+        use super::{contract_stats_with, ParseMode};
+
+        let fixture = crate::test_utils::contract_definition_fixture();
+        let mut contract_definition =
+            serde_json::from_slice::<serde_json::Value>(&fixture).unwrap();
+        contract_definition["program"]["data"] =
+            serde_json::json!(["0x1", "0x2", "0x1", "0x3", "0x1", "0x2"]);
+        let contract_definition = serde_json::to_vec(&contract_definition).unwrap();
+
+        for mode in [ParseMode::Strict, ParseMode::Lenient] {
+            let stats = contract_stats_with(&contract_definition, mode).unwrap();
+            assert_eq!(stats.bytecode_len, 6);
+            assert_eq!(stats.duplicate_data_entries, 3);
+        }
+    }
 }
@@ -0,0 +1,437 @@
+//! Tooling for hashing contract definitions in bulk: from zip archives and directories, on
+//! blocking threads, and exporting the results as JSON lines.
+
+use anyhow::{Context, Error, Result};
+
+use super::{compute_contract_hash, compute_contract_hash_async};
+use crate::core::ContractHash;
+
+/// Computes the [`compute_contract_hash`] of each of the contract definitions on blocking threads,
+/// hashing at most `max_threads` definitions at a time. Zero means one per CPU core.
+///
+/// The results are in the same order as `contract_definition_dumps`.
+pub async fn compute_contract_hashes_with_concurrency(
+    contract_definition_dumps: Vec<std::sync::Arc<[u8]>>,
+    max_threads: usize,
+) -> Vec<Result<ContractHash>> {
+    with_concurrency(
+        contract_definition_dumps,
+        max_threads,
+        compute_contract_hash_async,
+    )
+    .await
+}
+
+/// Runs `hash` on each of the dumps, running at most `max_threads` of them at a time, see
+/// [`compute_contract_hashes_with_concurrency`].
+async fn with_concurrency<F, Fut>(
+    contract_definition_dumps: Vec<std::sync::Arc<[u8]>>,
+    max_threads: usize,
+    hash: F,
+) -> Vec<Result<ContractHash>>
+where
+    F: Fn(std::sync::Arc<[u8]>) -> Fut,
+    Fut: std::future::Future<Output = Result<ContractHash>>,
+{
+    let max_threads = match max_threads {
+        0 => num_cpus::get(),
+        n => n,
+    };
+    let permits = std::sync::Arc::new(tokio::sync::Semaphore::new(max_threads));
+
+    let hashes = contract_definition_dumps.into_iter().map(|dump| {
+        let permits = permits.clone();
+        let hash = &hash;
+        async move {
+            let _permit = permits
+                .acquire_owned()
+                .await
+                .context("Contract hashing semaphore closed")?;
+            hash(dump).await
+        }
+    });
+
+    futures::future::join_all(hashes).await
+}
+
+/// The largest uncompressed archive entry [`compute_hashes_from_archive`] reads. The sizes in the
+/// zip headers are not trusted, so this bounds the memory used for a single entry.
+pub const MAX_ARCHIVE_ENTRY_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Computes the [`compute_contract_hash`] of every file in a zip archive of contract definitions,
+/// keyed by the name of the archive entry. Directories are skipped, and entries larger than
+/// [`MAX_ARCHIVE_ENTRY_BYTES`] are rejected.
+///
+/// Only failing to open the archive is an error; failures to read or hash a single entry are
+/// returned alongside its name, and the rest of the archive is still processed.
+pub fn compute_hashes_from_archive<R>(reader: R) -> Result<Vec<(String, Result<ContractHash>)>>
+where
+    R: std::io::Read + std::io::Seek,
+{
+    let mut archive = zip::ZipArchive::new(reader).context("Opening contract archive")?;
+
+    let mut hashes = Vec::with_capacity(archive.len());
+    for i in 0..archive.len() {
+        let mut entry = match archive.by_index(i) {
+            Ok(entry) => entry,
+            Err(err) => {
+                let err = Error::from(err).context(format!("Reading archive entry {i}"));
+                hashes.push((format!("<entry {i}>"), Err(err)));
+                continue;
+            }
+        };
+
+        if entry.is_dir() {
+            continue;
+        }
+
+        let name = entry.name().to_owned();
+        let hash = read_limited(&mut entry, MAX_ARCHIVE_ENTRY_BYTES)
+            .with_context(|| format!("Reading archive entry {name}"))
+            .and_then(|contract_definition| compute_contract_hash(&contract_definition));
+
+        hashes.push((name, hash));
+    }
+
+    Ok(hashes)
+}
+
+/// Reads all of `reader`, erroring instead of reading more than `limit` bytes.
+fn read_limited(reader: impl std::io::Read, limit: u64) -> Result<Vec<u8>> {
+    use std::io::Read;
+
+    let mut buffer = Vec::new();
+    reader.take(limit + 1).read_to_end(&mut buffer)?;
+    anyhow::ensure!(
+        buffer.len() as u64 <= limit,
+        "Entry is larger than {} bytes",
+        limit
+    );
+
+    Ok(buffer)
+}
+
+/// Computes the [`compute_contract_hash`] of every `.json` and `.json.zst` file in `dir` and its
+/// subdirectories, in path order. Zstandard compressed files are decompressed before hashing, and
+/// other files are skipped. Symbolic links to directories are not followed.
+///
+/// `progress` is called with the number of files hashed so far and the total number of files
+/// after each file. Only failing to walk the directory is an error; failures to read or hash a
+/// single file are returned alongside its path.
+pub fn hash_directory(
+    dir: &std::path::Path,
+    mut progress: impl FnMut(usize, usize),
+) -> Result<Vec<(std::path::PathBuf, Result<ContractHash>)>> {
+    let mut paths = Vec::new();
+    collect_contract_files(dir, &mut paths)?;
+    paths.sort();
+
+    let total = paths.len();
+    let mut hashes = Vec::with_capacity(total);
+    for (i, path) in paths.into_iter().enumerate() {
+        let hash = read_contract_file(&path).and_then(|contract_definition| {
+            compute_contract_hash(&contract_definition)
+                .with_context(|| format!("Hashing {}", path.display()))
+        });
+        hashes.push((path, hash));
+
+        progress(i + 1, total);
+    }
+
+    Ok(hashes)
+}
+
+/// Recursively collects the paths of the `.json` and `.json.zst` files in `dir`.
+fn collect_contract_files(
+    dir: &std::path::Path,
+    paths: &mut Vec<std::path::PathBuf>,
+) -> Result<()> {
+    let entries =
+        std::fs::read_dir(dir).with_context(|| format!("Reading directory {}", dir.display()))?;
+    for entry in entries {
+        let entry = entry.with_context(|| format!("Reading directory {}", dir.display()))?;
+        let file_type = entry
+            .file_type()
+            .with_context(|| format!("Reading directory {}", dir.display()))?;
+        let path = entry.path();
+        if file_type.is_dir() {
+            collect_contract_files(&path, paths)?;
+        } else if is_contract_file(&path) && path.is_file() {
+            // Symlinked directories are skipped, as they can form cycles.
+            paths.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn is_contract_file(path: &std::path::Path) -> bool {
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("");
+    name.ends_with(".json") || name.ends_with(".json.zst")
+}
+
+/// Writes the results of bulk hashing, for example of [`compute_hashes_from_archive`], to `writer`
+/// as JSON lines: one `{"name": .., "hash": ..}` or `{"name": .., "error": ..}` object per result,
+/// in order. Errors are written with their whole chain of causes.
+///
+/// Each line is written as soon as it is serialized; wrap `writer` in a [`std::io::BufWriter`]
+/// if it is unbuffered.
+pub fn export_hashes_jsonl(
+    results: &[(String, Result<ContractHash>)],
+    mut writer: impl std::io::Write,
+) -> std::io::Result<()> {
+    for (name, result) in results {
+        let line = match result {
+            Ok(hash) => serde_json::json!({ "name": name, "hash": hash.0 }),
+            Err(e) => serde_json::json!({ "name": name, "error": format!("{:#}", e) }),
+        };
+        serde_json::to_writer(&mut writer, &line)?;
+        writer.write_all(b"\n")?;
+    }
+
+    writer.flush()
+}
+
+/// Reads the contract definition at `path`, decompressing it if it is a `.zst` file.
+fn read_contract_file(path: &std::path::Path) -> Result<Vec<u8>> {
+    let contents = std::fs::read(path).with_context(|| format!("Reading {}", path.display()))?;
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("zst") => zstd::decode_all(&contents[..])
+            .with_context(|| format!("Decompressing {}", path.display())),
+        _ => Ok(contents),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn hashes_from_archive() {
+        use super::{compute_contract_hash, compute_hashes_from_archive};
+        use std::io::{Cursor, Write};
+        use zip::{write::FileOptions, ZipWriter};
+
+        let fixture = crate::test_utils::contract_definition_fixture();
+        let minimal = crate::test_utils::minimal_contract("{}");
+
+        let mut archive = ZipWriter::new(Cursor::new(Vec::new()));
+        archive
+            .add_directory("contracts/", FileOptions::default())
+            .unwrap();
+        for (name, contents) in [
+            ("contracts/fixture.json", &fixture[..]),
+            ("contracts/invalid.json", &b"not json"[..]),
+            ("contracts/minimal.json", &minimal[..]),
+        ] {
+            archive.start_file(name, FileOptions::default()).unwrap();
+            archive.write_all(contents).unwrap();
+        }
+        let archive = archive.finish().unwrap();
+
+        let hashes = compute_hashes_from_archive(Cursor::new(archive.into_inner())).unwrap();
+
+        let names = hashes
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                "contracts/fixture.json",
+                "contracts/invalid.json",
+                "contracts/minimal.json"
+            ]
+        );
+
+        assert_eq!(
+            hashes[0].1.as_ref().unwrap(),
+            &compute_contract_hash(&fixture).unwrap()
+        );
+        assert!(hashes[1].1.is_err());
+        assert_eq!(
+            hashes[2].1.as_ref().unwrap(),
+            &compute_contract_hash(&minimal).unwrap()
+        );
+    }
+
+    #[test]
+    fn read_limited_rejects_oversized_entries() {
+        use super::read_limited;
+
+        assert_eq!(read_limited(&b"1234"[..], 4).unwrap(), b"1234");
+
+        let err = read_limited(&b"12345"[..], 4).unwrap_err();
+        assert_eq!(err.to_string(), "Entry is larger than 4 bytes");
+    }
+
+    #[tokio::test]
+    async fn hashes_with_concurrency() {
+        use super::{compute_contract_hash, compute_contract_hashes_with_concurrency};
+        use std::sync::Arc;
+
+        let fixture = crate::test_utils::contract_definition_fixture();
+        let expected = compute_contract_hash(&fixture).unwrap();
+
+        // A different definition of the same contract, to tell the results apart.
+        let mut other = serde_json::from_slice::<serde_json::Value>(&fixture).unwrap();
+        other["abi"] = serde_json::json!([]);
+        let other = serde_json::to_vec(&other).unwrap();
+        let other_expected = compute_contract_hash(&other).unwrap();
+        assert_ne!(expected, other_expected);
+
+        let dumps: Vec<Arc<[u8]>> = vec![
+            fixture.clone().into(),
+            b"not json".to_vec().into(),
+            other.into(),
+            fixture.into(),
+        ];
+
+        for max_threads in [1, 4, 0] {
+            let hashes = compute_contract_hashes_with_concurrency(dumps.clone(), max_threads).await;
+
+            assert_eq!(hashes.len(), 4, "{}", max_threads);
+            assert_eq!(hashes[0].as_ref().unwrap(), &expected, "{}", max_threads);
+            assert!(hashes[1].is_err(), "{}", max_threads);
+            assert_eq!(
+                hashes[2].as_ref().unwrap(),
+                &other_expected,
+                "{}",
+                max_threads
+            );
+            assert_eq!(hashes[3].as_ref().unwrap(), &expected, "{}", max_threads);
+        }
+    }
+
+    #[tokio::test]
+    async fn concurrency_is_bounded() {
+        use super::with_concurrency;
+        use crate::core::ContractHash;
+        use pedersen::StarkHash;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        for max_threads in [1, 3] {
+            let (running, peak) = (AtomicUsize::new(0), AtomicUsize::new(0));
+            let (running, peak) = (&running, &peak);
+            let dumps: Vec<Arc<[u8]>> = vec![b"{}".to_vec().into(); 8];
+
+            let hashes = with_concurrency(dumps, max_threads, move |_| async move {
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+                running.fetch_sub(1, Ordering::SeqCst);
+                Ok(ContractHash(StarkHash::ZERO))
+            })
+            .await;
+
+            assert_eq!(hashes.len(), 8);
+            assert_eq!(peak.load(Ordering::SeqCst), max_threads);
+        }
+    }
+
+    #[test]
+    fn hashes_directory() {
+        use super::{compute_contract_hash, hash_directory};
+
+        let compressed = &include_bytes!("../../../fixtures/contract_definition.json.zst")[..];
+        let fixture = crate::test_utils::contract_definition_fixture();
+        let expected = compute_contract_hash(&fixture).unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("nested")).unwrap();
+        std::fs::write(dir.path().join("compressed.json.zst"), compressed).unwrap();
+        std::fs::write(dir.path().join("invalid.json"), b"not json").unwrap();
+        std::fs::write(dir.path().join("nested/plain.json"), &fixture).unwrap();
+        std::fs::write(dir.path().join("README.md"), b"skipped").unwrap();
+
+        let mut progress = Vec::new();
+        let hashes =
+            hash_directory(dir.path(), |done, total| progress.push((done, total))).unwrap();
+
+        let paths = hashes
+            .iter()
+            .map(|(path, _)| path.strip_prefix(dir.path()).unwrap().to_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            [
+                std::path::Path::new("compressed.json.zst"),
+                std::path::Path::new("invalid.json"),
+                std::path::Path::new("nested/plain.json"),
+            ]
+        );
+
+        assert_eq!(hashes[0].1.as_ref().unwrap(), &expected);
+        assert!(hashes[1].1.is_err());
+        assert_eq!(hashes[2].1.as_ref().unwrap(), &expected);
+
+        assert_eq!(progress, [(1, 3), (2, 3), (3, 3)]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn hash_directory_skips_symlinked_directories() {
+        use super::hash_directory;
+
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("nested")).unwrap();
+        std::fs::write(dir.path().join("nested/plain.json"), b"{}").unwrap();
+        // A cycle back to the root, and a link to a directory merely named like a contract file.
+        std::os::unix::fs::symlink(dir.path(), dir.path().join("nested/cycle")).unwrap();
+        std::os::unix::fs::symlink(dir.path().join("nested"), dir.path().join("linked.json"))
+            .unwrap();
+
+        let hashes = hash_directory(dir.path(), |_, _| {}).unwrap();
+
+        let paths = hashes
+            .iter()
+            .map(|(path, _)| path.strip_prefix(dir.path()).unwrap().to_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(paths, [std::path::Path::new("nested/plain.json")]);
+    }
+
+    #[test]
+    fn hash_directory_missing_dir_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(super::hash_directory(&dir.path().join("missing"), |_, _| {}).is_err());
+    }
+
+    #[test]
+    fn export_hashes_jsonl_round_trips() {
+        use super::export_hashes_jsonl;
+        use crate::core::ContractHash;
+        use anyhow::Context;
+        use pedersen::StarkHash;
+
+        let hash = ContractHash(StarkHash::from_hex_str("0x1234").unwrap());
+        let error = Err(anyhow::anyhow!("Unexpected end of input")).context("Hashing b.json");
+        let results = vec![
+            ("a.json".to_string(), Ok(hash)),
+            ("b.json".to_string(), error),
+        ];
+
+        let mut output = Vec::new();
+        export_hashes_jsonl(&results, &mut output).unwrap();
+
+        let lines = std::str::from_utf8(&output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            vec![
+                serde_json::json!({"name": "a.json", "hash": "0x1234"}),
+                serde_json::json!({
+                    "name": "b.json",
+                    "error": "Hashing b.json: Unexpected end of input"
+                }),
+            ]
+        );
+
+        let mut empty = Vec::new();
+        export_hashes_jsonl(&[], &mut empty).unwrap();
+        assert!(empty.is_empty());
+    }
+}
@@ -5,14 +5,17 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use crate::{
+    config::SyncSource,
     core::{
-        ContractRoot, GlobalRoot, StarknetBlockHash, StarknetBlockNumber, StarknetBlockTimestamp,
+        ContractRoot, EthereumBlockNumber, GlobalRoot, StarknetBlockHash, StarknetBlockNumber,
+        StarknetBlockTimestamp,
     },
     ethereum::{
         log::StateUpdateLog,
         state_update::{DeployedContract, StateUpdate},
         Chain,
     },
+    retry::RetryBudget,
     rpc::types::reply::syncing,
     sequencer::{self, reply::Block},
     state::{calculate_contract_state_hash, state_tree::GlobalStateTree, update_contract_state},
@@ -26,7 +29,7 @@ use crate::{
 use anyhow::Context;
 use pedersen::StarkHash;
 use rusqlite::{Connection, Transaction};
-use tokio::sync::{mpsc, RwLock};
+use tokio::sync::{mpsc, watch, RwLock};
 use web3::{transports::Http, Web3};
 
 use crate::rpc::types::reply::Syncing as SyncStatus;
@@ -43,12 +46,23 @@ impl Default for State {
     }
 }
 
+/// Syncs StarkNet state from the sources selected by `sync_source` into `storage`, until
+/// `shutdown` is set to `true` or its sender is dropped.
+///
+/// The retries of the L1 log queries are drawn from `retry_budget`, which should be the budget
+/// the `sequencer` client was built with.
+#[allow(clippy::too_many_arguments)]
 pub async fn sync(
     storage: Storage,
     transport: Web3<Http>,
     chain: Chain,
     sequencer: sequencer::Client,
     state: Arc<State>,
+    retry_decoder_errors: bool,
+    retry_budget: RetryBudget,
+    startup_reorg_buffer: u64,
+    sync_source: SyncSource,
+    shutdown: watch::Receiver<bool>,
 ) -> anyhow::Result<()> {
     // TODO: should this be owning a Storage, or just take in a Connection?
     let mut db_conn = storage
@@ -67,6 +81,14 @@ pub async fn sync(
         Ok((l1_head, l2_head))
     })?;
 
+    // Re-scan the most recent L1 state updates, in case a reorg happened while the node was down.
+    let l1_head = match l1_head {
+        Some(head) if startup_reorg_buffer > 0 => {
+            rewind_l1_head(&mut db_conn, &head, startup_reorg_buffer).await?
+        }
+        other => other,
+    };
+
     // Start update sync-status process.
     let starting_block = l2_head
         .map(|(_, hash)| hash)
@@ -77,9 +99,26 @@ pub async fn sync(
         starting_block,
     ));
 
-    // Start L1 and L2 sync processes.
-    let mut l1_handle = tokio::spawn(l1::sync(tx_l1, transport.clone(), chain, l1_head));
-    let mut l2_handle = tokio::spawn(l2::sync(tx_l2, sequencer.clone(), l2_head));
+    // Start L1 and L2 sync processes. A source which is not synced from is replaced by a task
+    // which only keeps its channel open, so that it never yields an event.
+    let mut l1_handle = match sync_source {
+        SyncSource::L1 | SyncSource::Both => tokio::spawn(l1::sync(
+            tx_l1,
+            transport.clone(),
+            chain,
+            l1_head,
+            retry_decoder_errors,
+            retry_budget.clone(),
+            stop_requested(shutdown.clone()),
+        )),
+        SyncSource::Sequencer => tokio::spawn(idle(tx_l1, shutdown.clone())),
+    };
+    let mut l2_handle = match sync_source {
+        SyncSource::Sequencer | SyncSource::Both => {
+            tokio::spawn(l2::sync(tx_l2, sequencer.clone(), l2_head))
+        }
+        SyncSource::L1 => tokio::spawn(idle(tx_l2, shutdown.clone())),
+    };
 
     let mut existed = (0, 0);
 
@@ -89,6 +128,22 @@ pub async fn sync(
 
     loop {
         tokio::select! {
+            _ = stop_requested(shutdown.clone()) => {
+                // L1 sync stops on the same signal, and L2 sync ends once its channel is closed.
+                // Pending events are discarded, which also fails any outstanding L2 queries.
+                rx_l1.close();
+                rx_l2.close();
+                while rx_l1.recv().await.is_some() {}
+                while rx_l2.recv().await.is_some() {}
+                if let Err(e) = l1_handle.await.context("Join L1 sync process handle")? {
+                    tracing::warn!("L1 sync process terminated with: {:?}", e);
+                }
+                if let Err(e) = l2_handle.await.context("Join L2 sync process handle")? {
+                    tracing::warn!("L2 sync process terminated with: {:?}", e);
+                }
+                tracing::info!("Sync stopped");
+                return Ok(());
+            }
             l1_event = rx_l1.recv() => match l1_event {
                 Some(l1::Event::Update(updates)) => {
                     let first = updates.first().map(|u| u.block_number.0);
@@ -148,6 +203,10 @@ pub async fn sync(
                             tracing::warn!("L1 sync process terminated with: {:?}", e);
                         }
                     }
+                    if *shutdown.borrow() {
+                        tracing::info!("Sync stopped");
+                        return Ok(());
+                    }
                     let l1_head = tokio::task::block_in_place(|| {
                         L1StateTable::get(&db_conn, L1TableBlockId::Latest)
                     })
@@ -156,7 +215,15 @@ pub async fn sync(
                     let (new_tx, new_rx) = mpsc::channel(1);
                     rx_l1 = new_rx;
 
-                    l1_handle = tokio::spawn(l1::sync(new_tx, transport.clone(), chain, l1_head));
+                    l1_handle = tokio::spawn(l1::sync(
+                        new_tx,
+                        transport.clone(),
+                        chain,
+                        l1_head,
+                        retry_decoder_errors,
+                        retry_budget.clone(),
+                        stop_requested(shutdown.clone()),
+                    ));
                     tracing::info!("L1 sync process restarted.")
                 },
             },
@@ -275,6 +342,10 @@ pub async fn sync(
                             tracing::warn!("L2 sync process terminated with: {:?}", e);
                         }
                     }
+                    if *shutdown.borrow() {
+                        tracing::info!("Sync stopped");
+                        return Ok(());
+                    }
 
                     let l2_head = tokio::task::block_in_place(|| {
                         StarknetBlocksTable::get(&db_conn, StarknetBlocksBlockId::Latest)
@@ -285,7 +356,11 @@ pub async fn sync(
                     let (new_tx, new_rx) = mpsc::channel(1);
                     rx_l2 = new_rx;
 
-                    l2_handle = tokio::spawn(l2::sync(new_tx, sequencer.clone(), l2_head));
+                    l2_handle = tokio::spawn(l2::sync(
+                        new_tx,
+                        sequencer.clone(),
+                        l2_head,
+                    ));
                     tracing::info!("L2 sync process restarted.");
                 }
             }
@@ -293,6 +368,22 @@ pub async fn sync(
     }
 }
 
+/// Stands in for a sync process which is not run, holding `tx` open until `shutdown`.
+async fn idle<T>(tx: mpsc::Sender<T>, shutdown: watch::Receiver<bool>) -> anyhow::Result<()> {
+    stop_requested(shutdown).await;
+    drop(tx);
+    Ok(())
+}
+
+/// Resolves once `shutdown` is set to `true`, or its sender is dropped.
+async fn stop_requested(mut shutdown: watch::Receiver<bool>) {
+    while !*shutdown.borrow() {
+        if shutdown.changed().await.is_err() {
+            return;
+        }
+    }
+}
+
 /// Periodically updates sync state with the latest block height.
 async fn update_sync_status_latest(
     state: Arc<State>,
@@ -387,6 +478,36 @@ async fn l1_update(connection: &mut Connection, updates: &[StateUpdateLog]) -> a
     })
 }
 
+/// Discards the L1 state updates logged in the last `reorg_buffer` Ethereum blocks up to and
+/// including the block of `head`, so that L1 sync fetches them again. Returns the new L1 head.
+async fn rewind_l1_head(
+    connection: &mut Connection,
+    head: &StateUpdateLog,
+    reorg_buffer: u64,
+) -> anyhow::Result<Option<StateUpdateLog>> {
+    let first_ethereum_block =
+        EthereumBlockNumber((head.origin.block.number.0 + 1).saturating_sub(reorg_buffer));
+    let reorg_tail = tokio::task::block_in_place(|| {
+        L1StateTable::first_since(connection, first_ethereum_block)
+            .context("Query L1 state from database")
+    })?;
+    let reorg_tail = match reorg_tail {
+        Some(tail) => tail,
+        None => return Ok(Some(head.clone())),
+    };
+
+    l1_reorg(connection, reorg_tail)
+        .await
+        .with_context(|| format!("Rewind L1 state to block {}", reorg_tail.0))?;
+
+    match reorg_tail {
+        StarknetBlockNumber::GENESIS => Ok(None),
+        other => tokio::task::block_in_place(|| {
+            L1StateTable::get(connection, (other - 1).into()).context("Query L1 head from database")
+        }),
+    }
+}
+
 async fn l1_reorg(
     connection: &mut Connection,
     reorg_tail: StarknetBlockNumber,
@@ -559,3 +680,100 @@ fn deploy_contract(
     ContractsTable::insert(transaction, contract.address, contract.hash)
         .context("Inserting contract hash into contracts table")
 }
+
+#[cfg(test)]
+mod tests {
+    mod rewind_l1_head {
+        use super::super::rewind_l1_head;
+        use crate::{
+            core::{
+                EthereumBlockHash, EthereumBlockNumber, EthereumLogIndex, EthereumTransactionHash,
+                EthereumTransactionIndex, GlobalRoot, StarknetBlockNumber,
+            },
+            ethereum::{log::StateUpdateLog, BlockOrigin, EthOrigin, TransactionOrigin},
+            storage::{L1StateTable, L1TableBlockId, RefsTable, Storage},
+        };
+        use pedersen::StarkHash;
+        use web3::types::H256;
+
+        /// StarkNet blocks 0..5, logged in Ethereum blocks 100, 100, 101, 105 and 110.
+        fn setup() -> (rusqlite::Connection, StateUpdateLog) {
+            let storage = Storage::in_memory().unwrap();
+            let connection = storage.connection().unwrap();
+
+            let updates = [100, 100, 101, 105, 110]
+                .into_iter()
+                .enumerate()
+                .map(|(i, ethereum_block)| StateUpdateLog {
+                    origin: EthOrigin {
+                        block: BlockOrigin {
+                            hash: EthereumBlockHash(H256::from_low_u64_be(ethereum_block)),
+                            number: EthereumBlockNumber(ethereum_block),
+                        },
+                        transaction: TransactionOrigin {
+                            hash: EthereumTransactionHash(H256::from_low_u64_be(i as u64)),
+                            index: EthereumTransactionIndex(0),
+                        },
+                        log_index: EthereumLogIndex(i as u64),
+                    },
+                    global_root: GlobalRoot(StarkHash::from_hex_str(&format!("{:x}", i)).unwrap()),
+                    block_number: StarknetBlockNumber(i as u64),
+                })
+                .collect::<Vec<_>>();
+            for update in &updates {
+                L1StateTable::insert(&connection, update).unwrap();
+            }
+            RefsTable::set_l1_l2_head(&connection, Some(StarknetBlockNumber(4))).unwrap();
+
+            (connection, updates.last().unwrap().clone())
+        }
+
+        fn l1_head(connection: &rusqlite::Connection) -> Option<StarknetBlockNumber> {
+            L1StateTable::get(connection, L1TableBlockId::Latest)
+                .unwrap()
+                .map(|update| update.block_number)
+        }
+
+        #[tokio::test(flavor = "multi_thread")]
+        async fn counts_ethereum_blocks() {
+            let (mut connection, head) = setup();
+
+            // Ethereum blocks 104..=110 hold the updates of StarkNet blocks 3 and 4.
+            let new_head = rewind_l1_head(&mut connection, &head, 7).await.unwrap();
+
+            assert_eq!(
+                new_head.map(|update| update.block_number),
+                Some(StarknetBlockNumber(2))
+            );
+            assert_eq!(l1_head(&connection), Some(StarknetBlockNumber(2)));
+            assert_eq!(
+                RefsTable::get_l1_l2_head(&connection).unwrap(),
+                Some(StarknetBlockNumber(2))
+            );
+        }
+
+        #[tokio::test(flavor = "multi_thread")]
+        async fn only_the_head_block() {
+            let (mut connection, head) = setup();
+
+            let new_head = rewind_l1_head(&mut connection, &head, 1).await.unwrap();
+
+            assert_eq!(
+                new_head.map(|update| update.block_number),
+                Some(StarknetBlockNumber(3))
+            );
+            assert_eq!(l1_head(&connection), Some(StarknetBlockNumber(3)));
+        }
+
+        #[tokio::test(flavor = "multi_thread")]
+        async fn past_genesis() {
+            let (mut connection, head) = setup();
+
+            let new_head = rewind_l1_head(&mut connection, &head, 1000).await.unwrap();
+
+            assert_eq!(new_head, None);
+            assert_eq!(l1_head(&connection), None);
+            assert_eq!(RefsTable::get_l1_l2_head(&connection).unwrap(), None);
+        }
+    }
+}
@@ -11,6 +11,7 @@ use crate::{
         state_update::state_root::StateRootFetcher,
         Chain,
     },
+    retry::RetryBudget,
 };
 
 /// Events and queries emitted by L1 sync process.
@@ -31,19 +32,26 @@ pub enum Event {
 
 /// Syncs L1 state update logs. Emits [sync events](Event) which should be handled
 /// to update storage and respond to queries.
+///
+/// Stops once `stop_flag` completes. Logs which were already fetched at that point are
+/// still emitted, but a fetch in progress is abandoned.
 pub(super) async fn sync(
     tx_event: mpsc::Sender<Event>,
     transport: Web3<Http>,
     chain: Chain,
     head: Option<StateUpdateLog>,
+    retry_decoder_errors: bool,
+    retry_budget: RetryBudget,
+    stop_flag: impl std::future::Future<Output = ()> + Send + 'static,
 ) -> anyhow::Result<()> {
-    let eth_api = EthereumImpl {
-        logs: StateRootFetcher::new(head, chain),
-        transport,
-    };
+    let mut logs = StateRootFetcher::new(head, chain);
+    logs.set_retry_spurious_decoder_errors(retry_decoder_errors);
+    logs.set_retry_budget(retry_budget);
+
+    let eth_api = EthereumImpl { logs, transport };
 
     // The core sync logic implementation.
-    sync_impl(eth_api, tx_event).await
+    sync_impl(eth_api, tx_event, stop_flag).await
 }
 
 #[cfg_attr(test, mockall::automock)]
@@ -137,15 +145,36 @@ impl EventSender {
 async fn sync_impl(
     mut eth_api: impl EthereumApi,
     tx_event: mpsc::Sender<Event>,
+    stop_flag: impl std::future::Future<Output = ()>,
 ) -> anyhow::Result<()> {
+    tokio::pin!(stop_flag);
+
     let event_sender = EventSender(tx_event);
     loop {
-        match eth_api.fetch_logs().await {
+        let fetched = tokio::select! {
+            // Check the stop flag first, so that an endless stream of logs cannot starve it.
+            biased;
+
+            _ = &mut stop_flag => {
+                tracing::debug!("L1 sync stopped");
+                return Ok(());
+            }
+            fetched = eth_api.fetch_logs() => fetched,
+        };
+
+        match fetched {
             Ok(logs) => {
                 // If empty, then we are at head of chain, sleep a bit and try again.
                 if logs.is_empty() {
-                    tokio::time::sleep(Duration::from_secs(5)).await;
-                    continue;
+                    tokio::select! {
+                        biased;
+
+                        _ = &mut stop_flag => {
+                            tracing::debug!("L1 sync stopped");
+                            return Ok(());
+                        }
+                        _ = tokio::time::sleep(Duration::from_secs(5)) => continue,
+                    }
                 }
 
                 // There were log updates, send the event!
@@ -307,7 +336,11 @@ mod tests {
                 .in_sequence(&mut seq)
                 .return_once(|| mock_output);
 
-            tokio::spawn(sync_impl(mock_fetcher, tx_event));
+            tokio::spawn(sync_impl(
+                mock_fetcher,
+                tx_event,
+                futures::future::pending(),
+            ));
 
             match rx_event.recv().await.unwrap() {
                 Event::Update(recv) => assert_eq!(recv, logs1),
@@ -346,7 +379,11 @@ mod tests {
             mock_fetcher
                 .expect_fetch_logs()
                 .return_once(move || Ok(logs));
-            let handle = tokio::spawn(sync_impl(mock_fetcher, tx_event));
+            let handle = tokio::spawn(sync_impl(
+                mock_fetcher,
+                tx_event,
+                futures::future::pending(),
+            ));
 
             // Wrap this in a timeout so we don't wait forever in case of test failure.
             tokio::time::timeout(Duration::from_secs(2), handle)
@@ -356,6 +393,101 @@ mod tests {
                 .unwrap();
         }
 
+        mod stop_flag {
+            use super::*;
+
+            fn log(block: u64) -> StateUpdateLog {
+                StateUpdateLog {
+                    origin: EthOrigin {
+                        block: BlockOrigin {
+                            hash: EthereumBlockHash(H256::from_low_u64_be(block)),
+                            number: EthereumBlockNumber(200 + block),
+                        },
+                        transaction: TransactionOrigin {
+                            hash: EthereumTransactionHash(H256::from_low_u64_be(block)),
+                            index: EthereumTransactionIndex(1),
+                        },
+                        log_index: EthereumLogIndex(1),
+                    },
+                    global_root: GlobalRoot(StarkHash::from_hex_str("123").unwrap()),
+                    block_number: StarknetBlockNumber(block),
+                }
+            }
+
+            #[tokio::test]
+            async fn mid_stream() {
+                let (tx_event, mut rx_event) = mpsc::channel(1);
+                let (tx_stop, rx_stop) = oneshot::channel::<()>();
+
+                // An endless stream of updates.
+                let mut mock_fetcher = MockEthereumApi::new();
+                let mut block = 0;
+                mock_fetcher.expect_fetch_logs().returning(move || {
+                    block += 1;
+                    Ok(vec![log(block)])
+                });
+
+                let handle = tokio::spawn(sync_impl(mock_fetcher, tx_event, async move {
+                    let _ = rx_stop.await;
+                }));
+
+                for expected in 1..=2 {
+                    match rx_event.recv().await.unwrap() {
+                        Event::Update(recv) => assert_eq!(recv, vec![log(expected)]),
+                        _other => panic!("Expected Updates event"),
+                    }
+                }
+
+                tx_stop.send(()).unwrap();
+
+                // Updates which were already fetched get flushed, after which the
+                // sync stops and closes the channel.
+                let drain = async {
+                    let mut flushed = 0;
+                    while rx_event.recv().await.is_some() {
+                        flushed += 1;
+                    }
+                    flushed
+                };
+                let flushed = tokio::time::timeout(Duration::from_secs(2), drain)
+                    .await
+                    .unwrap();
+                assert!(flushed <= 2, "Flushed {} updates after stopping", flushed);
+
+                tokio::time::timeout(Duration::from_secs(2), handle)
+                    .await
+                    .unwrap()
+                    .unwrap()
+                    .unwrap();
+            }
+
+            #[tokio::test]
+            async fn at_chain_head() {
+                let (tx_event, _rx_event) = mpsc::channel(1);
+                let (tx_stop, rx_stop) = oneshot::channel::<()>();
+
+                // No new logs, so the sync sleeps between fetches.
+                let mut mock_fetcher = MockEthereumApi::new();
+                mock_fetcher
+                    .expect_fetch_logs()
+                    .returning(|| Ok(Vec::new()));
+
+                let handle = tokio::spawn(sync_impl(mock_fetcher, tx_event, async move {
+                    let _ = rx_stop.await;
+                }));
+
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                tx_stop.send(()).unwrap();
+
+                // Must not wait out the sleep.
+                tokio::time::timeout(Duration::from_secs(2), handle)
+                    .await
+                    .unwrap()
+                    .unwrap()
+                    .unwrap();
+            }
+        }
+
         mod reorg {
             use super::*;
 
@@ -429,7 +561,11 @@ mod tests {
                     .in_sequence(&mut seq)
                     .return_once(move || mock_output);
 
-                tokio::spawn(sync_impl(mock_fetcher, tx_event));
+                tokio::spawn(sync_impl(
+                    mock_fetcher,
+                    tx_event,
+                    futures::future::pending(),
+                ));
 
                 // Receive first log update event.
                 match rx_event.recv().await.unwrap() {
@@ -517,7 +653,11 @@ mod tests {
                     .in_sequence(&mut seq)
                     .return_once(move || mock_output);
 
-                tokio::spawn(sync_impl(mock_fetcher, tx_event));
+                tokio::spawn(sync_impl(
+                    mock_fetcher,
+                    tx_event,
+                    futures::future::pending(),
+                ));
 
                 // Receive the first log update event.
                 match rx_event.recv().await.unwrap() {
@@ -601,7 +741,11 @@ mod tests {
                     .in_sequence(&mut seq)
                     .return_once(move || mock_output);
 
-                tokio::spawn(sync_impl(mock_fetcher, tx_event));
+                tokio::spawn(sync_impl(
+                    mock_fetcher,
+                    tx_event,
+                    futures::future::pending(),
+                ));
 
                 // First log batch event.
                 match rx_event.recv().await.unwrap() {
@@ -16,7 +16,13 @@ use crate::{
             Block,
         },
     },
-    state::{contract_hash::extract_abi_code_hash, CompressedContract},
+    state::{
+        contract_hash::{
+            check_program_data, contract_hash_debug_info, extract_abi_code_hash,
+            log_hash_debug_info, log_hash_mismatch, ParseOptions,
+        },
+        CompressedContract,
+    },
 };
 
 #[derive(Debug, Clone, Copy)]
@@ -323,6 +329,7 @@ async fn download_and_compress_contract(
     // be expensive, so perform in a blocking task.
     let extract = tokio::task::spawn_blocking(move || -> anyhow::Result<_> {
         let (abi, bytecode, hash) = extract_abi_code_hash(&contract_definition)?;
+        check_program_data(&bytecode)?;
         Ok((contract_definition, abi, bytecode, hash))
     });
     let (contract_definition, abi, bytecode, hash) = extract
@@ -330,11 +337,23 @@ async fn download_and_compress_contract(
         .context("Parse contract definition and compute hash")??;
 
     // Sanity check.
-    anyhow::ensure!(
-        contract.contract_hash == hash,
-        "Contract hash mismatch for contract {:?}",
-        contract.address
-    );
+    if contract.contract_hash != hash {
+        log_hash_mismatch(contract.address, contract.contract_hash, hash);
+
+        // The debug info only helps diagnose the mismatch, so failing to compute it is not fatal.
+        let debug_info = tokio::task::spawn_blocking(move || {
+            contract_hash_debug_info(&contract_definition, ParseOptions::default())
+        })
+        .await
+        .context("Compute contract hash debug info")
+        .and_then(|debug_info| debug_info);
+        match debug_info {
+            Ok(debug_info) => log_hash_debug_info(contract.address, &debug_info),
+            Err(e) => tracing::debug!("Failed to compute contract hash debug info: {:?}", e),
+        }
+
+        anyhow::bail!("Contract hash mismatch for contract {:?}", contract.address);
+    }
 
     let compress = tokio::task::spawn_blocking(move || -> anyhow::Result<_> {
         let mut compressor = zstd::bulk::Compressor::new(10).context("Create zstd compressor")?;
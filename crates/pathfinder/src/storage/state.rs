@@ -75,6 +75,27 @@ impl L1StateTable {
         Ok(())
     }
 
+    /// Returns the lowest StarkNet block number whose update was logged in the given Ethereum
+    /// block or later.
+    pub fn first_since(
+        connection: &Connection,
+        ethereum_block: EthereumBlockNumber,
+    ) -> anyhow::Result<Option<StarknetBlockNumber>> {
+        let block_number = connection
+            .query_row(
+                "SELECT starknet_block_number FROM l1_state WHERE ethereum_block_number >= ?
+                ORDER BY starknet_block_number ASC LIMIT 1",
+                params![ethereum_block.0],
+                |row| {
+                    let number = row.get_ref_unwrap(0).as_i64().unwrap() as u64;
+                    Ok(StarknetBlockNumber(number))
+                },
+            )
+            .optional()?;
+
+        Ok(block_number)
+    }
+
     /// Returns the [root](GlobalRoot) of the given block.
     pub fn get_root(
         connection: &Connection,
@@ -885,6 +906,49 @@ mod tests {
             }
         }
 
+        mod first_since {
+            use super::*;
+
+            #[test]
+            fn some() {
+                let storage = Storage::in_memory().unwrap();
+                let connection = storage.connection().unwrap();
+
+                let updates = create_updates();
+                for update in &updates {
+                    L1StateTable::insert(&connection, update).unwrap();
+                }
+
+                for update in &updates {
+                    assert_eq!(
+                        L1StateTable::first_since(&connection, update.origin.block.number).unwrap(),
+                        Some(update.block_number)
+                    );
+                }
+                assert_eq!(
+                    L1StateTable::first_since(&connection, EthereumBlockNumber(0)).unwrap(),
+                    Some(StarknetBlockNumber::GENESIS)
+                );
+            }
+
+            #[test]
+            fn none() {
+                let storage = Storage::in_memory().unwrap();
+                let connection = storage.connection().unwrap();
+
+                let updates = create_updates();
+                for update in &updates {
+                    L1StateTable::insert(&connection, update).unwrap();
+                }
+
+                let after = updates.last().unwrap().origin.block.number.0 + 1;
+                assert_eq!(
+                    L1StateTable::first_since(&connection, EthereumBlockNumber(after)).unwrap(),
+                    None
+                );
+            }
+        }
+
         mod get_root {
             use super::*;
 
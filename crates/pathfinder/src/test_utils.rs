@@ -0,0 +1,184 @@
+//! Helpers shared by the tests of several modules.
+
+use sha3::{Digest, Keccak256};
+use std::path::PathBuf;
+
+/// Downloads the body of `url`, caching it on disk under `target/test-cache` so that repeated
+/// test runs don't have to download large payloads, like contract definitions, again.
+///
+/// Only successful responses are cached. Delete the cache directory to force a new download.
+pub async fn cached_get(url: &str) -> Vec<u8> {
+    let path = cache_path(url);
+
+    if let Ok(body) = std::fs::read(&path) {
+        return body;
+    }
+
+    let body = reqwest::get(url)
+        .await
+        .and_then(|resp| resp.error_for_status())
+        .unwrap_or_else(|e| panic!("GET {}: {}", url, e))
+        .bytes()
+        .await
+        .unwrap_or_else(|e| panic!("Reading body of {}: {}", url, e))
+        .to_vec();
+
+    // Write to a temporary file first, so that concurrently running tests never read a partial
+    // cache entry. Failing to cache is not an error, the body is just downloaded again next time.
+    let _ = std::fs::create_dir_all(cache_dir()).and_then(|_| {
+        let mut file = tempfile::NamedTempFile::new_in(cache_dir())?;
+        std::io::Write::write_all(&mut file, &body)?;
+        file.persist(&path).map_err(|e| e.error)?;
+        Ok(())
+    });
+
+    body
+}
+
+/// Decompresses a fixture, detecting zstd and gzip compression from the magic bytes at its start.
+/// Uncompressed fixtures are returned as is.
+///
+/// This allows using gzip captures next to the zstd compressed fixtures, for example
+/// `decompress_fixture(include_bytes!("../fixtures/contract_definition.json.gz"))`.
+pub fn decompress_fixture(fixture: &[u8]) -> Vec<u8> {
+    const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+    const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+    if fixture.starts_with(&ZSTD_MAGIC) {
+        zstd::decode_all(fixture).expect("Decompressing zstd fixture")
+    } else if fixture.starts_with(&GZIP_MAGIC) {
+        let mut decompressed = Vec::new();
+        std::io::Read::read_to_end(
+            &mut flate2::read::GzDecoder::new(fixture),
+            &mut decompressed,
+        )
+        .expect("Decompressing gzip fixture");
+        decompressed
+    } else {
+        fixture.to_vec()
+    }
+}
+
+/// Starts a local Ethereum JSON-RPC server and returns a transport connected to it.
+///
+/// Every request is answered using `handler`, which returns either the `result` or the `error`
+/// object of the reply. The `jsonrpc` and `id` fields of the reply are filled in.
+pub fn mock_json_rpc<F>(handler: F) -> web3::Web3<web3::transports::Http>
+where
+    F: Fn(&serde_json::Value) -> Result<serde_json::Value, serde_json::Value>
+        + Clone
+        + Send
+        + Sync
+        + 'static,
+{
+    use warp::Filter;
+
+    let rpc = warp::post()
+        .and(warp::body::json())
+        .map(move |request: serde_json::Value| {
+            let reply = match handler(&request) {
+                Ok(result) => serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": request["id"],
+                    "result": result,
+                }),
+                Err(error) => serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": request["id"],
+                    "error": error,
+                }),
+            };
+            warp::reply::json(&reply)
+        });
+    let (addr, run_srv) = warp::serve(rpc).bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(run_srv);
+
+    let url = format!("http://{}/", addr);
+    web3::Web3::new(web3::transports::Http::new(&url).unwrap())
+}
+
+/// The decompressed `fixtures/contract_definition.json.zst` contract definition.
+pub fn contract_definition_fixture() -> Vec<u8> {
+    decompress_fixture(include_bytes!("../fixtures/contract_definition.json.zst"))
+}
+
+/// A minimal contract definition with an empty program, and `entry_points_by_type` inserted as is.
+pub fn minimal_contract(entry_points_by_type: &str) -> Vec<u8> {
+    format!(
+        r#"{{
+            "abi": [],
+            "entry_points_by_type": {},
+            "program": {{
+                "builtins": [],
+                "data": [],
+                "debug_info": null,
+                "hints": {{}},
+                "identifiers": {{}},
+                "main_scope": "__main__",
+                "prime": "0x800000000000011000000000000000000000000000000000000000000000001",
+                "reference_manager": {{"references": []}}
+            }}
+        }}"#,
+        entry_points_by_type
+    )
+    .into_bytes()
+}
+
+fn cache_path(url: &str) -> PathBuf {
+    cache_dir().join(hex::encode(Keccak256::digest(url.as_bytes())))
+}
+
+fn cache_dir() -> PathBuf {
+    let target = std::env::var_os("CARGO_TARGET_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../target"));
+    target.join("test-cache")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cache_path, cached_get, decompress_fixture};
+    use warp::Filter;
+
+    #[test]
+    fn zstd_and_gzip_fixtures_are_equal() {
+        let zstd = decompress_fixture(include_bytes!("../fixtures/contract_definition.json.zst"));
+        let gzip = decompress_fixture(include_bytes!("../fixtures/contract_definition.json.gz"));
+
+        assert_eq!(zstd, gzip);
+        // Sanity check that this is the decompressed definition and not the raw bytes.
+        serde_json::from_slice::<serde_json::Value>(&zstd).unwrap();
+    }
+
+    #[test]
+    fn uncompressed_fixture_is_returned_as_is() {
+        let block = include_bytes!("../fixtures/sequencer/block.json");
+        assert_eq!(decompress_fixture(block), block.to_vec());
+    }
+
+    #[tokio::test]
+    async fn second_get_is_cached() {
+        use std::sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        };
+
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = requests.clone();
+        let reply = warp::any().map(move || {
+            counter.fetch_add(1, Ordering::Relaxed);
+            "cached body"
+        });
+        let (addr, run_srv) = warp::serve(reply).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(run_srv);
+
+        // The port is random, which keeps the url unique between runs.
+        let url = format!("http://{}/cached_get?{}", addr, rand::random::<u64>());
+
+        assert_eq!(cached_get(&url).await, b"cached body");
+        assert_eq!(cached_get(&url).await, b"cached body");
+        assert_eq!(requests.load(Ordering::Relaxed), 1);
+
+        std::fs::remove_file(cache_path(&url)).unwrap();
+    }
+}
@@ -1,5 +1,5 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use pedersen::hash::{pedersen_hash, pedersen_hash_preprocessed, StarkHash};
+use pedersen::hash::{pedersen_hash, pedersen_hash_many, pedersen_hash_preprocessed, StarkHash};
 
 pub fn criterion_benchmark(c: &mut Criterion) {
     // These are the test vectors also used in tests, taken from
@@ -26,6 +26,26 @@ pub fn criterion_benchmark(c: &mut Criterion) {
             black_box(pedersen_hash(e0, e1));
         });
     });
+
+    // A hash chain like the ones of the contract hash, over 1000 elements.
+    let elements = (0..1000u32)
+        .map(|i| StarkHash::from_be_slice(&i.to_be_bytes()).unwrap())
+        .collect::<Vec<_>>();
+    c.bench_function("pedersen_hash_preprocessed_chain_1000", |b| {
+        b.iter(|| {
+            let hash = elements.iter().fold(StarkHash::ZERO, |acc, &e| {
+                pedersen_hash_preprocessed(acc, e)
+            });
+            black_box(hash);
+        });
+    });
+
+    let pairs = elements.iter().map(|&e| (e0, e)).collect::<Vec<_>>();
+    c.bench_function("pedersen_hash_many_1000", |b| {
+        b.iter(|| {
+            black_box(pedersen_hash_many(&pairs));
+        });
+    });
 }
 
 criterion_group!(benches, criterion_benchmark);
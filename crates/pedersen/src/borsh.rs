@@ -0,0 +1,45 @@
+//! Compact binary encoding of [StarkHash] as its fixed 32 big-endian bytes.
+use super::StarkHash;
+use borsh::{BorshDeserialize, BorshSerialize};
+
+impl BorshSerialize for StarkHash {
+    fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        writer.write_all(self.as_be_bytes())
+    }
+}
+
+impl BorshDeserialize for StarkHash {
+    fn deserialize(buf: &mut &[u8]) -> std::io::Result<Self> {
+        let bytes = <[u8; 32]>::deserialize(buf)?;
+        StarkHash::from_be_bytes(bytes)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn round_trip() {
+        let original =
+            StarkHash::from_hex_str("0x1234567890abcdef1234567890abcdef1234567890abcdef").unwrap();
+
+        let bytes = original.try_to_vec().unwrap();
+        assert_eq!(bytes, original.to_be_bytes());
+
+        let decoded = StarkHash::try_from_slice(&bytes).unwrap();
+        assert_eq!(decoded, original);
+    }
+
+    #[test]
+    fn overflow() {
+        assert!(StarkHash::try_from_slice(&[0xff; 32]).is_err());
+    }
+
+    #[test]
+    fn short() {
+        assert!(StarkHash::try_from_slice(&[0x01; 31]).is_err());
+    }
+}
@@ -25,8 +25,8 @@ impl std::fmt::Debug for StarkHash {
 
 impl std::fmt::Display for StarkHash {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        // 0xABCDEF1234567890
-        write!(f, "0x{:X}", self)
+        // 0xabcdef1234567890
+        write!(f, "0x{:x}", self)
     }
 }
 
@@ -116,6 +116,24 @@ impl StarkHash {
         Ok(Self(bytes))
     }
 
+    /// Generates a [StarkHash] uniformly distributed over the field, for use in tests.
+    ///
+    /// Uses rejection sampling: random 252-bit values are drawn until one is below the field
+    /// modulus, which takes two draws on average.
+    #[cfg(any(test, feature = "test-utils"))]
+    pub fn random<R: rand::Rng + ?Sized>(rng: &mut R) -> Self {
+        loop {
+            let mut bytes = [0u8; 32];
+            rng.fill_bytes(&mut bytes);
+            // the modulus is just above 2^251, so keep the 252 least significant bits
+            bytes[0] &= 0x0f;
+
+            if let Ok(hash) = StarkHash::from_be_bytes(bytes) {
+                return hash;
+            }
+        }
+    }
+
     /// Returns `true` if the value of [`StarkHash`] is larger than `2^251 - 1`.
     ///
     /// Every [`StarkHash`] that is used to traverse a Merkle-Patricia Tree
@@ -190,6 +208,17 @@ pub fn pedersen_hash_preprocessed(a: StarkHash, b: StarkHash) -> StarkHash {
     StarkHash::from(result.x)
 }
 
+/// Computes the [default Pedersen hash](crate::pedersen_hash) of each pair, in order.
+///
+/// Currently equivalent to hashing the pairs one by one, but gives callers hashing many
+/// independent pairs a single entry point which can later be parallelized.
+pub fn pedersen_hash_many(pairs: &[(StarkHash, StarkHash)]) -> Vec<StarkHash> {
+    pairs
+        .iter()
+        .map(|&(a, b)| crate::pedersen_hash(a, b))
+        .collect()
+}
+
 impl From<StarkHash> for FieldElement {
     fn from(hash: StarkHash) -> Self {
         debug_assert_eq!(
@@ -212,47 +241,127 @@ impl From<FieldElement> for StarkHash {
     }
 }
 
-impl StarkHash {
-    /// A convenience function which parses a hex string into a [StarkHash].
-    ///
-    /// Supports both upper and lower case hex strings, as well as an
-    /// optional "0x" prefix.
-    pub fn from_hex_str(hex_str: &str) -> Result<Self, HexParseError> {
-        fn parse_hex_digit(digit: u8) -> Result<u8, HexParseError> {
-            match digit {
-                b'0'..=b'9' => Ok(digit - b'0'),
-                b'A'..=b'F' => Ok(digit - b'A' + 10),
-                b'a'..=b'f' => Ok(digit - b'a' + 10),
-                other => Err(HexParseError::InvalidNibble(other)),
-            }
+/// Parses a hex string of at most 64 digits into big-endian bytes, without any range checks.
+fn parse_hex_bytes(hex_str: &str) -> Result<[u8; 32], HexParseError> {
+    fn parse_hex_digit(digit: u8) -> Result<u8, HexParseError> {
+        match digit {
+            b'0'..=b'9' => Ok(digit - b'0'),
+            b'A'..=b'F' => Ok(digit - b'A' + 10),
+            b'a'..=b'f' => Ok(digit - b'a' + 10),
+            other => Err(HexParseError::InvalidNibble(other)),
         }
+    }
 
-        let hex_str = hex_str.strip_prefix("0x").unwrap_or(hex_str);
-        if hex_str.len() > 64 {
-            return Err(HexParseError::InvalidLength(hex_str.len()));
-        }
+    let hex_str = hex_str
+        .strip_prefix("0x")
+        .or_else(|| hex_str.strip_prefix("0X"))
+        .unwrap_or(hex_str);
+    if hex_str.len() > 64 {
+        return Err(HexParseError::InvalidLength(hex_str.len()));
+    }
 
-        let mut buf = [0u8; 32];
+    let mut buf = [0u8; 32];
 
-        // We want the result in big-endian so reverse iterate over each pair of nibbles.
-        let chunks = hex_str.as_bytes().rchunks_exact(2);
+    // We want the result in big-endian so reverse iterate over each pair of nibbles.
+    let chunks = hex_str.as_bytes().rchunks_exact(2);
 
-        // Handle a possible odd nibble remaining nibble.
-        let odd_nibble = chunks.remainder();
-        if !odd_nibble.is_empty() {
-            let full_bytes = hex_str.len() / 2;
-            buf[31 - full_bytes] = parse_hex_digit(odd_nibble[0])?;
-        }
+    // Handle a possible odd nibble remaining nibble.
+    let odd_nibble = chunks.remainder();
+    if !odd_nibble.is_empty() {
+        let full_bytes = hex_str.len() / 2;
+        buf[31 - full_bytes] = parse_hex_digit(odd_nibble[0])?;
+    }
+
+    for (i, c) in chunks.enumerate() {
+        // Indexing c[0] and c[1] are safe since chunk-size is 2.
+        buf[31 - i] = parse_hex_digit(c[0])? << 4 | parse_hex_digit(c[1])?;
+    }
 
-        for (i, c) in chunks.enumerate() {
-            // Indexing c[0] and c[1] are safe since chunk-size is 2.
-            buf[31 - i] = parse_hex_digit(c[0])? << 4 | parse_hex_digit(c[1])?;
+    Ok(buf)
+}
+
+/// The modulus [StarkHash] values are checked against by
+/// [StarkHash::from_be_bytes_with_modulus] and [StarkHash::from_hex_str_with_modulus].
+///
+/// Defaults to the standard [stark prime](Modulus::STARK). Experimental devnets may use a smaller
+/// prime, which can be given as a custom modulus. A custom modulus may not exceed the stark prime,
+/// as every [StarkHash] must remain a valid element of the field used for hashing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Modulus([u8; 32]);
+
+impl Modulus {
+    /// The stark prime `2^251 + 17 * 2^192 + 1`.
+    pub const STARK: Modulus = Modulus([
+        0x08, 0, 0, 0, 0, 0, 0, 0x11, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0x01,
+    ]);
+
+    /// Creates a custom [Modulus] from big-endian bytes.
+    ///
+    /// Returns [OverflowError] if the modulus is larger than [Modulus::STARK], or zero.
+    pub fn from_be_bytes(bytes: [u8; 32]) -> Result<Self, OverflowError> {
+        if bytes > Self::STARK.0 || bytes == [0u8; 32] {
+            return Err(OverflowError);
         }
+        Ok(Self(bytes))
+    }
 
+    /// Creates a custom [Modulus] from a hex string, such as the `program.prime` of a contract
+    /// definition.
+    pub fn from_hex_str(hex_str: &str) -> Result<Self, HexParseError> {
+        let bytes = parse_hex_bytes(hex_str)?;
+        Ok(Self::from_be_bytes(bytes)?)
+    }
+
+    /// Big-endian representation of this [Modulus].
+    pub fn to_be_bytes(self) -> [u8; 32] {
+        self.0
+    }
+}
+
+impl Default for Modulus {
+    fn default() -> Self {
+        Self::STARK
+    }
+}
+
+impl StarkHash {
+    /// A convenience function which parses a hex string into a [StarkHash].
+    ///
+    /// Supports both upper and lower case hex strings, as well as an
+    /// optional "0x" prefix.
+    pub fn from_hex_str(hex_str: &str) -> Result<Self, HexParseError> {
+        let buf = parse_hex_bytes(hex_str)?;
         let hash = StarkHash::from_be_bytes(buf)?;
         Ok(hash)
     }
 
+    /// Creates a [StarkHash] from big-endian bytes, which must be less than `modulus`.
+    ///
+    /// With [Modulus::STARK] this is the same as [StarkHash::from_be_bytes].
+    pub fn from_be_bytes_with_modulus(
+        bytes: [u8; 32],
+        modulus: &Modulus,
+    ) -> Result<Self, OverflowError> {
+        // big-endian byte arrays compare like the numbers they represent; as the modulus is at
+        // most the stark prime this also implies a valid field element
+        if bytes < modulus.0 {
+            Ok(Self(bytes))
+        } else {
+            Err(OverflowError)
+        }
+    }
+
+    /// [StarkHash::from_hex_str] checking the value against `modulus` instead of the stark prime.
+    pub fn from_hex_str_with_modulus(
+        hex_str: &str,
+        modulus: &Modulus,
+    ) -> Result<Self, HexParseError> {
+        let buf = parse_hex_bytes(hex_str)?;
+        let hash = StarkHash::from_be_bytes_with_modulus(buf, modulus)?;
+        Ok(hash)
+    }
+
     /// A convenience function which produces a "0x" prefixed hex string from a [StarkHash].
     pub fn to_hex_str(&self) -> String {
         if self == &StarkHash::ZERO {
@@ -282,6 +391,30 @@ impl StarkHash {
         // Unwrap is safe as the buffer contains valid utf8
         String::from_utf8(buf).unwrap()
     }
+
+    /// A convenience function which produces a decimal string from a [StarkHash].
+    pub fn to_dec_str(&self) -> String {
+        // Repeatedly divide the big-endian bytes by 10, collecting the remainders as digits.
+        let mut bytes = self.0;
+        let mut digits = Vec::new();
+        loop {
+            let mut remainder = 0u16;
+            for byte in bytes.iter_mut() {
+                let acc = remainder << 8 | *byte as u16;
+                *byte = (acc / 10) as u8;
+                remainder = acc % 10;
+            }
+            digits.push(b'0' + remainder as u8);
+
+            if bytes.iter().all(|b| *b == 0) {
+                break;
+            }
+        }
+        digits.reverse();
+
+        // Unwrap is safe as the buffer contains valid utf8
+        String::from_utf8(digits).unwrap()
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -371,6 +504,35 @@ mod tests {
         assert_eq!(hash, expected);
     }
 
+    #[test]
+    fn implementations_agree() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let max = StarkHash::from_be_bytes({
+            let mut max = MODULUS;
+            max[31] -= 1;
+            max
+        })
+        .unwrap();
+        let one = StarkHash::from_be_slice(&[1]).unwrap();
+        // sets the bits above 248 which are multiplied with P2 and P4
+        let high_bits = StarkHash::from_hex_str(&format!("0x7{}", "f".repeat(62))).unwrap();
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let inputs = [StarkHash::ZERO, one, high_bits, max]
+            .into_iter()
+            .chain((0..8).map(|_| StarkHash::random(&mut rng)))
+            .collect::<Vec<_>>();
+
+        for &a in &inputs {
+            for &b in &inputs {
+                let computed = pedersen_hash(a, b);
+                assert_eq!(pedersen_hash_preprocessed(a, b), computed, "{} {}", a, b);
+                assert_eq!(crate::pedersen_hash(a, b), computed, "{} {}", a, b);
+            }
+        }
+    }
+
     #[test]
     fn bytes_round_trip() {
         let original = [
@@ -389,6 +551,65 @@ mod tests {
         0, 1,
     ];
 
+    #[test]
+    fn hash_many() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let pairs = (0..16)
+            .map(|_| (StarkHash::random(&mut rng), StarkHash::random(&mut rng)))
+            .collect::<Vec<_>>();
+
+        let expected = pairs
+            .iter()
+            .map(|&(a, b)| pedersen_hash(a, b))
+            .collect::<Vec<_>>();
+
+        assert_eq!(pedersen_hash_many(&pairs), expected);
+        assert_eq!(pedersen_hash_many(&[]), Vec::<StarkHash>::new());
+    }
+
+    mod random {
+        use super::*;
+        use rand::{rngs::StdRng, SeedableRng};
+
+        #[test]
+        fn below_modulus() {
+            let mut rng = StdRng::seed_from_u64(0);
+            for _ in 0..10_000 {
+                let hash = StarkHash::random(&mut rng);
+                assert!(hash.to_be_bytes() < MODULUS, "{}", hash);
+            }
+        }
+
+        #[test]
+        fn deterministic_with_seed() {
+            let mut a = StdRng::seed_from_u64(1234);
+            let mut b = StdRng::seed_from_u64(1234);
+
+            let a = (0..100)
+                .map(|_| StarkHash::random(&mut a))
+                .collect::<Vec<_>>();
+            let b = (0..100)
+                .map(|_| StarkHash::random(&mut b))
+                .collect::<Vec<_>>();
+
+            assert_eq!(a, b);
+        }
+
+        #[test]
+        fn covers_high_values() {
+            // Values above 2^251 make up a tiny fraction of the field, but values with the top
+            // bits set should show up often.
+            let mut rng = StdRng::seed_from_u64(0);
+            let high = (0..1000)
+                .map(|_| StarkHash::random(&mut rng))
+                .filter(|hash| hash.to_be_bytes()[0] >= 0x04)
+                .count();
+            assert!(high > 0);
+        }
+    }
+
     #[test]
     fn from_bytes_overflow() {
         // Field modulus
@@ -412,6 +633,24 @@ mod tests {
         assert_eq!(hash, original);
     }
 
+    #[test]
+    fn map_key() {
+        use std::collections::HashMap;
+
+        let one = StarkHash::from_be_slice(&[1]).unwrap();
+        let two = StarkHash::from_be_slice(&[2]).unwrap();
+
+        let mut map = HashMap::new();
+        map.insert(one, "one");
+        map.insert(two, "two");
+        map.insert(StarkHash::from_hex_str("0x1").unwrap(), "uno");
+
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get(&one), Some(&"uno"));
+        assert_eq!(map.get(&two), Some(&"two"));
+        assert_eq!(map.get(&StarkHash::ZERO), None);
+    }
+
     mod from_be_slice {
         use super::*;
         use pretty_assertions::assert_eq;
@@ -465,11 +704,23 @@ mod tests {
             let starkhash = StarkHash::from_hex_str(hex_str).unwrap();
             let result = format!("{:?}", starkhash);
 
-            let mut expected = "0".repeat(64 - hex_str.len());
-            expected.push_str(hex_str);
-            let expected = format!("StarkHash({})", starkhash);
+            assert_eq!(
+                result,
+                "StarkHash(0x0000000000000000000000000000001234567890abcdef000edcba0987654321)"
+            );
+        }
 
-            assert_eq!(result, expected);
+        #[test]
+        fn debug_of_wrapper() {
+            #[derive(Debug)]
+            #[allow(dead_code)]
+            struct Wrapper(StarkHash);
+
+            let starkhash = StarkHash::from_hex_str("0xABCDEF").unwrap();
+            assert_eq!(
+                format!("{:?}", Wrapper(starkhash)),
+                "Wrapper(StarkHash(0x0000000000000000000000000000000000000000000000000000000000abcdef))"
+            );
         }
 
         #[test]
@@ -485,6 +736,16 @@ mod tests {
             assert_eq!(result.to_lowercase(), expected.to_lowercase());
         }
 
+        #[test]
+        fn display_is_lowercase() {
+            let starkhash = StarkHash::from_hex_str("0xABCDEF").unwrap();
+            assert_eq!(
+                starkhash.to_string(),
+                "0x0000000000000000000000000000000000000000000000000000000000abcdef"
+            );
+            assert_eq!(starkhash.to_hex_str(), "0xabcdef");
+        }
+
         #[test]
         fn lower_hex() {
             let hex_str = "1234567890abcdef000edcba0987654321";
@@ -510,6 +771,80 @@ mod tests {
         }
     }
 
+    mod modulus {
+        use super::*;
+        use assert_matches::assert_matches;
+
+        /// A small prime, 65521.
+        fn small() -> Modulus {
+            Modulus::from_hex_str("0xfff1").unwrap()
+        }
+
+        #[test]
+        fn stark_is_default() {
+            assert_eq!(Modulus::default(), Modulus::STARK);
+            assert_eq!(Modulus::STARK.to_be_bytes(), MODULUS);
+        }
+
+        #[test]
+        fn stark_matches_from_be_bytes() {
+            let mut max_val = MODULUS;
+            max_val[31] -= 1;
+
+            for bytes in [[0u8; 32], max_val, MODULUS, [0xff; 32]] {
+                assert_eq!(
+                    StarkHash::from_be_bytes_with_modulus(bytes, &Modulus::STARK),
+                    StarkHash::from_be_bytes(bytes)
+                );
+            }
+        }
+
+        #[test]
+        fn small_bytes() {
+            let mut bytes = [0u8; 32];
+            bytes[30] = 0xff;
+            bytes[31] = 0xf0;
+            StarkHash::from_be_bytes_with_modulus(bytes, &small()).unwrap();
+
+            bytes[31] = 0xf1;
+            assert_eq!(
+                StarkHash::from_be_bytes_with_modulus(bytes, &small()),
+                Err(OverflowError)
+            );
+        }
+
+        #[test]
+        fn small_hex_str() {
+            assert_eq!(
+                StarkHash::from_hex_str_with_modulus("0xfff0", &small()).unwrap(),
+                StarkHash::from_hex_str("0xfff0").unwrap()
+            );
+            assert_matches!(
+                StarkHash::from_hex_str_with_modulus("0xfff1", &small()),
+                Err(HexParseError::Overflow)
+            );
+            // Valid under the stark prime, but not under the custom one.
+            assert_matches!(
+                StarkHash::from_hex_str_with_modulus("0x10000", &small()),
+                Err(HexParseError::Overflow)
+            );
+        }
+
+        #[test]
+        fn larger_than_stark_is_rejected() {
+            assert_eq!(Modulus::from_be_bytes([0xff; 32]), Err(OverflowError));
+            let mut above = MODULUS;
+            above[31] += 1;
+            assert_eq!(Modulus::from_be_bytes(above), Err(OverflowError));
+            assert_eq!(Modulus::from_be_bytes(MODULUS), Ok(Modulus::STARK));
+        }
+
+        #[test]
+        fn zero_is_rejected() {
+            assert_eq!(Modulus::from_be_bytes([0u8; 32]), Err(OverflowError));
+        }
+    }
+
     mod from_hex_str {
         use super::*;
         use assert_matches::assert_matches;
@@ -548,6 +883,23 @@ mod tests {
             assert_eq!(uut, expected);
         }
 
+        #[test]
+        fn uppercase_prefix() {
+            let (test_str, expected) = test_data();
+            let uut = StarkHash::from_hex_str(&format!("0X{}", test_str)).unwrap();
+            assert_eq!(uut, expected);
+        }
+
+        #[test]
+        fn case_insensitive() {
+            let (test_str, _) = test_data();
+            let lower = StarkHash::from_hex_str(&test_str.to_lowercase()).unwrap();
+            let upper = StarkHash::from_hex_str(&test_str.to_uppercase()).unwrap();
+            assert_eq!(lower, upper);
+            assert_eq!(lower.to_hex_str(), upper.to_hex_str());
+            assert_eq!(upper.to_hex_str(), format!("0x{}", test_str.to_lowercase()));
+        }
+
         #[test]
         fn leading_zeros() {
             let (test_str, expected) = test_data();
@@ -619,6 +971,34 @@ mod tests {
         }
     }
 
+    mod to_dec_str {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        #[test]
+        fn zero() {
+            assert_eq!(StarkHash::ZERO.to_dec_str(), "0");
+        }
+
+        #[test]
+        fn odd() {
+            let hash = StarkHash::from_hex_str("0x1234567890abcde").unwrap();
+            assert_eq!(hash.to_dec_str(), "81985529205931230");
+        }
+
+        #[test]
+        fn max() {
+            let hash = StarkHash::from_hex_str(
+                "0x800000000000011000000000000000000000000000000000000000000000000",
+            )
+            .unwrap();
+            assert_eq!(
+                hash.to_dec_str(),
+                "3618502788666131213697322783095070105623107215331596699973092056135872020480"
+            );
+        }
+    }
+
     mod has_more_than_251_bits {
         use super::*;
 
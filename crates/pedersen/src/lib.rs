@@ -3,9 +3,17 @@ pub mod field;
 pub mod hash;
 pub mod serde;
 
+#[cfg(feature = "borsh")]
+mod borsh;
+
 mod curve_consts;
 mod curve_consts_gen;
 
-pub use hash::{
-    pedersen_hash_preprocessed as pedersen_hash, HexParseError, OverflowError, StarkHash,
-};
+pub use hash::{pedersen_hash_many, HexParseError, Modulus, OverflowError, StarkHash};
+
+#[cfg(feature = "computed-hash")]
+pub use hash::pedersen_hash;
+/// The Pedersen hash implementation used by default: [hash::pedersen_hash_preprocessed], unless
+/// the `computed-hash` feature selects [hash::pedersen_hash].
+#[cfg(not(feature = "computed-hash"))]
+pub use hash::pedersen_hash_preprocessed as pedersen_hash;
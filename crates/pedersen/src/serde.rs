@@ -1,12 +1,37 @@
 use super::StarkHash;
 use serde::{de::Visitor, Deserialize, Serialize};
+use std::cell::Cell;
+
+thread_local! {
+    static DECIMAL: Cell<bool> = Cell::new(false);
+}
+
+/// Runs `f` with every [StarkHash] serialized on the current thread as a decimal string
+/// instead of a "0x" prefixed hex string. Deserialization accepts hex strings only either way.
+pub fn with_decimal_serialization<R>(f: impl FnOnce() -> R) -> R {
+    /// Restores the previous mode, also if `f` panics.
+    struct Restore(bool);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            DECIMAL.with(|decimal| decimal.set(self.0));
+        }
+    }
+
+    let _restore = Restore(DECIMAL.with(|decimal| decimal.replace(true)));
+    f()
+}
 
 impl Serialize for StarkHash {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
-        serializer.serialize_str(&self.to_hex_str())
+        if DECIMAL.with(Cell::get) {
+            serializer.serialize_str(&self.to_dec_str())
+        } else {
+            serializer.serialize_str(&self.to_hex_str())
+        }
     }
 }
 
@@ -96,4 +121,14 @@ mod tests {
             original
         );
     }
+
+    #[test]
+    fn decimal() {
+        let original = StarkHash::from_hex_str(ODD).unwrap();
+        let decimal = with_decimal_serialization(|| serde_json::to_string(&original).unwrap());
+        assert_eq!(decimal, r#""81985529205931230""#);
+        // The mode only applies within the closure.
+        let expected = format!("\"{}\"", ODD);
+        assert_eq!(serde_json::to_string(&original).unwrap(), expected);
+    }
 }